@@ -0,0 +1,22 @@
+create table notification_preferences
+(
+    "user"                  uuid    not null
+        constraint notification_preferences_pk primary key
+        constraint notification_preferences_user_fk references users (id),
+    action_item_assigned    boolean not null default true,
+    question_status_changed boolean not null default true,
+    followed_case_comment   boolean not null default true,
+    overdue_digest          boolean not null default true
+);
+
+create table investigation_followers
+(
+    investigation uuid                     not null
+        constraint investigation_followers_investigation_fk references investigations (id),
+    "user"        uuid                     not null
+        constraint investigation_followers_user_fk references users (id),
+    created       timestamp with time zone not null,
+    constraint investigation_followers_pk primary key (investigation, "user")
+);
+
+create index investigation_followers_user_index on investigation_followers ("user");
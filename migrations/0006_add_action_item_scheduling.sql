@@ -0,0 +1,6 @@
+alter table action_items
+    add column due_date        date,
+    add column priority        text not null default 'normal',
+    add column estimated_hours real;
+
+create index action_items_due_date_index on action_items (due_date);
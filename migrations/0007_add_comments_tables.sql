@@ -0,0 +1,37 @@
+create table comments
+(
+    id            uuid                     not null
+        constraint comments_pk primary key,
+    created       timestamp with time zone not null,
+    author        uuid                     not null
+        constraint comments_author_fk references users (id),
+    investigation uuid                     not null
+        constraint comments_investigation_fk references investigations (id),
+    target        uuid                     not null,
+    target_type   text                     not null,
+    parent        uuid
+        constraint comments_parent_fk references comments (id),
+    body          text                     not null,
+    edited        timestamp with time zone
+);
+
+create index comments_investigation_index on comments (investigation);
+
+create index comments_target_index on comments (target);
+
+create index comments_parent_index on comments (parent);
+
+create table comment_revisions
+(
+    id      uuid                     not null
+        constraint comment_revisions_pk primary key,
+    comment uuid                     not null
+        constraint comment_revisions_comment_fk references comments (id),
+    created timestamp with time zone not null,
+    body    text                     not null
+);
+
+create index comment_revisions_comment_index on comment_revisions (comment);
+
+alter table notification_preferences
+    add column mentioned boolean not null default true;
@@ -0,0 +1,23 @@
+create table attachments
+(
+    id            uuid                     not null
+        constraint attachments_pk primary key,
+    created       timestamp with time zone not null,
+    uploader      uuid                     not null
+        constraint attachments_uploader_fk references users (id),
+    investigation uuid                     not null
+        constraint attachments_investigation_fk references investigations (id),
+    target        uuid                     not null,
+    target_type   text                     not null,
+    filename      text                     not null,
+    mime_type     text                     not null,
+    size          bigint                   not null,
+    sha256        text                     not null,
+    storage_key   text                     not null
+);
+
+create index attachments_investigation_index on attachments (investigation);
+
+create index attachments_target_index on attachments (target);
+
+create index attachments_sha256_index on attachments (sha256);
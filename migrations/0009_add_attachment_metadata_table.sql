@@ -0,0 +1,25 @@
+create table attachment_metadata
+(
+    attachment         uuid             not null
+        constraint attachment_metadata_pk primary key
+        constraint attachment_metadata_attachment_fk references attachments (id),
+    captured           timestamp with time zone,
+    latitude           double precision,
+    longitude          double precision,
+    altitude           double precision,
+    camera_make        text,
+    camera_model       text,
+    exif               jsonb            not null,
+    xmp                text,
+    stripped_key       text,
+    stripped_sha256    text,
+    stripped_mime_type text,
+    thumbnail_key      text,
+    thumbnail_sha256   text
+);
+
+create index attachment_metadata_captured_index on attachment_metadata (captured);
+
+create index attachment_metadata_location_index on attachment_metadata (latitude, longitude);
+
+create index attachment_metadata_exif_index on attachment_metadata using gin (exif);
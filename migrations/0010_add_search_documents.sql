@@ -0,0 +1,153 @@
+-- every searchable record gets one row here, kept in sync by the triggers below, so a single
+-- ranked query can cover investigations, questions, action items and comments together
+create table search_documents
+(
+    target        uuid     not null
+        constraint search_documents_pk primary key,
+    target_type   text     not null,
+    investigation uuid     not null,
+    title         text     not null,
+    body          text     not null,
+    document      tsvector not null
+);
+
+create index search_documents_document_index on search_documents using gin (document);
+
+create index search_documents_investigation_index on search_documents (investigation);
+
+create function search_documents_upsert(_target uuid, _target_type text, _investigation uuid,
+                                        _title text, _body text) returns void as
+$$
+begin
+    insert into search_documents (target, target_type, investigation, title, body, document)
+    values (_target, _target_type, _investigation, _title, _body,
+            setweight(to_tsvector('english', _title), 'A') ||
+            setweight(to_tsvector('english', _body), 'B'))
+    on conflict (target) do update set target_type   = excluded.target_type,
+                                       investigation = excluded.investigation,
+                                       title         = excluded.title,
+                                       body          = excluded.body,
+                                       document      = excluded.document;
+end;
+$$ language plpgsql;
+
+create function search_documents_delete() returns trigger as
+$$
+begin
+    delete from search_documents where target = old.id;
+    return old;
+end;
+$$ language plpgsql;
+
+-- snippets are returned as HTML, so the user-supplied text they're built from is escaped first
+create function search_escape(text) returns text as
+$$
+select replace(replace(replace($1, '&', '&amp;'), '<', '&lt;'), '>', '&gt;')
+$$ language sql immutable;
+
+create function investigations_search() returns trigger as
+$$
+begin
+    perform search_documents_upsert(new.id, 'investigation', new.id,
+                                    concat_ws(' ', new.first_name, new.middle_name, new.last_name,
+                                              new.internal_id, new.namus_id),
+                                    new.synopsis);
+    return new;
+end;
+$$ language plpgsql;
+
+create function questions_search() returns trigger as
+$$
+begin
+    perform search_documents_upsert(new.id, 'question', new.investigation,
+                                    concat_ws(' ', new.pretty_id, new.summary),
+                                    concat_ws(' ', new.details, new.outcome));
+    return new;
+end;
+$$ language plpgsql;
+
+create function action_items_search() returns trigger as
+$$
+begin
+    perform search_documents_upsert(new.id, 'action_item',
+                                    (select investigation from questions where id = new.question),
+                                    concat_ws(' ', new.pretty_id, new.summary),
+                                    concat_ws(' ', new.details, new.outcome));
+    return new;
+end;
+$$ language plpgsql;
+
+create function comments_search() returns trigger as
+$$
+begin
+    perform search_documents_upsert(new.id, 'comment', new.investigation, '', new.body);
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger investigations_search
+    after insert or update
+    on investigations
+    for each row
+execute function investigations_search();
+
+create trigger investigations_search_delete
+    after delete
+    on investigations
+    for each row
+execute function search_documents_delete();
+
+create trigger questions_search
+    after insert or update
+    on questions
+    for each row
+execute function questions_search();
+
+create trigger questions_search_delete
+    after delete
+    on questions
+    for each row
+execute function search_documents_delete();
+
+create trigger action_items_search
+    after insert or update
+    on action_items
+    for each row
+execute function action_items_search();
+
+create trigger action_items_search_delete
+    after delete
+    on action_items
+    for each row
+execute function search_documents_delete();
+
+create trigger comments_search
+    after insert or update
+    on comments
+    for each row
+execute function comments_search();
+
+create trigger comments_search_delete
+    after delete
+    on comments
+    for each row
+execute function search_documents_delete();
+
+-- index everything that already exists
+select search_documents_upsert(id, 'investigation', id,
+                               concat_ws(' ', first_name, middle_name, last_name, internal_id, namus_id),
+                               synopsis)
+from investigations;
+
+select search_documents_upsert(id, 'question', investigation, concat_ws(' ', pretty_id, summary),
+                               concat_ws(' ', details, outcome))
+from questions;
+
+select search_documents_upsert(action_items.id, 'action_item', questions.investigation,
+                               concat_ws(' ', action_items.pretty_id, action_items.summary),
+                               concat_ws(' ', action_items.details, action_items.outcome))
+from action_items
+         join questions on questions.id = action_items.question;
+
+select search_documents_upsert(id, 'comment', investigation, '', body)
+from comments;
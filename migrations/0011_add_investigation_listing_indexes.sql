@@ -0,0 +1,5 @@
+create index investigations_missing_since_index on investigations (missing_since);
+
+create index investigations_date_of_birth_index on investigations (date_of_birth);
+
+create index investigations_creator_index on investigations (creator);
@@ -0,0 +1,28 @@
+create extension if not exists pg_trgm;
+
+create extension if not exists fuzzystrmatch;
+
+alter table investigations
+    add column merged_into uuid
+        constraint investigations_merged_into_fk references investigations (id);
+
+create index investigations_name_trgm_index on investigations using gin (lower(first_name || ' ' || last_name) gin_trgm_ops);
+
+create index investigations_last_name_dmetaphone_index on investigations (dmetaphone(last_name));
+
+-- merged investigations are only kept for their history, and shouldn't show up in search
+create or replace function investigations_search() returns trigger as
+$$
+begin
+    if new.merged_into is not null then
+        delete from search_documents where target = new.id;
+        return new;
+    end if;
+
+    perform search_documents_upsert(new.id, 'investigation', new.id,
+                                    concat_ws(' ', new.first_name, new.middle_name, new.last_name,
+                                              new.internal_id, new.namus_id),
+                                    new.synopsis);
+    return new;
+end;
+$$ language plpgsql;
@@ -0,0 +1,77 @@
+-- other names and dates of birth the subject is known by; any of the identity fields may be
+-- missing (e.g. a nickname has no last name, an alternate DOB has no name at all)
+create table investigation_aliases
+(
+    id            uuid                     not null
+        constraint investigation_aliases_pk primary key,
+    created       timestamp with time zone not null,
+    creator       uuid                     not null
+        constraint investigation_aliases_creator_fk references users (id),
+    investigation uuid                     not null
+        constraint investigation_aliases_investigation_fk references investigations (id),
+    kind          text                     not null,
+    first_name    text,
+    middle_name   text,
+    last_name     text,
+    date_of_birth date,
+    notes         text,
+    constraint investigation_aliases_identity_check
+        check (coalesce(first_name, middle_name, last_name) is not null or date_of_birth is not null)
+);
+
+create index investigation_aliases_investigation_index on investigation_aliases (investigation);
+
+create index investigation_aliases_last_name_dmetaphone_index on investigation_aliases (dmetaphone(last_name));
+
+-- aliases are folded into their investigation's search document, so it's rebuilt from the tables
+-- whenever either side changes
+create function investigations_reindex(_investigation uuid) returns void as
+$$
+begin
+    perform search_documents_upsert(investigations.id, 'investigation', investigations.id,
+                                    concat_ws(' ', investigations.first_name, investigations.middle_name,
+                                              investigations.last_name, investigations.internal_id,
+                                              investigations.namus_id,
+                                              (select string_agg(concat_ws(' ', investigation_aliases.first_name,
+                                                                           investigation_aliases.middle_name,
+                                                                           investigation_aliases.last_name), ' ')
+                                               from investigation_aliases
+                                               where investigation_aliases.investigation = investigations.id)),
+                                    investigations.synopsis)
+    from investigations
+    where investigations.id = _investigation
+      and investigations.merged_into is null;
+end;
+$$ language plpgsql;
+
+create or replace function investigations_search() returns trigger as
+$$
+begin
+    if new.merged_into is not null then
+        delete from search_documents where target = new.id;
+        return new;
+    end if;
+
+    perform investigations_reindex(new.id);
+    return new;
+end;
+$$ language plpgsql;
+
+create function investigation_aliases_search() returns trigger as
+$$
+begin
+    if tg_op in ('UPDATE', 'DELETE') then
+        perform investigations_reindex(old.investigation);
+    end if;
+    if tg_op in ('INSERT', 'UPDATE') then
+        perform investigations_reindex(new.investigation);
+    end if;
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger investigation_aliases_search
+    after insert or update or delete
+    on investigation_aliases
+    for each row
+execute function investigation_aliases_search();
@@ -0,0 +1,47 @@
+-- the structured description of the missing person, one per investigation. Lists that are only
+-- ever read and written as a whole (marks, vehicles, identifiers) are stored as JSON
+create table investigation_profiles
+(
+    investigation          uuid                     not null
+        constraint investigation_profiles_pk primary key
+        constraint investigation_profiles_investigation_fk references investigations (id),
+    version                integer                  not null,
+    updated                timestamp with time zone not null,
+    updated_by             uuid                     not null
+        constraint investigation_profiles_updated_by_fk references users (id),
+    sex                    text,
+    race                   text,
+    height_min_cm          real,
+    height_max_cm          real,
+    weight_min_kg          real,
+    weight_max_kg          real,
+    hair_color             text,
+    eye_color              text,
+    distinguishing_marks   jsonb                    not null default '[]',
+    clothing               text,
+    vehicles               jsonb                    not null default '[]',
+    medical_conditions     text,
+    identifiers            jsonb                    not null default '[]',
+    last_known_address     text,
+    last_known_city        text,
+    last_known_county      text,
+    last_known_state       text,
+    last_known_country     text,
+    last_known_latitude    double precision,
+    last_known_longitude   double precision
+);
+
+create index investigation_profiles_last_known_location_index on investigation_profiles (last_known_latitude, last_known_longitude);
+
+-- a snapshot of each previous version of a profile, saved whenever it's changed
+create table investigation_profile_revisions
+(
+    id            uuid                     not null
+        constraint investigation_profile_revisions_pk primary key,
+    investigation uuid                     not null
+        constraint investigation_profile_revisions_investigation_fk references investigations (id),
+    version       integer                  not null,
+    created       timestamp with time zone not null,
+    data          jsonb                    not null,
+    constraint investigation_profile_revisions_version_unique unique (investigation, version)
+);
@@ -0,0 +1,50 @@
+-- when an event's exact time isn't known, `earliest` and `latest` bound when it could have happened;
+-- for an exact time they're the same
+create table timeline_events
+(
+    id            uuid                     not null
+        constraint timeline_events_pk primary key,
+    created       timestamp with time zone not null,
+    creator       uuid                     not null
+        constraint timeline_events_creator_fk references users (id),
+    investigation uuid                     not null
+        constraint timeline_events_investigation_fk references investigations (id),
+    earliest      timestamp with time zone not null,
+    latest        timestamp with time zone not null,
+    location      text,
+    latitude      double precision,
+    longitude     double precision,
+    description   text                     not null,
+    source        text,
+    confidence    text                     not null default 'unverified',
+    question      uuid
+        constraint timeline_events_question_fk references questions (id),
+    action_item   uuid
+        constraint timeline_events_action_item_fk references action_items (id),
+    constraint timeline_events_range_check check (earliest <= latest)
+);
+
+create index timeline_events_investigation_index on timeline_events (investigation, earliest, latest);
+
+create function timeline_events_search() returns trigger as
+$$
+begin
+    perform search_documents_upsert(new.id, 'timeline_event', new.investigation,
+                                    coalesce(new.location, ''),
+                                    concat_ws(' ', new.description, new.source));
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger timeline_events_search
+    after insert or update
+    on timeline_events
+    for each row
+execute function timeline_events_search();
+
+create trigger timeline_events_search_delete
+    after delete
+    on timeline_events
+    for each row
+execute function search_documents_delete();
+
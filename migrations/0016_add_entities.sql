@@ -0,0 +1,57 @@
+-- people and organizations involved in cases. An entity isn't owned by an investigation, so the
+-- same person or agency can be linked to any number of them
+create table entities
+(
+    id       uuid                     not null
+        constraint entities_pk primary key,
+    created  timestamp with time zone not null,
+    creator  uuid                     not null
+        constraint entities_creator_fk references users (id),
+    kind     text                     not null,
+    name     text                     not null,
+    email    text,
+    phone    text,
+    address  text,
+    website  text,
+    notes    text
+);
+
+create index entities_name_trgm_index on entities using gin (lower(name) gin_trgm_ops);
+
+create table investigation_entities
+(
+    investigation uuid                     not null
+        constraint investigation_entities_investigation_fk references investigations (id),
+    entity        uuid                     not null
+        constraint investigation_entities_entity_fk references entities (id),
+    created       timestamp with time zone not null,
+    creator       uuid                     not null
+        constraint investigation_entities_creator_fk references users (id),
+    role          text                     not null,
+    notes         text,
+    constraint investigation_entities_pk primary key (investigation, entity)
+);
+
+create index investigation_entities_entity_index on investigation_entities (entity);
+
+-- relationships only hold within the investigation they were recorded for. A missing `to_entity`
+-- means the relationship is with the subject of the investigation
+create table entity_relationships
+(
+    id            uuid                     not null
+        constraint entity_relationships_pk primary key,
+    created       timestamp with time zone not null,
+    creator       uuid                     not null
+        constraint entity_relationships_creator_fk references users (id),
+    investigation uuid                     not null
+        constraint entity_relationships_investigation_fk references investigations (id),
+    from_entity   uuid                     not null
+        constraint entity_relationships_from_entity_fk references entities (id),
+    to_entity     uuid
+        constraint entity_relationships_to_entity_fk references entities (id),
+    kind          text                     not null,
+    description   text,
+    constraint entity_relationships_self_check check (from_entity <> to_entity)
+);
+
+create index entity_relationships_investigation_index on entity_relationships (investigation);
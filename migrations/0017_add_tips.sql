@@ -0,0 +1,79 @@
+-- tips are submitted by the public, so nothing here references a user until someone triages them.
+-- Submitters are only identified by a keyed hash of their IP address, which is enough to rate
+-- limit them without keeping the address itself
+create table tips
+(
+    id                    uuid                     not null
+        constraint tips_pk primary key,
+    created               timestamp with time zone not null,
+    investigation         uuid                     not null
+        constraint tips_investigation_fk references investigations (id),
+    status                text                     not null default 'new',
+    body                  text                     not null,
+    contact_name          text,
+    contact_email         text,
+    contact_phone         text,
+    submitter             text                     not null,
+    reviewed_by           uuid
+        constraint tips_reviewed_by_fk references users (id),
+    reviewed              timestamp with time zone,
+    converted_target      uuid,
+    converted_target_type text
+);
+
+create index tips_status_index on tips (status, created);
+
+create index tips_investigation_index on tips (investigation);
+
+create index tips_submitter_index on tips (submitter, created);
+
+create table tip_attachments
+(
+    id          uuid                     not null
+        constraint tip_attachments_pk primary key,
+    created     timestamp with time zone not null,
+    tip         uuid                     not null
+        constraint tip_attachments_tip_fk references tips (id),
+    filename    text                     not null,
+    mime_type   text                     not null,
+    size        bigint                   not null,
+    sha256      text                     not null,
+    storage_key text                     not null
+);
+
+create index tip_attachments_tip_index on tip_attachments (tip);
+
+-- proof-of-work challenges handed out before a tip can be submitted, each good for one tip
+create table tip_challenges
+(
+    id         uuid                     not null
+        constraint tip_challenges_pk primary key,
+    created    timestamp with time zone not null,
+    challenge  text                     not null,
+    difficulty integer                  not null,
+    submitter  text                     not null
+);
+
+create index tip_challenges_submitter_index on tip_challenges (submitter, created);
+
+create function tips_search() returns trigger as
+$$
+begin
+    perform search_documents_upsert(new.id, 'tip', new.investigation,
+                                    concat_ws(' ', new.contact_name, new.contact_email, new.contact_phone),
+                                    new.body);
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger tips_search
+    after insert or update
+    on tips
+    for each row
+execute function tips_search();
+
+create trigger tips_search_delete
+    after delete
+    on tips
+    for each row
+execute function search_documents_delete();
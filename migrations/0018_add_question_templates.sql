@@ -0,0 +1,16 @@
+-- reusable checklists of questions and their action items, copied into an investigation when
+-- they're applied. The questions are only ever read and written as a whole, so they're stored as
+-- JSON, and changing a template never changes investigations it was already applied to
+create table question_templates
+(
+    id          uuid                     not null
+        constraint question_templates_pk primary key,
+    created     timestamp with time zone not null,
+    creator     uuid                     not null
+        constraint question_templates_creator_fk references users (id),
+    updated     timestamp with time zone not null,
+    name        text                     not null
+        constraint question_templates_name_unique unique,
+    description text,
+    questions   jsonb                    not null default '[]'
+);
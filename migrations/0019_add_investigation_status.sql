@@ -0,0 +1,34 @@
+-- where an investigation is in its lifecycle. Closure outcomes and notes are only set while the
+-- investigation is resolved or closed; every transition is kept in investigation_status_changes
+alter table investigations
+    add column status          text                     not null default 'open',
+    add column status_changed  timestamp with time zone,
+    add column closure_outcome text,
+    add column closure_notes   text;
+
+update investigations
+set status_changed = created;
+
+alter table investigations
+    alter column status_changed set not null;
+
+create index investigations_status_index on investigations (status);
+
+create table investigation_status_changes
+(
+    id                 uuid                     not null
+        constraint investigation_status_changes_pk primary key,
+    investigation      uuid                     not null
+        constraint investigation_status_changes_investigation_fk references investigations (id),
+    changed            timestamp with time zone not null,
+    changed_by         uuid                     not null
+        constraint investigation_status_changes_changed_by_fk references users (id),
+    previous_status    text                     not null,
+    status             text                     not null,
+    outcome            text,
+    notes              text,
+    -- how many action items were still open, which takes an override when closing
+    open_action_items  integer                  not null default 0
+);
+
+create index investigation_status_changes_investigation_index on investigation_status_changes (investigation, changed);
@@ -0,0 +1,15 @@
+-- every version of an investigation, question or action item, saved as it's edited so earlier
+-- versions can be compared and restored. Version 1 is the record as it was before its first edit
+create table revisions
+(
+    id          uuid                     not null
+        constraint revisions_pk primary key,
+    target      uuid                     not null,
+    target_type text                     not null,
+    version     integer                  not null,
+    created     timestamp with time zone not null,
+    created_by  uuid                     not null
+        constraint revisions_created_by_fk references users (id),
+    data        jsonb                    not null,
+    constraint revisions_version_unique unique (target_type, target, version)
+);
@@ -0,0 +1,83 @@
+-- announces every change to investigations, questions, action items and comments on the `changes`
+-- channel, so each server instance can push them to its connected clients. Postgres only delivers
+-- the notifications once the transaction commits
+create function notify_change(_target uuid, _target_type text, _investigation uuid,
+                              _operation text) returns void as
+$$
+begin
+    perform pg_notify('changes', json_build_object(
+            'target', _target,
+            'target_type', _target_type,
+            'investigation', _investigation,
+            'action', case _operation
+                          when 'INSERT' then 'created'
+                          when 'UPDATE' then 'updated'
+                          else 'deleted'
+                end)::text);
+end;
+$$ language plpgsql;
+
+create function investigations_notify() returns trigger as
+$$
+declare
+    _row investigations := coalesce(new, old);
+begin
+    perform notify_change(_row.id, 'investigation', _row.id, tg_op);
+    return null;
+end;
+$$ language plpgsql;
+
+create function questions_notify() returns trigger as
+$$
+declare
+    _row questions := coalesce(new, old);
+begin
+    perform notify_change(_row.id, 'question', _row.investigation, tg_op);
+    return null;
+end;
+$$ language plpgsql;
+
+create function action_items_notify() returns trigger as
+$$
+declare
+    _row action_items := coalesce(new, old);
+begin
+    perform notify_change(_row.id, 'action_item',
+                          (select investigation from questions where id = _row.question), tg_op);
+    return null;
+end;
+$$ language plpgsql;
+
+create function comments_notify() returns trigger as
+$$
+declare
+    _row comments := coalesce(new, old);
+begin
+    perform notify_change(_row.id, 'comment', _row.investigation, tg_op);
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger investigations_notify
+    after insert or update or delete
+    on investigations
+    for each row
+execute function investigations_notify();
+
+create trigger questions_notify
+    after insert or update or delete
+    on questions
+    for each row
+execute function questions_notify();
+
+create trigger action_items_notify
+    after insert or update or delete
+    on action_items
+    for each row
+execute function action_items_notify();
+
+create trigger comments_notify
+    after insert or update or delete
+    on comments
+    for each row
+execute function comments_notify();
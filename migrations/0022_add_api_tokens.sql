@@ -0,0 +1,27 @@
+-- service accounts are users that scripts and integrations act as. They have no password, so they
+-- can only authenticate with an API token
+alter table users
+    add column service_account boolean not null default false;
+
+-- bearer tokens for scripting. Only a hash of the token is kept; `hint` is the start of it, so
+-- people can tell their tokens apart. Revoked tokens are kept for the audit log
+create table api_tokens
+(
+    id         uuid                     not null
+        constraint api_tokens_pk primary key,
+    "user"     uuid                     not null
+        constraint api_tokens_user_fk references users (id),
+    name       text                     not null,
+    hash       text                     not null
+        constraint api_tokens_hash_unique unique,
+    hint       text                     not null,
+    scopes     text[]                   not null,
+    created    timestamp with time zone not null,
+    created_by uuid                     not null
+        constraint api_tokens_created_by_fk references users (id),
+    expires    timestamp with time zone not null,
+    last_used  timestamp with time zone,
+    revoked    timestamp with time zone
+);
+
+create index api_tokens_user_index on api_tokens ("user");
@@ -0,0 +1,8 @@
+-- one row per day the overdue digest was sent. Every server instance wakes up to send it, and
+-- whichever claims the day first is the only one that does
+create table overdue_digest_runs
+(
+    day     date                     not null
+        constraint overdue_digest_runs_pk primary key,
+    started timestamp with time zone not null
+);
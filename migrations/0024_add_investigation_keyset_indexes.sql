@@ -0,0 +1,15 @@
+-- keyset pagination orders by the sort column and then id, so each sortable column needs both
+create index investigations_last_name_id_index on investigations (last_name, id);
+
+create index investigations_first_name_id_index on investigations (first_name, id);
+
+create index investigations_created_id_index on investigations (created, id);
+
+create index investigations_missing_since_id_index on investigations (missing_since, id);
+
+create index investigations_date_of_birth_id_index on investigations (date_of_birth, id);
+
+-- range filters on these are served by the indexes above
+drop index investigations_missing_since_index;
+
+drop index investigations_date_of_birth_index;
@@ -0,0 +1,5 @@
+-- who made each service account. Its tokens stop working if that person is deactivated; accounts
+-- made before this was recorded have no creator
+alter table users
+    add column created_by uuid
+        constraint users_created_by_fk references users (id);
@@ -0,0 +1,9 @@
+-- the one definition of an overdue action item for queries: unresolved, with a due date before
+-- today. Keep this in sync with `ActionItem::overdue`
+create function action_item_is_overdue(resolved timestamp with time zone, due_date date, today date)
+    returns boolean
+    language sql
+    immutable
+as $$
+    select resolved is null and coalesce(due_date < today, false)
+$$;
@@ -0,0 +1,10 @@
+-- whether a user can see an investigation and everything in it. For now every active user can see
+-- every investigation that hasn't been merged into another; this is the one place to change that
+create function investigation_visible_to(investigation uuid, viewer uuid)
+    returns boolean
+    language sql
+    stable
+as $$
+    select exists (select 1 from users where id = viewer and enabled)
+        and exists (select 1 from investigations where id = investigation and merged_into is null)
+$$;
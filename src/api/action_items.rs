@@ -0,0 +1,326 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Router,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        investigations::{ActionItem, ActionItemDetails, PRIORITIES, WORK_STATUSES},
+        revisions::{self, Revision, RevisionTarget},
+        spreadsheets::{self, ActionItemFilter, SpreadsheetFormat},
+        users::User,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, ToSchema)]
+pub struct AssignActionItemRequest {
+    assignee: Uuid,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetActionItemStatusRequest {
+    status: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ScheduleActionItemRequest {
+    due_date: Option<NaiveDate>,
+    priority: String,
+    estimated_hours: Option<f32>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MyActionItemsQuery {
+    overdue: Option<bool>,
+    priority: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportActionItemsQuery {
+    #[serde(default)]
+    format: SpreadsheetFormat,
+    investigation: Option<Uuid>,
+    assignee: Option<Uuid>,
+    status: Option<String>,
+    overdue: Option<bool>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(mine, export, edit, assign, set_status, schedule, revisions, restore),
+    components(schemas(SpreadsheetFormat))
+)]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/mine", get(mine))
+        .route("/export", get(export))
+        .route("/:action_item_id", post(edit))
+        .route("/:action_item_id/assign", post(assign))
+        .route("/:action_item_id/status", post(set_status))
+        .route("/:action_item_id/schedule", post(schedule))
+        .route("/:action_item_id/revisions", get(revisions))
+        .route("/:action_item_id/revisions/:version/restore", post(restore))
+}
+
+#[utoipa::path(
+    get,
+    path = "/mine",
+    params(MyActionItemsQuery),
+    responses((status = 200, body = Vec<ActionItem>))
+)]
+pub async fn mine(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Query(query): Query<MyActionItemsQuery>,
+) -> impl IntoResponse {
+    if let Ok(action_items) = ActionItem::get_by_user(State(state), user.id).await {
+        // filtering here keeps the urgency ordering from `get_by_user` intact
+        let action_items = action_items
+            .into_iter()
+            .filter(|a| {
+                query
+                    .overdue
+                    .is_none_or(|overdue| a.is_overdue() == overdue)
+            })
+            .filter(|a| query.priority.as_ref().is_none_or(|p| a.priority == *p))
+            .collect::<Vec<ActionItem>>();
+        return axum::Json(action_items).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/export",
+    params(ExportActionItemsQuery),
+    responses((
+        status = 200,
+        content(
+            ("text/csv"),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        )
+    ))
+)]
+pub async fn export(
+    State(state): State<AppState>,
+    Query(query): Query<ExportActionItemsQuery>,
+) -> impl IntoResponse {
+    let filter = ActionItemFilter {
+        investigation: query.investigation,
+        assignee: query.assignee,
+        status: query.status,
+        overdue: query.overdue,
+    };
+    match spreadsheets::export_action_items(State(state), filter, query.format).await {
+        Ok(data) => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    query.format.content_type().to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"action-items.{}\"",
+                        query.format.extension()
+                    ),
+                ),
+            ],
+            data,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{action_item_id}/assign",
+    params(("action_item_id" = Uuid, Path)),
+    request_body = AssignActionItemRequest,
+    responses((status = 200, body = ActionItem))
+)]
+pub async fn assign(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(action_item_id): Path<Uuid>,
+    Json(req): Json<AssignActionItemRequest>,
+) -> impl IntoResponse {
+    if let Ok(action_item) = ActionItem::get(State(state.clone()), action_item_id).await {
+        if let Ok(updated) = action_item
+            .update(State(state), Extension(user), req.assignee)
+            .await
+        {
+            return axum::Json(updated).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{action_item_id}/status",
+    params(("action_item_id" = Uuid, Path)),
+    request_body = SetActionItemStatusRequest,
+    responses(
+        (status = 200, body = ActionItem),
+        (status = 400, description = "Unknown status")
+    )
+)]
+pub async fn set_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(action_item_id): Path<Uuid>,
+    Json(req): Json<SetActionItemStatusRequest>,
+) -> impl IntoResponse {
+    if !WORK_STATUSES.contains(&req.status.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if let Ok(action_item) = ActionItem::get(State(state.clone()), action_item_id).await {
+        if let Ok(updated) = action_item
+            .set_status(State(state), Extension(user), &req.status)
+            .await
+        {
+            return axum::Json(updated).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{action_item_id}/schedule",
+    params(("action_item_id" = Uuid, Path)),
+    request_body = ScheduleActionItemRequest,
+    responses(
+        (status = 200, body = ActionItem),
+        (status = 400, description = "Unknown priority or negative estimate")
+    )
+)]
+pub async fn schedule(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(action_item_id): Path<Uuid>,
+    Json(req): Json<ScheduleActionItemRequest>,
+) -> impl IntoResponse {
+    if !PRIORITIES.contains(&req.priority.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if req.estimated_hours.is_some_and(|hours| hours < 0.0) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(action_item) = ActionItem::get(State(state.clone()), action_item_id).await {
+        if let Ok(updated) = action_item
+            .set_schedule(
+                State(state),
+                Extension(user),
+                req.due_date,
+                &req.priority,
+                req.estimated_hours,
+            )
+            .await
+        {
+            return axum::Json(updated).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{action_item_id}",
+    params(("action_item_id" = Uuid, Path)),
+    request_body = ActionItemDetails,
+    responses(
+        (status = 200, body = ActionItem),
+        (status = 400, description = "Invalid details"),
+        (status = 404, description = "No such action item")
+    )
+)]
+pub async fn edit(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(action_item_id): Path<Uuid>,
+    Json(req): Json<ActionItemDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(action_item) = ActionItem::get(State(state.clone()), action_item_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match action_item.edit(State(state), Extension(user), req).await {
+        Ok(updated) => axum::Json(updated).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{action_item_id}/revisions",
+    params(("action_item_id" = Uuid, Path)),
+    responses((status = 200, description = "Oldest first", body = Vec<Revision>))
+)]
+pub async fn revisions(
+    State(state): State<AppState>,
+    Path(action_item_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(revisions) =
+        revisions::list(State(state), RevisionTarget::ActionItem, action_item_id).await
+    {
+        return axum::Json(revisions).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{action_item_id}/revisions/{version}/restore",
+    params(("action_item_id" = Uuid, Path), ("version" = i32, Path)),
+    responses(
+        (status = 200, body = ActionItem),
+        (status = 404, description = "No such action item or version")
+    )
+)]
+pub async fn restore(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((action_item_id, version)): Path<(Uuid, i32)>,
+) -> impl IntoResponse {
+    let Ok(action_item) = ActionItem::get(State(state.clone()), action_item_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if revisions::get(
+        State(state.clone()),
+        RevisionTarget::ActionItem,
+        action_item_id,
+        version,
+    )
+    .await
+    .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match action_item
+        .restore(State(state), Extension(user), version)
+        .await
+    {
+        Ok(updated) => axum::Json(updated).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
@@ -1,8 +1,10 @@
+use axum::body::Bytes;
+use axum::extract::DefaultBodyLimit;
 use axum::http::StatusCode;
 use axum::routing::post;
 use axum::Extension;
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Path, State},
     response::IntoResponse,
     Router,
 };
@@ -10,16 +12,63 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
 use serde_json::json;
 use ts_rs::TS;
+use utoipa::{OpenApi, ToSchema};
 use uuid::Uuid;
 
-use crate::core::investigations::ActionItem;
+use crate::core::aliases::AliasDetails;
+use crate::core::bundles::{self, BundleImport};
+use crate::core::duplicates::{self, DuplicateCandidate, MergeError};
+use crate::core::investigations::{ActionItem, CreatedInvestigation, PRIORITIES, WORK_STATUSES};
+use crate::core::namus;
+use crate::core::profiles::ProfileDetails;
+use crate::core::question_templates::QuestionTemplate;
 use crate::core::users::User;
 use crate::{core::helpers::parse_form_date, core::investigations::Investigation, AppState};
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    create,
+    duplicates,
+    merge,
+    import_namus,
+    import_namus_export,
+    import_bundle
+))]
+pub struct ApiDoc;
+
 pub fn router() -> Router<AppState> {
-    Router::new().route("/create", post(create))
+    // bundles carry every attachment in the case, so they get a larger limit than single uploads
+    let max_bundle_mb = std::env::var("MAX_BUNDLE_MB")
+        .ok()
+        .and_then(|mb| mb.parse::<usize>().ok())
+        .unwrap_or(500);
+
+    Router::new()
+        .route("/create", post(create))
+        .route("/duplicates", post(duplicates))
+        .route("/:investigation_id/merge", post(merge))
+        .route("/namus/import", post(import_namus))
+        .route("/namus/:namus_id/import", post(import_namus_export))
+        .route(
+            "/bundle/import",
+            post(import_bundle).layer(DefaultBodyLimit::max(max_bundle_mb * 1024 * 1024)),
+        )
 }
 
-#[derive(Debug, Deserialize, TS)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FindDuplicatesRequest {
+    pub first_name: String,
+    pub last_name: String,
+    pub date_of_birth: NaiveDate,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MergeInvestigationRequest {
+    // the surviving investigation
+    pub into: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct CreateInvestigationDetails {
     pub internal_id: Option<String>,
@@ -34,14 +83,30 @@ pub struct CreateInvestigationDetails {
     // we use `default` here to give us an empty Vec if the field is not present
     #[serde(default)]
     pub questions: Vec<CreateQuestionDetails>,
+    #[serde(default)]
+    pub aliases: Vec<AliasDetails>,
+    pub profile: Option<ProfileDetails>,
+    // question templates to expand into questions alongside any given above
+    #[serde(default)]
+    pub templates: Vec<Uuid>,
+}
+
+impl CreateInvestigationDetails {
+    // question templates are checked separately, since that needs the database
+    pub fn is_valid(&self) -> bool {
+        self.questions.iter().all(|q| q.is_valid())
+            && self.profile.as_ref().is_none_or(|p| p.is_valid())
+            && self.aliases.iter().all(|a| a.is_valid())
+    }
 }
 
-#[derive(Debug, Deserialize, TS)]
+#[derive(Debug, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct CreateQuestionDetails {
     pub pretty_id: String,
     pub summary: String,
     pub details: Option<String>,
+    // ignored when creating an investigation, since it doesn't have an id yet
     pub investigation: Uuid,
     pub outcome: Option<String>,
     pub status: String,
@@ -51,25 +116,253 @@ pub struct CreateQuestionDetails {
     pub action_items: Vec<CreateActionItemDetails>,
 }
 
-#[derive(Debug, Deserialize, TS)]
+impl CreateQuestionDetails {
+    pub fn is_valid(&self) -> bool {
+        let mut pretty_ids = self
+            .action_items
+            .iter()
+            .filter_map(|a| a.pretty_id.as_deref())
+            .collect::<Vec<_>>();
+        pretty_ids.sort();
+        let unique_pretty_ids = pretty_ids.windows(2).all(|w| w[0] != w[1]);
+        unique_pretty_ids && self.action_items.iter().all(|a| a.is_valid())
+    }
+}
+
+#[derive(Debug, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct CreateActionItemDetails {
-    pub pretty_id: String,
+    // numbered after the question's existing action items when not provided
+    #[serde(default)]
+    #[ts(optional)]
+    pub pretty_id: Option<String>,
     pub summary: String,
     pub details: Option<String>,
+    // defaults to "not_started" when assigned, "unassigned" otherwise
+    #[serde(default)]
+    #[ts(optional)]
+    pub status: Option<String>,
     pub outcome: Option<String>,
     pub assignee: Option<Uuid>,
-    pub status: String,
+    // only for completed action items, and defaults to now for those
     pub resolved: Option<DateTime<Utc>>,
+    pub due_date: Option<NaiveDate>,
+    // defaults to "normal" when not provided
+    pub priority: Option<String>,
+    pub estimated_hours: Option<f32>,
 }
 
+impl CreateActionItemDetails {
+    pub fn is_valid(&self) -> bool {
+        let completed = self.status.as_deref() == Some("completed");
+        self.pretty_id
+            .as_deref()
+            .is_none_or(|p| !p.trim().is_empty())
+            && self
+                .status
+                .as_deref()
+                .is_none_or(|s| WORK_STATUSES.contains(&s))
+            && (self.resolved.is_none() || completed)
+            && self
+                .priority
+                .as_deref()
+                .is_none_or(|p| PRIORITIES.contains(&p))
+            && self.estimated_hours.is_none_or(|h| h >= 0.0)
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/create",
+    tag = "admin_investigations",
+    request_body = CreateInvestigationDetails,
+    responses(
+        (status = 200, body = CreatedInvestigation),
+        (status = 400, description = "Invalid details or unknown question template")
+    )
+)]
 pub async fn create(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
     Json(req): Json<CreateInvestigationDetails>,
 ) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    for template in &req.templates {
+        if QuestionTemplate::get(State(state.clone()), *template)
+            .await
+            .is_err()
+        {
+            return (StatusCode::BAD_REQUEST, "Unknown question template").into_response();
+        }
+    }
+
     if let Ok(res) = Investigation::create(State(state), Extension(user), req).await {
         return axum::Json(res).into_response();
     }
     StatusCode::INTERNAL_SERVER_ERROR.into_response()
 }
+
+// lets the front-end warn about likely duplicates before the investigation is created
+#[utoipa::path(
+    post,
+    path = "/duplicates",
+    tag = "admin_investigations",
+    request_body = FindDuplicatesRequest,
+    responses((status = 200, description = "Most likely first", body = Vec<DuplicateCandidate>))
+)]
+pub async fn duplicates(
+    State(state): State<AppState>,
+    Json(req): Json<FindDuplicatesRequest>,
+) -> impl IntoResponse {
+    if let Ok(candidates) = duplicates::find_duplicates(
+        State(state),
+        &req.first_name,
+        &req.last_name,
+        req.date_of_birth,
+        None,
+    )
+    .await
+    {
+        return axum::Json(candidates).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/merge",
+    tag = "admin_investigations",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = MergeInvestigationRequest,
+    responses(
+        (status = 200, description = "Merged into the surviving investigation"),
+        (status = 400, description = "Merging an investigation into itself"),
+        (status = 404, description = "No such investigation"),
+        (status = 409, description = "Already merged, or both investigations have a profile")
+    )
+)]
+pub async fn merge(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<MergeInvestigationRequest>,
+) -> impl IntoResponse {
+    match duplicates::merge(State(state), Extension(user), investigation_id, req.into).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => match e.downcast_ref::<MergeError>() {
+            Some(MergeError::SameInvestigation) => StatusCode::BAD_REQUEST.into_response(),
+            Some(MergeError::NotFound) => StatusCode::NOT_FOUND.into_response(),
+            Some(MergeError::AlreadyMerged | MergeError::ProfilesConflict) => {
+                StatusCode::CONFLICT.into_response()
+            }
+            None => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        },
+    }
+}
+
+// takes a NamUs case export as the request body
+#[utoipa::path(
+    post,
+    path = "/namus/import",
+    tag = "admin_investigations",
+    request_body(description = "A NamUs case export", content(("application/octet-stream"))),
+    responses(
+        (status = 200, body = CreatedInvestigation),
+        (status = 400, description = "Not a NamUs case export, or the case is missing details we need"),
+        (status = 409, description = "The case has already been imported")
+    )
+)]
+pub async fn import_namus(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Ok(case) = namus::parse(&body) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    import(State(state), Extension(user), case).await
+}
+
+// imports a case export that's already been saved to NAMUS_EXPORT_PATH
+#[utoipa::path(
+    post,
+    path = "/namus/{namus_id}/import",
+    tag = "admin_investigations",
+    params(("namus_id" = String, Path)),
+    responses(
+        (status = 200, body = CreatedInvestigation),
+        (status = 400, description = "The case is missing details we need"),
+        (status = 404, description = "No saved export for the case"),
+        (status = 409, description = "The case has already been imported")
+    )
+)]
+pub async fn import_namus_export(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(namus_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(case) = namus::load_export(&namus_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    import(State(state), Extension(user), case).await
+}
+
+async fn import(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    case: namus::NamusCase,
+) -> axum::response::Response {
+    match namus::find_investigation(State(state.clone()), &case.namus_id()).await {
+        Ok(Some(_)) => return StatusCode::CONFLICT.into_response(),
+        Ok(None) => {}
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    // held to the same rules as an investigation entered by hand
+    let Ok(details) = case.to_investigation_details() else {
+        return (StatusCode::BAD_REQUEST, "NamUs case has no date of birth").into_response();
+    };
+    if !details.is_valid() {
+        return (StatusCode::BAD_REQUEST, "NamUs case has invalid details").into_response();
+    }
+
+    match namus::import(State(state), Extension(user), details).await {
+        Ok(res) => axum::Json(res).into_response(),
+        // another import of the same case got there first
+        Err(e) if e.downcast_ref::<namus::ImportError>().is_some() => {
+            StatusCode::CONFLICT.into_response()
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+// takes a case bundle zip as the request body
+#[utoipa::path(
+    post,
+    path = "/bundle/import",
+    tag = "admin_investigations",
+    request_body(description = "A case bundle zip", content(("application/zip"))),
+    responses(
+        (status = 200, body = BundleImport),
+        (status = 400, description = "Not a case bundle"),
+        (status = 422, description = "The bundle failed verification")
+    )
+)]
+pub async fn import_bundle(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let bundle = match bundles::verify(&body) {
+        Ok(Some(bundle)) => bundle,
+        Ok(None) => return StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    if let Ok(res) = bundles::import(State(state), Extension(user), bundle).await {
+        return axum::Json(res).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
@@ -1,11 +1,25 @@
 use crate::AppState;
 use axum::Router;
+use utoipa::OpenApi;
 
 pub mod investigations;
+pub mod question_templates;
+pub mod service_accounts;
 pub mod users;
 
+#[derive(OpenApi)]
+#[openapi(nest(
+    (path = "/users", api = users::ApiDoc),
+    (path = "/investigations", api = investigations::ApiDoc),
+    (path = "/question-templates", api = question_templates::ApiDoc),
+    (path = "/service-accounts", api = service_accounts::ApiDoc)
+))]
+pub struct ApiDoc;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .nest("/users", users::router())
         .nest("/investigations", investigations::router())
+        .nest("/question-templates", question_templates::router())
+        .nest("/service-accounts", service_accounts::router())
 }
@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::post,
+    Extension, Router,
+};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::core::question_templates::{QuestionTemplate, QuestionTemplateDetails};
+use crate::core::users::User;
+use crate::AppState;
+
+#[derive(OpenApi)]
+#[openapi(paths(create, update, delete))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create))
+        .route("/:template_id", post(update).delete(delete))
+}
+
+#[utoipa::path(
+    post,
+    path = "",
+    tag = "admin_question_templates",
+    request_body = QuestionTemplateDetails,
+    responses(
+        (status = 200, body = QuestionTemplate),
+        (status = 400, description = "Invalid details"),
+        (status = 409, description = "A template with that name exists")
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(req): Json<QuestionTemplateDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match QuestionTemplate::find_by_name(State(state.clone()), &req.name).await {
+        Ok(Some(_)) => return StatusCode::CONFLICT.into_response(),
+        Ok(None) => {}
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    match QuestionTemplate::create(State(state), Extension(user), req).await {
+        Ok(template) => axum::Json(template).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{template_id}",
+    tag = "admin_question_templates",
+    params(("template_id" = Uuid, Path)),
+    request_body = QuestionTemplateDetails,
+    responses(
+        (status = 200, body = QuestionTemplate),
+        (status = 400, description = "Invalid details"),
+        (status = 404, description = "No such template"),
+        (status = 409, description = "Another template has that name")
+    )
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(template_id): Path<Uuid>,
+    Json(req): Json<QuestionTemplateDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(template) = QuestionTemplate::get(State(state.clone()), template_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match QuestionTemplate::find_by_name(State(state.clone()), &req.name).await {
+        Ok(Some(other)) if other.id != template.id => return StatusCode::CONFLICT.into_response(),
+        Ok(_) => {}
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    match template.update(State(state), Extension(user), req).await {
+        Ok(template) => axum::Json(template).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{template_id}",
+    tag = "admin_question_templates",
+    params(("template_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "Deleted"),
+        (status = 404, description = "No such template")
+    )
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(template_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Ok(template) = QuestionTemplate::get(State(state.clone()), template_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match template.delete(State(state), Extension(user)).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::core::tokens::{ApiToken, ApiTokenDetails, CreatedApiToken};
+use crate::core::users::{self, User};
+use crate::AppState;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateServiceAccountRequest {
+    name: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list, create, get_tokens, create_token, revoke_token))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list).post(create))
+        .route("/:user_id/tokens", get(get_tokens).post(create_token))
+        .route("/:user_id/tokens/:token_id", delete(revoke_token))
+}
+
+async fn get_service_account(State(state): State<AppState>, user_id: Uuid) -> Option<User> {
+    User::get_by_id(State(state), &user_id.to_string())
+        .await
+        .ok()
+        .filter(|user| user.is_service_account())
+}
+
+#[utoipa::path(get, path = "", tag = "admin_service_accounts", responses((status = 200, body = Vec<User>)))]
+pub async fn list(State(state): State<AppState>) -> impl IntoResponse {
+    if let Ok(accounts) = users::get_service_accounts(&state.db).await {
+        return axum::Json(accounts).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "",
+    tag = "admin_service_accounts",
+    request_body = CreateServiceAccountRequest,
+    responses(
+        (status = 200, body = User),
+        (status = 400, description = "Missing name")
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(req): Json<CreateServiceAccountRequest>,
+) -> impl IntoResponse {
+    if req.name.trim().is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(account) = User::create_service_account(&state.db, user.id, req.name.trim()).await {
+        return axum::Json(account).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{user_id}/tokens",
+    tag = "admin_service_accounts",
+    params(("user_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "Newest first, including expired and revoked tokens", body = Vec<ApiToken>),
+        (status = 404, description = "No such service account")
+    )
+)]
+pub async fn get_tokens(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if get_service_account(State(state.clone()), user_id)
+        .await
+        .is_none()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    if let Ok(tokens) = ApiToken::list_for_user(State(state), user_id).await {
+        return axum::Json(tokens).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{user_id}/tokens",
+    tag = "admin_service_accounts",
+    params(("user_id" = Uuid, Path)),
+    request_body = ApiTokenDetails,
+    responses(
+        (status = 200, description = "The only response that includes the token itself", body = CreatedApiToken),
+        (status = 400, description = "Missing name, unknown scope or invalid expiry"),
+        (status = 404, description = "No such service account")
+    )
+)]
+pub async fn create_token(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<ApiTokenDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if get_service_account(State(state.clone()), user_id)
+        .await
+        .is_none()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match ApiToken::create(State(state), Extension(user), user_id, req).await {
+        Ok(created) => axum::Json(created).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{user_id}/tokens/{token_id}",
+    tag = "admin_service_accounts",
+    params(("user_id" = Uuid, Path), ("token_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = ApiToken),
+        (status = 404, description = "No such token for the service account")
+    )
+)]
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((user_id, token_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let Ok(mut token) = ApiToken::get(State(state.clone()), token_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if token.user != user_id
+        || get_service_account(State(state.clone()), user_id)
+            .await
+            .is_none()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match token.revoke(State(state), Extension(user)).await {
+        Ok(()) => axum::Json(token).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
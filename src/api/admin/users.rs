@@ -7,23 +7,29 @@ use axum::{
 };
 use serde::Deserialize;
 use serde_json::json;
+use utoipa::{OpenApi, ToSchema};
 
 use crate::{
     core::users::{self, User},
     AppState,
 };
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateUserRequest {
     email: String,
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(list, invite))]
+pub struct ApiDoc;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/list", get(list))
         .route("/invite", post(invite))
 }
 
+#[utoipa::path(get, path = "/list", tag = "admin_users", responses((status = 200, body = Vec<User>)))]
 pub async fn list(State(state): State<AppState>) -> impl IntoResponse {
     if let Ok(users) = users::get_all(&state.db).await {
         return axum::Json(users).into_response();
@@ -31,6 +37,13 @@ pub async fn list(State(state): State<AppState>) -> impl IntoResponse {
     StatusCode::INTERNAL_SERVER_ERROR.into_response()
 }
 
+#[utoipa::path(
+    post,
+    path = "/invite",
+    tag = "admin_users",
+    request_body = CreateUserRequest,
+    responses((status = 200, body = User))
+)]
 pub async fn invite(
     State(state): State<AppState>,
     Json(request): Json<CreateUserRequest>,
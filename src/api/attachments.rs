@@ -0,0 +1,350 @@
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Extension, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        attachments::{Attachment, AttachmentMetadata, AttachmentTarget, MetadataSearch},
+        users::User,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AttachmentsQuery {
+    target_type: AttachmentTarget,
+    target: Uuid,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MetadataSearchQuery {
+    investigation: Option<Uuid>,
+    captured_after: Option<DateTime<Utc>>,
+    captured_before: Option<DateTime<Utc>>,
+    has_location: Option<bool>,
+    q: Option<String>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_for_target,
+        upload,
+        search_metadata,
+        get_by_id,
+        download,
+        get_metadata,
+        download_stripped,
+        download_thumbnail
+    ),
+    components(schemas(AttachmentTarget))
+)]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    // TODO: load settings like this into AppState
+    let max_upload_mb = std::env::var("MAX_UPLOAD_MB")
+        .ok()
+        .and_then(|mb| mb.parse::<usize>().ok())
+        .unwrap_or(50);
+
+    Router::new()
+        .route(
+            "/",
+            get(get_for_target)
+                .post(upload)
+                .layer(DefaultBodyLimit::max(max_upload_mb * 1024 * 1024)),
+        )
+        .route("/metadata", get(search_metadata))
+        .route("/:attachment_id", get(get_by_id))
+        .route("/:attachment_id/download", get(download))
+        .route("/:attachment_id/metadata", get(get_metadata))
+        .route("/:attachment_id/stripped", get(download_stripped))
+        .route("/:attachment_id/thumbnail", get(download_thumbnail))
+}
+
+#[utoipa::path(
+    get,
+    path = "/metadata",
+    params(MetadataSearchQuery),
+    responses((status = 200, body = Vec<AttachmentMetadata>))
+)]
+pub async fn search_metadata(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Query(query): Query<MetadataSearchQuery>,
+) -> impl IntoResponse {
+    let search = MetadataSearch {
+        investigation: query.investigation,
+        captured_after: query.captured_after,
+        captured_before: query.captured_before,
+        has_location: query.has_location,
+        text: query.q.filter(|q| !q.trim().is_empty()),
+    };
+    if let Ok(metadata) = Attachment::search_metadata(State(state), user.id, search).await {
+        return axum::Json(metadata).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{attachment_id}/metadata",
+    params(("attachment_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = AttachmentMetadata),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn get_metadata(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(attachment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(attachment) =
+        Attachment::get_for_user(State(state.clone()), attachment_id, user.id).await
+    {
+        if let Ok(metadata) = attachment.get_metadata(State(state)).await {
+            return axum::Json(metadata).into_response();
+        }
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(AttachmentsQuery),
+    responses((status = 200, body = Vec<Attachment>))
+)]
+pub async fn get_for_target(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Query(query): Query<AttachmentsQuery>,
+) -> impl IntoResponse {
+    if let Ok(attachments) =
+        Attachment::get_for_target(State(state), user.id, query.target_type, query.target).await
+    {
+        return axum::Json(attachments).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{attachment_id}",
+    params(("attachment_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Attachment),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(attachment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(attachment) = Attachment::get_for_user(State(state), attachment_id, user.id).await {
+        return axum::Json(attachment).into_response();
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
+
+// expects `target_type` and `target` fields before a single `file` field
+#[utoipa::path(
+    post,
+    path = "",
+    request_body(
+        description = "`target_type` and `target` fields before a single `file` field",
+        content_type = "multipart/form-data"
+    ),
+    responses(
+        (status = 200, body = Attachment),
+        (status = 400, description = "Missing or out of order fields")
+    )
+)]
+pub async fn upload(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut target_type: Option<AttachmentTarget> = None;
+    let mut target: Option<Uuid> = None;
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("target_type") => {
+                let Ok(value) = field.text().await else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+                target_type = AttachmentTarget::parse(&value);
+            }
+            Some("target") => {
+                let Ok(value) = field.text().await else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+                target = Uuid::parse_str(&value).ok();
+            }
+            Some("file") => {
+                let (Some(target_type), Some(target)) = (target_type, target) else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+                let filename = field.file_name().unwrap_or("upload").to_string();
+                let content_type = field.content_type().map(|c| c.to_string());
+                let Ok(data) = field.bytes().await else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+                if let Ok(attachment) = Attachment::create(
+                    State(state),
+                    Extension(user),
+                    target_type,
+                    target,
+                    &filename,
+                    content_type.as_deref(),
+                    data,
+                )
+                .await
+                {
+                    return axum::Json(attachment).into_response();
+                }
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+            _ => {}
+        }
+    }
+    StatusCode::BAD_REQUEST.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{attachment_id}/download",
+    params(("attachment_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "The file as uploaded", content(("application/octet-stream"))),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn download(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(attachment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    // downloads only ever go through here, behind the session layer, so every read is tied to an
+    // authenticated user in the audit log
+    let Ok(attachment) =
+        Attachment::get_for_user(State(state.clone()), attachment_id, user.id).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match attachment.read(State(state), Extension(user)).await {
+        Ok(data) => (
+            [
+                (header::CONTENT_TYPE, attachment.mime_type.clone()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}\"",
+                        sanitize_filename(&attachment.filename)
+                    ),
+                ),
+            ],
+            data,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+// keeps the header value plain ASCII and free of quotes
+pub(crate) fn sanitize_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' | ' ' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+// the stripped copy is the only version of an image that should ever be shared outside the team
+#[utoipa::path(
+    get,
+    path = "/{attachment_id}/stripped",
+    params(("attachment_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "The file without its metadata", content(("application/octet-stream"))),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn download_stripped(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(attachment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Ok(attachment) =
+        Attachment::get_for_user(State(state.clone()), attachment_id, user.id).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match attachment
+        .read_stripped(State(state), Extension(user))
+        .await
+    {
+        Ok((data, mime_type)) => (
+            [
+                (header::CONTENT_TYPE, mime_type),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}\"",
+                        sanitize_filename(&attachment.filename)
+                    ),
+                ),
+            ],
+            data,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{attachment_id}/thumbnail",
+    params(("attachment_id" = Uuid, Path)),
+    responses(
+        (status = 200, content(("image/jpeg"))),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn download_thumbnail(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(attachment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Ok(attachment) =
+        Attachment::get_for_user(State(state.clone()), attachment_id, user.id).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match attachment
+        .read_thumbnail(State(state), Extension(user))
+        .await
+    {
+        Ok(data) => ([(header::CONTENT_TYPE, "image/jpeg")], data).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
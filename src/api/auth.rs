@@ -9,18 +9,19 @@ use axum_extra::extract::{cookie::Cookie, CookieJar, PrivateCookieJar};
 use serde::Deserialize;
 use serde_json::json;
 use tracing::*;
+use utoipa::{OpenApi, ToSchema};
 use uuid::Uuid;
 use zxcvbn::zxcvbn;
 
 use crate::{core::users::User, AppState};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct UserLoginRequest {
     email: String,
     password: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct UserActivateRequest {
     user_id: String,
     display_name: String,
@@ -29,7 +30,7 @@ struct UserActivateRequest {
     otp: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct UserPasswordPostureRequest {
     id: String,
     display_name: String,
@@ -37,6 +38,10 @@ struct UserPasswordPostureRequest {
     confirm: String,
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(login, logout, activate, password_posture))]
+pub struct ApiDoc;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/login", post(login))
@@ -45,6 +50,15 @@ pub fn router() -> Router<AppState> {
         .route("/passwordPosture", post(password_posture))
 }
 
+#[utoipa::path(
+    post,
+    path = "/activate",
+    request_body = UserActivateRequest,
+    responses(
+        (status = 200, description = "Activated"),
+        (status = 401, description = "No such user")
+    )
+)]
 async fn activate(
     State(state): State<AppState>,
     Json(req): Json<UserActivateRequest>,
@@ -62,6 +76,15 @@ async fn activate(
     StatusCode::UNAUTHORIZED
 }
 
+#[utoipa::path(
+    post,
+    path = "/login",
+    request_body = UserLoginRequest,
+    responses(
+        (status = 200, description = "Sets the `session` and `user_details` cookies"),
+        (status = 500, description = "Wrong email or password; clears the cookies")
+    )
+)]
 async fn login(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -91,6 +114,11 @@ async fn login(
     )
 }
 
+#[utoipa::path(
+    get,
+    path = "/logout",
+    responses((status = 307, description = "Ends the session and redirects home"))
+)]
 async fn logout(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -113,6 +141,12 @@ async fn logout(
 
 // TODO: move this to core, to be safe, call it one final time before updating the database
 // TODO: change these to use axum::Json
+#[utoipa::path(
+    post,
+    path = "/passwordPosture",
+    request_body = UserPasswordPostureRequest,
+    responses((status = 200, description = "`valid` and a `reason` when it isn't"))
+)]
 async fn password_posture(
     State(state): State<AppState>,
     Json(req): Json<UserPasswordPostureRequest>,
@@ -0,0 +1,180 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        comments::{Comment, CommentError, CommentRevision, CommentTarget},
+        users::User,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CommentsQuery {
+    target_type: CommentTarget,
+    target: Uuid,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateCommentRequest {
+    target_type: CommentTarget,
+    target: Uuid,
+    parent: Option<Uuid>,
+    body: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditCommentRequest {
+    body: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_for_target, create, get_by_id, edit, revisions))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_for_target).post(create))
+        .route("/:comment_id", get(get_by_id).post(edit))
+        .route("/:comment_id/revisions", get(revisions))
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(CommentsQuery),
+    responses((status = 200, body = Vec<Comment>))
+)]
+pub async fn get_for_target(
+    State(state): State<AppState>,
+    Query(query): Query<CommentsQuery>,
+) -> impl IntoResponse {
+    if let Ok(comments) =
+        Comment::get_for_target(State(state), query.target_type, query.target).await
+    {
+        return axum::Json(comments).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{comment_id}",
+    params(("comment_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Comment),
+        (status = 404, description = "No such comment")
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<AppState>,
+    Path(comment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(comment) = Comment::get(State(state), comment_id).await {
+        return axum::Json(comment).into_response();
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "",
+    request_body = CreateCommentRequest,
+    responses(
+        (status = 200, body = Comment),
+        (status = 400, description = "Empty body, or a parent comment on a different target"),
+        (status = 404, description = "No such target")
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(req): Json<CreateCommentRequest>,
+) -> impl IntoResponse {
+    if req.body.trim().is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    match Comment::create(
+        State(state),
+        Extension(user),
+        req.target_type,
+        req.target,
+        req.parent,
+        &req.body,
+    )
+    .await
+    {
+        Ok(comment) => axum::Json(comment).into_response(),
+        Err(e) => match e.downcast_ref::<CommentError>() {
+            Some(CommentError::TargetNotFound) => StatusCode::NOT_FOUND.into_response(),
+            Some(CommentError::InvalidParent) => StatusCode::BAD_REQUEST.into_response(),
+            None => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        },
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{comment_id}",
+    params(("comment_id" = Uuid, Path)),
+    request_body = EditCommentRequest,
+    responses(
+        (status = 200, body = Comment),
+        (status = 400, description = "Empty body"),
+        (status = 403, description = "Only the author can edit a comment"),
+        (status = 404, description = "No such comment")
+    )
+)]
+pub async fn edit(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(comment_id): Path<Uuid>,
+    Json(req): Json<EditCommentRequest>,
+) -> impl IntoResponse {
+    if req.body.trim().is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let Ok(mut comment) = Comment::get(State(state.clone()), comment_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if comment.author != user.id {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if comment
+        .edit(State(state), Extension(user), &req.body)
+        .await
+        .is_ok()
+    {
+        return axum::Json(comment).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{comment_id}/revisions",
+    params(("comment_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<CommentRevision>))
+)]
+pub async fn revisions(
+    State(state): State<AppState>,
+    Path(comment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(comment) = Comment::get(State(state.clone()), comment_id).await {
+        if let Ok(revisions) = comment.get_revisions(State(state)).await {
+            return axum::Json(revisions).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
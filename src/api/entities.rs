@@ -0,0 +1,146 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi};
+use uuid::Uuid;
+
+use crate::core::entities::{Entity, EntityDetails, EntityInvestigation};
+use crate::core::users::User;
+use crate::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EntitiesQuery {
+    q: Option<String>,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(search, create, get_by_id, update, investigations))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(search).post(create))
+        .route("/:entity_id", get(get_by_id).post(update))
+        .route("/:entity_id/investigations", get(investigations))
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(EntitiesQuery),
+    responses((status = 200, body = Vec<Entity>))
+)]
+pub async fn search(
+    State(state): State<AppState>,
+    Query(query): Query<EntitiesQuery>,
+) -> impl IntoResponse {
+    let q = query
+        .q
+        .as_deref()
+        .map(|q| q.trim())
+        .filter(|q| !q.is_empty());
+    if let Ok(entities) = Entity::search(State(state), q).await {
+        return axum::Json(entities).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "",
+    request_body = EntityDetails,
+    responses(
+        (status = 200, body = Entity),
+        (status = 400, description = "Invalid details")
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(req): Json<EntityDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(entity) = Entity::create(State(state), Extension(user), req).await {
+        return axum::Json(entity).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{entity_id}",
+    params(("entity_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Entity),
+        (status = 404, description = "No such entity")
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<AppState>,
+    Path(entity_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(entity) = Entity::get(State(state), entity_id).await {
+        return axum::Json(entity).into_response();
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{entity_id}",
+    params(("entity_id" = Uuid, Path)),
+    request_body = EntityDetails,
+    responses(
+        (status = 200, body = Entity),
+        (status = 400, description = "Invalid details")
+    )
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(entity_id): Path<Uuid>,
+    Json(req): Json<EntityDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(mut entity) = Entity::get(State(state.clone()), entity_id).await {
+        if entity
+            .update(State(state), Extension(user), req)
+            .await
+            .is_ok()
+        {
+            return axum::Json(entity).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+// every case the entity is involved in
+#[utoipa::path(
+    get,
+    path = "/{entity_id}/investigations",
+    params(("entity_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<EntityInvestigation>))
+)]
+pub async fn investigations(
+    State(state): State<AppState>,
+    Path(entity_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(entity) = Entity::get(State(state.clone()), entity_id).await {
+        if let Ok(investigations) = entity.get_investigations(State(state)).await {
+            return axum::Json(investigations).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
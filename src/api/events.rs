@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Query, State},
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse,
+    },
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::time::{interval_at, Instant};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, IntervalStream},
+    StreamExt,
+};
+use utoipa::{IntoParams, OpenApi};
+use uuid::Uuid;
+
+use crate::{
+    core::{events::Event, sessions::Credential},
+    AppState,
+};
+
+// how often an open stream checks that whoever opened it is still allowed to
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+enum Item {
+    Event(Result<SseEvent, axum::Error>),
+    StillValid(bool),
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventsQuery {
+    // only send changes to this investigation
+    investigation: Option<Uuid>,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(stream))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(stream))
+}
+
+// a Server-Sent Events stream of `change` events. Clients that fall too far behind are sent a
+// `lagged` event with the number they missed, and should refetch what they're showing. The stream
+// ends once the session or token behind it stops being valid, or the user is deactivated
+#[utoipa::path(
+    get,
+    path = "",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "`change` events carrying an `Event`, and `lagged` events with the number missed", body = Event, content_type = "text/event-stream")
+    )
+)]
+pub async fn stream(
+    State(state): State<AppState>,
+    Extension(credential): Extension<Credential>,
+    Query(query): Query<EventsQuery>,
+) -> impl IntoResponse {
+    let events =
+        BroadcastStream::new(state.events.subscribe()).filter_map(move |event| match event {
+            // changes that can't be tied to an investigation go to everyone
+            Ok(event)
+                if query.investigation.is_none_or(|id| {
+                    event
+                        .investigation
+                        .is_none_or(|investigation| investigation == id)
+                }) =>
+            {
+                Some(SseEvent::default().event("change").json_data(event))
+            }
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Ok(SseEvent::default()
+                .event("lagged")
+                .data(missed.to_string()))),
+        });
+
+    let checks = IntervalStream::new(interval_at(
+        Instant::now() + RECHECK_INTERVAL,
+        RECHECK_INTERVAL,
+    ))
+    .then(move |_| {
+        let (state, credential) = (state.clone(), credential.clone());
+        async move { credential.is_valid(State(state)).await }
+    });
+
+    let events = events
+        .map(Item::Event)
+        .merge(checks.map(Item::StillValid))
+        .take_while(|item| !matches!(item, Item::StillValid(false)))
+        .filter_map(|item| match item {
+            Item::Event(event) => Some(event),
+            Item::StillValid(_) => None,
+        });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -0,0 +1,237 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::core::entities::{self, EntityGraph, LinkedEntity, Relationship};
+use crate::core::users::User;
+use crate::AppState;
+
+#[derive(Deserialize, ToSchema)]
+pub struct LinkEntityRequest {
+    entity: Uuid,
+    role: String,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateRelationshipRequest {
+    from_entity: Uuid,
+    // leave out for a relationship with the subject of the investigation
+    to_entity: Option<Uuid>,
+    kind: String,
+    description: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GraphQuery {
+    #[serde(default)]
+    linked_cases: bool,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    get_linked,
+    link,
+    unlink,
+    get_relationships,
+    create_relationship,
+    delete_relationship,
+    graph
+))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_linked).post(link))
+        .route("/:entity_id", delete(unlink))
+        .route(
+            "/relationships",
+            get(get_relationships).post(create_relationship),
+        )
+        .route(
+            "/relationships/:relationship_id",
+            delete(delete_relationship),
+        )
+        .route("/graph", get(graph))
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<LinkedEntity>))
+)]
+pub async fn get_linked(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(linked) = entities::get_linked(State(state), investigation_id).await {
+        return axum::Json(linked).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = LinkEntityRequest,
+    responses(
+        (status = 200, description = "Linked"),
+        (status = 400, description = "Unknown role")
+    )
+)]
+pub async fn link(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<LinkEntityRequest>,
+) -> impl IntoResponse {
+    if !entities::ROLES.contains(&req.role.as_str()) {
+        return StatusCode::BAD_REQUEST;
+    }
+
+    if entities::link(
+        State(state),
+        Extension(user),
+        investigation_id,
+        req.entity,
+        &req.role,
+        req.notes,
+    )
+    .await
+    .is_ok()
+    {
+        return StatusCode::OK;
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{entity_id}",
+    params(("investigation_id" = Uuid, Path), ("entity_id" = Uuid, Path)),
+    responses((status = 200, description = "Unlinked"))
+)]
+pub async fn unlink(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, entity_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if entities::unlink(State(state), Extension(user), investigation_id, entity_id)
+        .await
+        .is_ok()
+    {
+        return StatusCode::OK;
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[utoipa::path(
+    get,
+    path = "/relationships",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<Relationship>))
+)]
+pub async fn get_relationships(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(relationships) =
+        Relationship::get_for_investigation(State(state), investigation_id).await
+    {
+        return axum::Json(relationships).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/relationships",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = CreateRelationshipRequest,
+    responses(
+        (status = 200, body = Relationship),
+        (status = 400, description = "Unknown kind")
+    )
+)]
+pub async fn create_relationship(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<CreateRelationshipRequest>,
+) -> impl IntoResponse {
+    if !entities::RELATIONSHIP_KINDS.contains(&req.kind.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(relationship) = Relationship::create(
+        State(state),
+        Extension(user),
+        investigation_id,
+        req.from_entity,
+        req.to_entity,
+        &req.kind,
+        req.description,
+    )
+    .await
+    {
+        return axum::Json(relationship).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/relationships/{relationship_id}",
+    params(("investigation_id" = Uuid, Path), ("relationship_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "Deleted"),
+        (status = 404, description = "No such relationship on the investigation")
+    )
+)]
+pub async fn delete_relationship(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, relationship_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if let Ok(relationship) = Relationship::get(State(state.clone()), relationship_id).await {
+        if relationship.investigation != investigation_id {
+            return StatusCode::NOT_FOUND;
+        }
+        if relationship
+            .delete(State(state), Extension(user))
+            .await
+            .is_ok()
+        {
+            return StatusCode::OK;
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[utoipa::path(
+    get,
+    path = "/graph",
+    params(("investigation_id" = Uuid, Path), GraphQuery),
+    responses((status = 200, body = EntityGraph))
+)]
+pub async fn graph(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+    Query(query): Query<GraphQuery>,
+) -> impl IntoResponse {
+    if let Ok(graph) = entities::get_graph(State(state), investigation_id, query.linked_cases).await
+    {
+        return axum::Json(graph).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
@@ -0,0 +1,818 @@
+use crate::core::aliases::{Alias, AliasDetails};
+use crate::core::attachments::Attachment;
+use crate::core::bundles;
+use crate::core::flyers::{self, FlyerFormat};
+use crate::core::investigations;
+use crate::core::investigations::{
+    InvalidListQuery, Investigation, InvestigationDetails, InvestigationPage,
+    InvestigationStatusDetails, StatusChange,
+};
+use crate::core::namus::{self, Discrepancy};
+use crate::core::notifications;
+use crate::core::profiles::{Profile, ProfileDetails, ProfileRevision};
+use crate::core::reports::{self, RedactionProfile};
+use crate::core::revisions::{self, Revision, RevisionTarget};
+use crate::core::users::User;
+use crate::AppState;
+use axum::body::Bytes;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Extension, Router};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+pub mod entities;
+pub mod timeline;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InvestigationSort {
+    #[default]
+    LastName,
+    FirstName,
+    Created,
+    MissingSince,
+    DateOfBirth,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportQuery {
+    #[serde(default)]
+    profile: RedactionProfile,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FlyerQuery {
+    #[serde(default)]
+    format: FlyerFormat,
+    template: Option<String>,
+    photo: Option<Uuid>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListInvestigationsQuery {
+    pub creator: Option<Uuid>,
+    pub missing_after: Option<NaiveDate>,
+    pub missing_before: Option<NaiveDate>,
+    pub born_after: Option<NaiveDate>,
+    pub born_before: Option<NaiveDate>,
+    pub min_age: Option<u32>,
+    pub max_age: Option<u32>,
+    pub has_open_questions: Option<bool>,
+    pub internal_id: Option<String>,
+    pub namus_id: Option<String>,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub sort: InvestigationSort,
+    pub descending: Option<bool>,
+    pub after: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_all,
+        get_page,
+        flyer_templates,
+        get_by_id,
+        edit,
+        revisions,
+        restore,
+        follow,
+        unfollow,
+        set_status,
+        status_history,
+        get_aliases,
+        create_alias,
+        update_alias,
+        delete_alias,
+        get_profile,
+        save_profile,
+        profile_revisions,
+        report,
+        bundle,
+        flyer,
+        namus_sync_check,
+        namus_sync_check_upload
+    ),
+    components(schemas(InvestigationSort, RedactionProfile, FlyerFormat)),
+    nest(
+        (path = "/{investigation_id}/entities", api = entities::ApiDoc),
+        (path = "/{investigation_id}/timeline", api = timeline::ApiDoc)
+    )
+)]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_all))
+        .route("/page", get(get_page))
+        .route("/flyer-templates", get(flyer_templates))
+        .route("/:investigation_id", get(get_by_id).post(edit))
+        .route("/:investigation_id/revisions", get(revisions))
+        .route(
+            "/:investigation_id/revisions/:version/restore",
+            post(restore),
+        )
+        .route("/:investigation_id/follow", post(follow).delete(unfollow))
+        .route("/:investigation_id/status", post(set_status))
+        .route("/:investigation_id/status/history", get(status_history))
+        .route(
+            "/:investigation_id/aliases",
+            get(get_aliases).post(create_alias),
+        )
+        .route(
+            "/:investigation_id/profile",
+            get(get_profile).post(save_profile),
+        )
+        .route(
+            "/:investigation_id/profile/revisions",
+            get(profile_revisions),
+        )
+        .route("/:investigation_id/report", get(report))
+        .route("/:investigation_id/bundle", get(bundle))
+        .route("/:investigation_id/flyer", get(flyer))
+        .nest("/:investigation_id/entities", entities::router())
+        .nest("/:investigation_id/timeline", timeline::router())
+        .route(
+            "/:investigation_id/namus",
+            get(namus_sync_check).post(namus_sync_check_upload),
+        )
+        .route(
+            "/:investigation_id/aliases/:alias_id",
+            post(update_alias).delete(delete_alias),
+        )
+}
+
+// `after` and `limit` are ignored here; use `/page` to fetch results a page at a time
+#[utoipa::path(
+    get,
+    path = "",
+    params(ListInvestigationsQuery),
+    responses(
+        (status = 200, body = [Investigation]),
+        (status = 400, description = "Invalid filter")
+    )
+)]
+pub async fn get_all(
+    State(state): State<AppState>,
+    Query(query): Query<ListInvestigationsQuery>,
+) -> impl IntoResponse {
+    match investigations::list_all(State(state.clone()), query).await {
+        Ok(invs) => (StatusCode::OK, json!(invs).to_string()),
+        Err(e) => (list_error_status(&e), "".to_string()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/page",
+    params(ListInvestigationsQuery),
+    responses(
+        (status = 200, body = InvestigationPage),
+        (status = 400, description = "Invalid filter or cursor")
+    )
+)]
+pub async fn get_page(
+    State(state): State<AppState>,
+    Query(query): Query<ListInvestigationsQuery>,
+) -> impl IntoResponse {
+    match investigations::list(State(state.clone()), query).await {
+        Ok(invs) => (StatusCode::OK, json!(invs).to_string()),
+        Err(e) => (list_error_status(&e), "".to_string()),
+    }
+}
+
+fn list_error_status(e: &anyhow::Error) -> StatusCode {
+    match e.downcast_ref::<InvalidListQuery>() {
+        Some(_) => StatusCode::BAD_REQUEST,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, description = "With its questions, aliases and profile", body = Investigation))
+)]
+pub async fn get_by_id(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<String>,
+) -> impl IntoResponse {
+    if let Ok(investigation_id) = Uuid::parse_str(&investigation_id) {
+        if let Ok(investigation) =
+            Investigation::get(State(state.clone()), &investigation_id.to_string(), true).await
+        {
+            return axum::Json(investigation).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = InvestigationDetails,
+    responses(
+        (status = 200, body = Investigation),
+        (status = 400, description = "Invalid details"),
+        (status = 404, description = "No such investigation")
+    )
+)]
+pub async fn edit(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<InvestigationDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(mut investigation) =
+        Investigation::get(State(state.clone()), &investigation_id.to_string(), false).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match investigation.edit(State(state), Extension(user), req).await {
+        Ok(_) => axum::Json(investigation).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/revisions",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, description = "Oldest first", body = Vec<Revision>))
+)]
+pub async fn revisions(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(revisions) = revisions::list(
+        State(state),
+        RevisionTarget::Investigation,
+        investigation_id,
+    )
+    .await
+    {
+        return axum::Json(revisions).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/revisions/{version}/restore",
+    params(("investigation_id" = Uuid, Path), ("version" = i32, Path)),
+    responses(
+        (status = 200, body = Investigation),
+        (status = 404, description = "No such investigation or version")
+    )
+)]
+pub async fn restore(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, version)): Path<(Uuid, i32)>,
+) -> impl IntoResponse {
+    let Ok(mut investigation) =
+        Investigation::get(State(state.clone()), &investigation_id.to_string(), false).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if revisions::get(
+        State(state.clone()),
+        RevisionTarget::Investigation,
+        investigation_id,
+        version,
+    )
+    .await
+    .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match investigation
+        .restore(State(state), Extension(user), version)
+        .await
+    {
+        Ok(_) => axum::Json(investigation).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/report",
+    params(("investigation_id" = Uuid, Path), ReportQuery),
+    responses(
+        (status = 200, content(("application/pdf"))),
+        (status = 404, description = "No such investigation")
+    )
+)]
+pub async fn report(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Query(query): Query<ReportQuery>,
+) -> impl IntoResponse {
+    if Investigation::get(State(state.clone()), &investigation_id.to_string(), false)
+        .await
+        .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match reports::case_report(
+        State(state),
+        Extension(user),
+        investigation_id,
+        query.profile,
+    )
+    .await
+    {
+        Ok(pdf) => (
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"case-report-{}.pdf\"",
+                        investigation_id
+                    ),
+                ),
+            ],
+            pdf,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/bundle",
+    params(("investigation_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "A signed case bundle", content(("application/zip"))),
+        (status = 404, description = "No such investigation")
+    )
+)]
+pub async fn bundle(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if Investigation::get(State(state.clone()), &investigation_id.to_string(), false)
+        .await
+        .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match bundles::export(State(state), Extension(user), investigation_id).await {
+        Ok(bundle) => (
+            [
+                (header::CONTENT_TYPE, "application/zip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"case-{}.zip\"", investigation_id),
+                ),
+            ],
+            bundle,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/status",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = InvestigationStatusDetails,
+    responses(
+        (status = 200, body = Investigation),
+        (status = 400, description = "Invalid or unchanged status"),
+        (status = 404, description = "No such investigation"),
+        (status = 409, description = "Closing would leave action items open; `open_action_items` has the count")
+    )
+)]
+pub async fn set_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<InvestigationStatusDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(mut investigation) =
+        Investigation::get(State(state.clone()), &investigation_id.to_string(), false).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if investigation.status == req.status {
+        return (StatusCode::BAD_REQUEST, "Status is unchanged").into_response();
+    }
+
+    // tell the client how many are open so it can ask whether to close anyway
+    if investigations::is_closed_status(&req.status) && !req.override_open_action_items {
+        match investigation.open_action_items(State(state.clone())).await {
+            Ok(0) => {}
+            Ok(open_action_items) => {
+                return (
+                    StatusCode::CONFLICT,
+                    axum::Json(json!({ "open_action_items": open_action_items })),
+                )
+                    .into_response()
+            }
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+
+    match investigation
+        .set_status(State(state), Extension(user), req)
+        .await
+    {
+        Ok(_) => axum::Json(investigation).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/status/history",
+    params(("investigation_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Vec<StatusChange>),
+        (status = 404, description = "No such investigation")
+    )
+)]
+pub async fn status_history(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Ok(investigation) =
+        Investigation::get(State(state.clone()), &investigation_id.to_string(), false).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Ok(history) = investigation.status_history(State(state)).await {
+        return axum::Json(history).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/flyer-templates",
+    responses((status = 200, body = Vec<String>))
+)]
+pub async fn flyer_templates() -> impl IntoResponse {
+    match flyers::templates() {
+        Ok(templates) => axum::Json(templates).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/flyer",
+    params(("investigation_id" = Uuid, Path), FlyerQuery),
+    responses(
+        (
+            status = 200,
+            content(("application/pdf"), ("image/png"))
+        ),
+        (status = 400, description = "Unknown template or invalid photo"),
+        (status = 404, description = "No such investigation")
+    )
+)]
+pub async fn flyer(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Query(query): Query<FlyerQuery>,
+) -> impl IntoResponse {
+    if Investigation::get(State(state.clone()), &investigation_id.to_string(), false)
+        .await
+        .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    // only templates that exist can be named, which also keeps the name from being used as a path
+    let template = query
+        .template
+        .unwrap_or(flyers::DEFAULT_TEMPLATE.to_string());
+    match flyers::templates() {
+        Ok(templates) if templates.contains(&template) => {}
+        Ok(_) => return (StatusCode::BAD_REQUEST, "Unknown template").into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    // the photo has to be an image attached to this investigation
+    let photo = match query.photo {
+        Some(photo) => match Attachment::get(State(state.clone()), photo).await {
+            Ok(attachment)
+                if attachment.investigation == investigation_id
+                    && attachment.mime_type.starts_with("image/") =>
+            {
+                Some(attachment)
+            }
+            _ => return (StatusCode::BAD_REQUEST, "Invalid photo").into_response(),
+        },
+        None => None,
+    };
+
+    match flyers::flyer(
+        State(state),
+        Extension(user),
+        investigation_id,
+        &template,
+        photo,
+        query.format,
+    )
+    .await
+    {
+        Ok(flyer) => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    query.format.content_type().to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"flyer-{}.{}\"",
+                        investigation_id,
+                        query.format.extension()
+                    ),
+                ),
+            ],
+            flyer,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/follow",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, description = "Following"))
+)]
+pub async fn follow(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if notifications::follow(State(state), investigation_id, user.id)
+        .await
+        .is_ok()
+    {
+        return StatusCode::OK;
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{investigation_id}/follow",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, description = "No longer following"))
+)]
+pub async fn unfollow(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if notifications::unfollow(State(state), investigation_id, user.id)
+        .await
+        .is_ok()
+    {
+        return StatusCode::OK;
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/aliases",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<Alias>))
+)]
+pub async fn get_aliases(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(aliases) = Alias::get_for_investigation(State(state), investigation_id).await {
+        return axum::Json(aliases).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/aliases",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = AliasDetails,
+    responses(
+        (status = 200, body = Alias),
+        (status = 400, description = "Invalid details")
+    )
+)]
+pub async fn create_alias(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<AliasDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(alias) = Alias::create(State(state), Extension(user), investigation_id, req).await {
+        return axum::Json(alias).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/aliases/{alias_id}",
+    params(("investigation_id" = Uuid, Path), ("alias_id" = Uuid, Path)),
+    request_body = AliasDetails,
+    responses(
+        (status = 200, body = Alias),
+        (status = 400, description = "Invalid details"),
+        (status = 404, description = "No such alias on the investigation")
+    )
+)]
+pub async fn update_alias(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, alias_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<AliasDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(mut alias) = Alias::get(State(state.clone()), alias_id).await {
+        if alias.investigation != investigation_id {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        if alias
+            .update(State(state), Extension(user), req)
+            .await
+            .is_ok()
+        {
+            return axum::Json(alias).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{investigation_id}/aliases/{alias_id}",
+    params(("investigation_id" = Uuid, Path), ("alias_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "Deleted"),
+        (status = 404, description = "No such alias on the investigation")
+    )
+)]
+pub async fn delete_alias(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, alias_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if let Ok(alias) = Alias::get(State(state.clone()), alias_id).await {
+        if alias.investigation != investigation_id {
+            return StatusCode::NOT_FOUND;
+        }
+        if alias.delete(State(state), Extension(user)).await.is_ok() {
+            return StatusCode::OK;
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/profile",
+    params(("investigation_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Profile),
+        (status = 404, description = "The investigation has no profile")
+    )
+)]
+pub async fn get_profile(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match Profile::get(State(state), investigation_id).await {
+        Ok(Some(profile)) => axum::Json(profile).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/profile",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = ProfileDetails,
+    responses(
+        (status = 200, body = Profile),
+        (status = 400, description = "Invalid details")
+    )
+)]
+pub async fn save_profile(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<ProfileDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(profile) = Profile::save(State(state), Extension(user), investigation_id, req).await {
+        return axum::Json(profile).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/profile/revisions",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<ProfileRevision>))
+)]
+pub async fn profile_revisions(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(revisions) = Profile::get_revisions(State(state), investigation_id).await {
+        return axum::Json(revisions).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+// compares the investigation against its saved NamUs export
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}/namus",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<Discrepancy>))
+)]
+pub async fn namus_sync_check(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(discrepancies) = namus::sync_check(State(state), investigation_id, None).await {
+        return axum::Json(discrepancies).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+// compares the investigation against a NamUs case export given as the request body
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}/namus",
+    params(("investigation_id" = Uuid, Path)),
+    request_body(description = "A NamUs case export", content(("application/octet-stream"))),
+    responses(
+        (status = 200, body = Vec<Discrepancy>),
+        (status = 400, description = "Not a NamUs case export")
+    )
+)]
+pub async fn namus_sync_check_upload(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Ok(case) = namus::parse(&body) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if let Ok(discrepancies) = namus::sync_check(State(state), investigation_id, Some(case)).await {
+        return axum::Json(discrepancies).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
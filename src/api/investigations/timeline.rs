@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Router,
+};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::core::timeline::{self, ChronologyEntry, TimelineEvent, TimelineEventDetails};
+use crate::core::users::User;
+use crate::AppState;
+
+#[derive(OpenApi)]
+#[openapi(paths(chronology, get_events, create_event, update_event, delete_event))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(chronology))
+        .route("/events", get(get_events).post(create_event))
+        .route("/events/:event_id", post(update_event).delete(delete_event))
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, description = "Oldest first", body = Vec<ChronologyEntry>))
+)]
+pub async fn chronology(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(entries) = timeline::get_chronology(State(state), investigation_id).await {
+        return axum::Json(entries).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    params(("investigation_id" = Uuid, Path)),
+    responses((status = 200, body = Vec<TimelineEvent>))
+)]
+pub async fn get_events(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(events) = TimelineEvent::get_for_investigation(State(state), investigation_id).await {
+        return axum::Json(events).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/events",
+    params(("investigation_id" = Uuid, Path)),
+    request_body = TimelineEventDetails,
+    responses(
+        (status = 200, body = TimelineEvent),
+        (status = 400, description = "Invalid details")
+    )
+)]
+pub async fn create_event(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(investigation_id): Path<Uuid>,
+    Json(req): Json<TimelineEventDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(event) =
+        TimelineEvent::create(State(state), Extension(user), investigation_id, req).await
+    {
+        return axum::Json(event).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/events/{event_id}",
+    params(("investigation_id" = Uuid, Path), ("event_id" = Uuid, Path)),
+    request_body = TimelineEventDetails,
+    responses(
+        (status = 200, body = TimelineEvent),
+        (status = 400, description = "Invalid details"),
+        (status = 404, description = "No such event on the investigation")
+    )
+)]
+pub async fn update_event(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, event_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<TimelineEventDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if let Ok(mut event) = TimelineEvent::get(State(state.clone()), event_id).await {
+        if event.investigation != investigation_id {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        if event
+            .update(State(state), Extension(user), req)
+            .await
+            .is_ok()
+        {
+            return axum::Json(event).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/events/{event_id}",
+    params(("investigation_id" = Uuid, Path), ("event_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "Deleted"),
+        (status = 404, description = "No such event on the investigation")
+    )
+)]
+pub async fn delete_event(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((investigation_id, event_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if let Ok(event) = TimelineEvent::get(State(state.clone()), event_id).await {
+        if event.investigation != investigation_id {
+            return StatusCode::NOT_FOUND;
+        }
+        if event.delete(State(state), Extension(user)).await.is_ok() {
+            return StatusCode::OK;
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
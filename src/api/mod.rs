@@ -1,17 +1,131 @@
 use axum::{extract::State, Router};
+use utoipa::{
+    openapi::{
+        path::{Operation, PathItem},
+        security::{
+            ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityRequirement, SecurityScheme,
+        },
+    },
+    Modify, OpenApi,
+};
 
 use crate::core;
 use crate::AppState;
 
+pub mod action_items;
 pub mod admin;
+pub mod attachments;
 pub mod auth;
+pub mod comments;
+pub mod entities;
+pub mod events;
 pub mod investigations;
+pub mod public;
+pub mod question_templates;
+pub mod questions;
+pub mod search;
+pub mod tips;
 pub mod users;
 
+// the paths are relative to the `/api` server, and nested the same way as the routers below
+#[derive(OpenApi)]
+#[openapi(
+    servers((url = "/api")),
+    nest(
+        (path = "/action-items", api = action_items::ApiDoc),
+        (path = "/admin", api = admin::ApiDoc),
+        (path = "/attachments", api = attachments::ApiDoc),
+        (path = "/comments", api = comments::ApiDoc),
+        (path = "/entities", api = entities::ApiDoc),
+        (path = "/events", api = events::ApiDoc),
+        (path = "/investigations", api = investigations::ApiDoc),
+        (path = "/question-templates", api = question_templates::ApiDoc),
+        (path = "/questions", api = questions::ApiDoc),
+        (path = "/search", api = search::ApiDoc),
+        (path = "/tips", api = tips::ApiDoc),
+        (path = "/users", api = users::ApiDoc),
+        (path = "/auth", api = auth::ApiDoc),
+        (path = "/public", api = public::ApiDoc)
+    ),
+    modifiers(&Authentication, &OperationIds)
+)]
+pub struct ApiDoc;
+
+fn operations(item: &mut PathItem) -> impl Iterator<Item = &mut Operation> {
+    [
+        &mut item.get,
+        &mut item.post,
+        &mut item.put,
+        &mut item.patch,
+        &mut item.delete,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+// everything needs either the `session` cookie from logging in or an API token, except the
+// unauthenticated routes
+struct Authentication;
+
+impl Modify for Authentication {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "session",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("session"))),
+        );
+        components.add_security_scheme(
+            "token",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+        openapi.security = Some(vec![
+            SecurityRequirement::new("session", Vec::<String>::new()),
+            SecurityRequirement::new("token", Vec::<String>::new()),
+        ]);
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if !path.starts_with("/auth/") && !path.starts_with("/public/") {
+                continue;
+            }
+            for operation in operations(item) {
+                operation.security = Some(vec![SecurityRequirement::default()]);
+            }
+        }
+    }
+}
+
+// operation ids default to the handler's name, which repeats across modules, so prefix them with
+// the module's tag to keep them unique for client generators
+struct OperationIds;
+
+impl Modify for OperationIds {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            for operation in operations(item) {
+                if let (Some(tag), Some(id)) = (
+                    operation.tags.as_ref().and_then(|tags| tags.first()),
+                    &operation.operation_id,
+                ) {
+                    operation.operation_id = Some(format!("{}_{}", tag, id));
+                }
+            }
+        }
+    }
+}
+
 pub fn router(state: AppState) -> Router<AppState> {
     Router::new()
+        .nest("/action-items", action_items::router())
         .nest("/admin", admin::router())
+        .nest("/attachments", attachments::router())
+        .nest("/comments", comments::router())
+        .nest("/entities", entities::router())
+        .nest("/events", events::router())
         .nest("/investigations", investigations::router())
+        .nest("/question-templates", question_templates::router())
+        .nest("/questions", questions::router())
+        .nest("/search", search::router())
+        .nest("/tips", tips::router())
         .nest("/users", users::router())
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
@@ -21,4 +135,5 @@ pub fn router(state: AppState) -> Router<AppState> {
         // ROUTES BELOW THIS LINE ARE UNAUTHENTICATED
         //
         .nest("/auth", auth::router())
+        .nest("/public", public::router())
 }
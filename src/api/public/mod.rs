@@ -0,0 +1,21 @@
+use crate::AppState;
+use axum::{extract::DefaultBodyLimit, Router};
+use utoipa::OpenApi;
+
+pub mod tips;
+
+#[derive(OpenApi)]
+#[openapi(nest((path = "/tips", api = tips::ApiDoc)))]
+pub struct ApiDoc;
+
+// anyone can send these, so nothing public gets the higher limit that signed-in uploads do
+pub fn router() -> Router<AppState> {
+    let max_body_mb = std::env::var("MAX_PUBLIC_BODY_MB")
+        .ok()
+        .and_then(|mb| mb.parse::<usize>().ok())
+        .unwrap_or(20);
+
+    Router::new()
+        .nest("/tips", tips::router())
+        .layer(DefaultBodyLimit::max(max_body_mb * 1024 * 1024))
+}
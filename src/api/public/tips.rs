@@ -0,0 +1,220 @@
+use axum::{
+    extract::{ConnectInfo, Multipart, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use std::net::SocketAddr;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    core::tips::{self, Tip, TipChallenge, TipInvestigation, TipSubmission, MAX_TIP_ATTACHMENTS},
+    AppState,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(challenge, get_investigation, submit))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/challenge", get(challenge))
+        .route("/:investigation_id", get(get_investigation).post(submit))
+}
+
+// only trust X-Forwarded-For when we've been told there's a proxy in front of us, otherwise anyone
+// could dodge the rate limit by making up a new address for every request
+fn client_address(headers: &HeaderMap, address: SocketAddr) -> String {
+    let trust_proxy = std::env::var("TRUST_PROXY").is_ok_and(|t| t == "true");
+    if trust_proxy {
+        if let Some(forwarded) = headers
+            .get("x-forwarded-for")
+            .and_then(|f| f.to_str().ok())
+            .and_then(|f| f.split(',').next())
+        {
+            return forwarded.trim().to_string();
+        }
+    }
+    address.ip().to_string()
+}
+
+#[utoipa::path(
+    get,
+    path = "/challenge",
+    tag = "public_tips",
+    responses(
+        (status = 200, description = "A proof-of-work puzzle to solve before submitting", body = TipChallenge),
+        (status = 429, description = "Too many tips from this address")
+    )
+)]
+pub async fn challenge(
+    State(state): State<AppState>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let Ok(submitter) = tips::submitter_hash(&client_address(&headers, address)) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    match tips::is_rate_limited(State(state.clone()), &submitter).await {
+        Ok(false) => {}
+        Ok(true) => return StatusCode::TOO_MANY_REQUESTS.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    if let Ok(challenge) = tips::issue_challenge(State(state), &submitter).await {
+        return axum::Json(challenge).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{investigation_id}",
+    tag = "public_tips",
+    params(("investigation_id" = Uuid, Path)),
+    responses(
+        (status = 200, description = "What the public can see of the investigation", body = TipInvestigation),
+        (status = 404, description = "No such investigation")
+    )
+)]
+pub async fn get_investigation(
+    State(state): State<AppState>,
+    Path(investigation_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(investigation) = tips::get_investigation(State(state), investigation_id).await {
+        return axum::Json(investigation).into_response();
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+// expects `challenge` and `nonce` fields first, then the tip itself and up to
+// `MAX_TIP_ATTACHMENTS` `file` fields. The challenge is checked before any file is read, so
+// unsolved submissions can't make us buffer their attachments
+#[utoipa::path(
+    post,
+    path = "/{investigation_id}",
+    tag = "public_tips",
+    params(("investigation_id" = Uuid, Path)),
+    request_body(
+        description = "`challenge` and `nonce` fields first, then `body` and optional contact fields, plus up to `MAX_TIP_ATTACHMENTS` `file` fields",
+        content_type = "multipart/form-data"
+    ),
+    responses(
+        (status = 200, description = "Received"),
+        (status = 400, description = "Missing body, or a challenge that isn't sent first"),
+        (status = 403, description = "The challenge wasn't solved"),
+        (status = 404, description = "No such investigation"),
+        (status = 413, description = "Too many attachments"),
+        (status = 429, description = "Too many tips from this address")
+    )
+)]
+pub async fn submit(
+    State(state): State<AppState>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(investigation_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let Ok(submitter) = tips::submitter_hash(&client_address(&headers, address)) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    match tips::is_rate_limited(State(state.clone()), &submitter).await {
+        Ok(false) => {}
+        Ok(true) => return StatusCode::TOO_MANY_REQUESTS.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    if tips::get_investigation(State(state.clone()), investigation_id)
+        .await
+        .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    // the challenge is the only thing read before it's been checked
+    let mut challenge: Option<Uuid> = None;
+    let mut nonce: Option<String> = None;
+    for _ in 0..2 {
+        let Ok(Some(field)) = multipart.next_field().await else {
+            break;
+        };
+        let name = field.name().map(|name| name.to_string());
+        let Ok(value) = field.text().await else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        match name.as_deref() {
+            Some("challenge") => match Uuid::parse_str(&value) {
+                Ok(id) => challenge = Some(id),
+                Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+            },
+            Some("nonce") => nonce = Some(value),
+            _ => return StatusCode::BAD_REQUEST.into_response(),
+        }
+    }
+    let (Some(challenge), Some(nonce)) = (challenge, nonce) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    match tips::verify_challenge(State(state.clone()), challenge, &nonce).await {
+        Ok(true) => {}
+        Ok(false) => return StatusCode::FORBIDDEN.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+
+    let mut submission = TipSubmission::default();
+    let mut honeypot = false;
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("file") => {
+                if submission.files.len() >= MAX_TIP_ATTACHMENTS {
+                    return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+                }
+                let filename = field.file_name().unwrap_or("upload").to_string();
+                let content_type = field.content_type().map(|c| c.to_string());
+                let Ok(data) = field.bytes().await else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+                if !data.is_empty() {
+                    submission.files.push((filename, content_type, data));
+                }
+            }
+            Some(name) => {
+                let name = name.to_string();
+                let Ok(value) = field.text().await else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+                match name.as_str() {
+                    "body" => submission.body = value.trim().to_string(),
+                    "contact_name" => submission.contact_name = non_empty(value),
+                    "contact_email" => submission.contact_email = non_empty(value),
+                    "contact_phone" => submission.contact_phone = non_empty(value),
+                    // hidden from people by the form, so only bots fill it in
+                    "website" => honeypot = !value.trim().is_empty(),
+                    _ => {}
+                }
+            }
+            None => {}
+        }
+    }
+
+    // bots get the same response as everyone else so they don't learn to avoid the field
+    if honeypot {
+        return StatusCode::OK.into_response();
+    }
+    if submission.body.is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    // nothing about the stored tip is handed back to the submitter
+    match Tip::submit(State(state), investigation_id, &submitter, submission).await {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
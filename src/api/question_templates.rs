@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::core::investigations::{Investigation, Question};
+use crate::core::question_templates::QuestionTemplate;
+use crate::core::users::User;
+use crate::AppState;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ApplyQuestionTemplateRequest {
+    investigation: Uuid,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list, get_by_id, apply))]
+pub struct ApiDoc;
+
+// templates are managed under /admin/question-templates; anyone can list and apply them
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list))
+        .route("/:template_id", get(get_by_id))
+        .route("/:template_id/apply", post(apply))
+}
+
+#[utoipa::path(get, path = "", responses((status = 200, body = Vec<QuestionTemplate>)))]
+pub async fn list(State(state): State<AppState>) -> impl IntoResponse {
+    match QuestionTemplate::list(State(state)).await {
+        Ok(templates) => axum::Json(templates).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{template_id}",
+    params(("template_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = QuestionTemplate),
+        (status = 404, description = "No such template")
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<AppState>,
+    Path(template_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match QuestionTemplate::get(State(state), template_id).await {
+        Ok(template) => axum::Json(template).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// copies the template's questions and action items into an existing investigation
+#[utoipa::path(
+    post,
+    path = "/{template_id}/apply",
+    params(("template_id" = Uuid, Path)),
+    request_body = ApplyQuestionTemplateRequest,
+    responses(
+        (status = 200, description = "The questions added to the investigation", body = Vec<Question>),
+        (status = 404, description = "No such template or investigation")
+    )
+)]
+pub async fn apply(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(template_id): Path<Uuid>,
+    Json(req): Json<ApplyQuestionTemplateRequest>,
+) -> impl IntoResponse {
+    let Ok(template) = QuestionTemplate::get(State(state.clone()), template_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if Investigation::get(State(state.clone()), &req.investigation.to_string(), false)
+        .await
+        .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match template
+        .apply(State(state), Extension(user), req.investigation)
+        .await
+    {
+        Ok(questions) => axum::Json(questions).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
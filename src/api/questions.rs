@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        investigations::{Question, QuestionDetails, WORK_STATUSES},
+        revisions::{self, Revision, RevisionTarget},
+        users::User,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetQuestionStatusRequest {
+    status: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(edit, set_status, revisions, restore))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/:question_id", post(edit))
+        .route("/:question_id/status", post(set_status))
+        .route("/:question_id/revisions", get(revisions))
+        .route("/:question_id/revisions/:version/restore", post(restore))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{question_id}/status",
+    params(("question_id" = Uuid, Path)),
+    request_body = SetQuestionStatusRequest,
+    responses(
+        (status = 200, body = Question),
+        (status = 400, description = "Unknown status")
+    )
+)]
+pub async fn set_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(question_id): Path<Uuid>,
+    Json(req): Json<SetQuestionStatusRequest>,
+) -> impl IntoResponse {
+    if !WORK_STATUSES.contains(&req.status.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if let Ok(mut question) = Question::get(State(state.clone()), question_id).await {
+        if question
+            .set_status(State(state), Extension(user), &req.status)
+            .await
+            .is_ok()
+        {
+            return axum::Json(question).into_response();
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{question_id}",
+    params(("question_id" = Uuid, Path)),
+    request_body = QuestionDetails,
+    responses(
+        (status = 200, body = Question),
+        (status = 400, description = "Invalid details"),
+        (status = 404, description = "No such question")
+    )
+)]
+pub async fn edit(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(question_id): Path<Uuid>,
+    Json(req): Json<QuestionDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(mut question) = Question::get(State(state.clone()), question_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match question.edit(State(state), Extension(user), req).await {
+        Ok(_) => axum::Json(question).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{question_id}/revisions",
+    params(("question_id" = Uuid, Path)),
+    responses((status = 200, description = "Oldest first", body = Vec<Revision>))
+)]
+pub async fn revisions(
+    State(state): State<AppState>,
+    Path(question_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(revisions) =
+        revisions::list(State(state), RevisionTarget::Question, question_id).await
+    {
+        return axum::Json(revisions).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{question_id}/revisions/{version}/restore",
+    params(("question_id" = Uuid, Path), ("version" = i32, Path)),
+    responses(
+        (status = 200, body = Question),
+        (status = 404, description = "No such question or version")
+    )
+)]
+pub async fn restore(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((question_id, version)): Path<(Uuid, i32)>,
+) -> impl IntoResponse {
+    let Ok(mut question) = Question::get(State(state.clone()), question_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if revisions::get(
+        State(state.clone()),
+        RevisionTarget::Question,
+        question_id,
+        version,
+    )
+    .await
+    .is_err()
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match question
+        .restore(State(state), Extension(user), version)
+        .await
+    {
+        Ok(_) => axum::Json(question).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
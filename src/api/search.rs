@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        search::{self, SearchResult},
+        users::User,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    q: String,
+    target_type: Option<String>,
+    investigation: Option<Uuid>,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(search))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(search))
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(SearchQuery),
+    responses((status = 200, description = "Best matches first", body = Vec<SearchResult>))
+)]
+pub async fn search(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    if query.q.trim().is_empty() {
+        return axum::Json(Vec::<SearchResult>::new()).into_response();
+    }
+
+    if let Ok(results) = search::search(
+        State(state),
+        user.id,
+        &query.q,
+        query.target_type.as_deref(),
+        query.investigation,
+    )
+    .await
+    {
+        return axum::Json(results).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
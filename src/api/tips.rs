@@ -0,0 +1,214 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Router,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::attachments::sanitize_filename;
+use crate::core::tips::{Tip, TipAttachment, TipConversion};
+use crate::core::users::User;
+use crate::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TipsQuery {
+    status: Option<String>,
+    investigation: Option<Uuid>,
+}
+
+#[derive(Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TipStatusDetails {
+    pub status: String,
+}
+
+#[derive(Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TipConversionResult {
+    pub tip: Tip,
+    pub target: Uuid,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list, get_by_id, set_status, convert, attachments, download))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list))
+        .route("/:tip_id", get(get_by_id))
+        .route("/:tip_id/status", post(set_status))
+        .route("/:tip_id/convert", post(convert))
+        .route("/:tip_id/attachments", get(attachments))
+        .route(
+            "/:tip_id/attachments/:attachment_id/download",
+            get(download),
+        )
+}
+
+#[utoipa::path(
+    get,
+    path = "",
+    params(TipsQuery),
+    responses((status = 200, body = Vec<Tip>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    Query(query): Query<TipsQuery>,
+) -> impl IntoResponse {
+    if let Ok(tips) = Tip::list(State(state), query.status.as_deref(), query.investigation).await {
+        return axum::Json(tips).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{tip_id}",
+    params(("tip_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Tip),
+        (status = 404, description = "No such tip")
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<AppState>,
+    Path(tip_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Ok(tip) = Tip::get(State(state), tip_id).await {
+        return axum::Json(tip).into_response();
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/{tip_id}/status",
+    params(("tip_id" = Uuid, Path)),
+    request_body = TipStatusDetails,
+    responses(
+        (status = 200, body = Tip),
+        (status = 400, description = "Unknown status"),
+        (status = 404, description = "No such tip"),
+        (status = 409, description = "The tip has already been converted")
+    )
+)]
+pub async fn set_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(tip_id): Path<Uuid>,
+    Json(req): Json<TipStatusDetails>,
+) -> impl IntoResponse {
+    let Ok(mut tip) = Tip::get(State(state.clone()), tip_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if tip.status == "converted" {
+        return StatusCode::CONFLICT.into_response();
+    }
+
+    match tip
+        .set_status(State(state), Extension(user), &req.status)
+        .await
+    {
+        Ok(()) => axum::Json(tip).into_response(),
+        Err(_) => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{tip_id}/convert",
+    params(("tip_id" = Uuid, Path)),
+    request_body = TipConversion,
+    responses(
+        (status = 200, body = TipConversionResult),
+        (status = 404, description = "No such tip"),
+        (status = 409, description = "The tip has already been converted")
+    )
+)]
+pub async fn convert(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(tip_id): Path<Uuid>,
+    Json(req): Json<TipConversion>,
+) -> impl IntoResponse {
+    let Ok(mut tip) = Tip::get(State(state.clone()), tip_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if tip.status == "converted" {
+        return StatusCode::CONFLICT.into_response();
+    }
+
+    match tip.convert(State(state), Extension(user), req).await {
+        Ok(target) => axum::Json(TipConversionResult { tip, target }).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{tip_id}/attachments",
+    params(("tip_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Vec<TipAttachment>),
+        (status = 404, description = "No such tip")
+    )
+)]
+pub async fn attachments(
+    State(state): State<AppState>,
+    Path(tip_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Ok(tip) = Tip::get(State(state.clone()), tip_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Ok(attachments) = tip.get_attachments(State(state)).await {
+        return axum::Json(attachments).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/{tip_id}/attachments/{attachment_id}/download",
+    params(("tip_id" = Uuid, Path), ("attachment_id" = Uuid, Path)),
+    responses(
+        (status = 200, content(("application/octet-stream"))),
+        (status = 404, description = "No such attachment on the tip")
+    )
+)]
+pub async fn download(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((tip_id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let Ok(attachment) = TipAttachment::get(State(state.clone()), attachment_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if attachment.tip != tip_id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match attachment.read(State(state), Extension(user)).await {
+        Ok(data) => (
+            [
+                (header::CONTENT_TYPE, attachment.mime_type.clone()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}\"",
+                        sanitize_filename(&attachment.filename)
+                    ),
+                ),
+            ],
+            data,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
@@ -0,0 +1,175 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        dashboard::{self, MyWork},
+        notifications::NotificationPreferences,
+        tokens::{ApiToken, ApiTokenDetails, CreatedApiToken},
+        users::User,
+    },
+    AppState,
+};
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateNotificationPreferencesRequest {
+    action_item_assigned: bool,
+    question_status_changed: bool,
+    followed_case_comment: bool,
+    overdue_digest: bool,
+    mentioned: bool,
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    action_items,
+    get_notification_preferences,
+    set_notification_preferences,
+    get_tokens,
+    create_token,
+    revoke_token
+))]
+pub struct ApiDoc;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/action-items", get(action_items))
+        .route(
+            "/notifications",
+            get(get_notification_preferences).post(set_notification_preferences),
+        )
+        .route("/tokens", get(get_tokens).post(create_token))
+        .route("/tokens/:token_id", delete(revoke_token))
+}
+
+#[utoipa::path(
+    get,
+    path = "/action-items",
+    responses((status = 200, description = "The dashboard of the user's own work", body = MyWork))
+)]
+pub async fn action_items(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    if let Ok(work) = dashboard::get_my_work(State(state), user.id).await {
+        return axum::Json(work).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    responses((status = 200, body = NotificationPreferences))
+)]
+pub async fn get_notification_preferences(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    if let Ok(prefs) = NotificationPreferences::get(State(state), user.id).await {
+        return axum::Json(prefs).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications",
+    request_body = UpdateNotificationPreferencesRequest,
+    responses((status = 200, body = NotificationPreferences))
+)]
+pub async fn set_notification_preferences(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(req): Json<UpdateNotificationPreferencesRequest>,
+) -> impl IntoResponse {
+    // the user always comes from the session, never from the request body
+    let prefs = NotificationPreferences {
+        user: user.id,
+        action_item_assigned: req.action_item_assigned,
+        question_status_changed: req.question_status_changed,
+        followed_case_comment: req.followed_case_comment,
+        overdue_digest: req.overdue_digest,
+        mentioned: req.mentioned,
+    };
+    if let Ok(prefs) = prefs.save(State(state)).await {
+        return axum::Json(prefs).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/tokens",
+    responses((status = 200, description = "Newest first, including expired and revoked tokens", body = Vec<ApiToken>))
+)]
+pub async fn get_tokens(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    if let Ok(tokens) = ApiToken::list_for_user(State(state), user.id).await {
+        return axum::Json(tokens).into_response();
+    }
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+// personal tokens act as the user who made them
+#[utoipa::path(
+    post,
+    path = "/tokens",
+    request_body = ApiTokenDetails,
+    responses(
+        (status = 200, description = "The only response that includes the token itself", body = CreatedApiToken),
+        (status = 400, description = "Missing name, unknown scope or invalid expiry")
+    )
+)]
+pub async fn create_token(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(req): Json<ApiTokenDetails>,
+) -> impl IntoResponse {
+    if !req.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let owner = user.id;
+    match ApiToken::create(State(state), Extension(user), owner, req).await {
+        Ok(created) => axum::Json(created).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tokens/{token_id}",
+    params(("token_id" = Uuid, Path)),
+    responses(
+        (status = 200, body = ApiToken),
+        (status = 404, description = "No such token of the user's")
+    )
+)]
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(token_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let Ok(mut token) = ApiToken::get(State(state.clone()), token_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if token.user != user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match token.revoke(State(state), Extension(user)).await {
+        Ok(()) => axum::Json(token).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
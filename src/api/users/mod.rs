@@ -1,15 +1,25 @@
-use crate::core::users;
+use crate::core::users::{self, User};
 use crate::AppState;
 use axum::extract::State;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::Router;
+use utoipa::OpenApi;
+
+pub mod me;
+
+#[derive(OpenApi)]
+#[openapi(paths(get_all), nest((path = "/me", api = me::ApiDoc)))]
+pub struct ApiDoc;
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/", get(get_all))
+    Router::new()
+        .route("/", get(get_all))
+        .nest("/me", me::router())
 }
 
 // TODO: this is duplicated in the `admin` module - check fields that need to be masked for non-admins
+#[utoipa::path(get, path = "", responses((status = 200, body = Vec<User>)))]
 pub async fn get_all(State(state): State<AppState>) -> impl IntoResponse {
     if let Ok(users) = users::get_all(&state.db).await {
         return axum::Json(users).into_response();
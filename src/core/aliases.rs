@@ -0,0 +1,197 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{log, users::User};
+use crate::AppState;
+
+pub const ALIAS_KINDS: [&str; 4] = ["alias", "nickname", "maiden_name", "alternate_identity"];
+
+// another name and/or date of birth the subject of an investigation is known by
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct Alias {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub investigation: Uuid,
+    pub kind: String,
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct AliasDetails {
+    pub kind: String,
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+impl AliasDetails {
+    // an alias has to identify the subject somehow, by at least part of a name or a date of birth
+    pub fn is_valid(&self) -> bool {
+        let has_name = [&self.first_name, &self.middle_name, &self.last_name]
+            .iter()
+            .any(|n| n.as_deref().is_some_and(|n| !n.trim().is_empty()));
+        ALIAS_KINDS.contains(&self.kind.as_str()) && (has_name || self.date_of_birth.is_some())
+    }
+}
+
+impl Alias {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Alias> {
+        sqlx::query_as!(
+            Alias,
+            "SELECT * FROM investigation_aliases WHERE id = $1",
+            id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn get_for_investigation(
+        State(state): State<AppState>,
+        investigation: Uuid,
+    ) -> Result<Vec<Alias>> {
+        sqlx::query_as!(
+            Alias,
+            "SELECT * FROM investigation_aliases WHERE investigation = $1 ORDER BY created ASC",
+            investigation
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // leaves the audit log to the caller, so it can be used inside a transaction
+    pub async fn insert(
+        conn: &mut PgConnection,
+        creator: Uuid,
+        investigation: Uuid,
+        details: AliasDetails,
+    ) -> Result<Alias> {
+        if !details.is_valid() {
+            return Err(Error::msg("Invalid alias"));
+        }
+
+        sqlx::query_as!(
+            Alias,
+            "INSERT INTO investigation_aliases (id, created, creator, investigation, kind, first_name, middle_name, last_name, date_of_birth, notes) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            Utc::now(),
+            creator,
+            investigation,
+            details.kind,
+            details.first_name,
+            details.middle_name,
+            details.last_name,
+            details.date_of_birth,
+            details.notes,
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        investigation: Uuid,
+        details: AliasDetails,
+    ) -> Result<Alias> {
+        let alias = Alias::insert(
+            &mut *state.db.acquire().await?,
+            user.id,
+            investigation,
+            details,
+        )
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            alias.id,
+            "alias",
+            None,
+            Some(&alias),
+            "Alias created",
+        )
+        .await?;
+
+        Ok(alias)
+    }
+
+    pub async fn update(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: AliasDetails,
+    ) -> Result<()> {
+        if !details.is_valid() {
+            return Err(Error::msg("Invalid alias"));
+        }
+
+        let previous = self.clone();
+        *self = sqlx::query_as!(
+            Alias,
+            "UPDATE investigation_aliases SET kind = $1, first_name = $2, middle_name = $3, last_name = $4, date_of_birth = $5, notes = $6 WHERE id = $7 RETURNING *",
+            details.kind,
+            details.first_name,
+            details.middle_name,
+            details.last_name,
+            details.date_of_birth,
+            details.notes,
+            self.id,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "alias",
+            Some(&previous),
+            Some(self),
+            "Alias updated",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(
+        self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<()> {
+        sqlx::query!("DELETE FROM investigation_aliases WHERE id = $1", self.id)
+            .execute(&state.db)
+            .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "alias",
+            Some(&self),
+            None,
+            "Alias deleted",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,458 @@
+use anyhow::{Error, Result};
+use axum::{body::Bytes, extract::State, Extension};
+use chrono::{DateTime, Utc};
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use tracing::*;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{investigations, log, media, storage, users::User};
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum AttachmentTarget {
+    Investigation,
+    ActionItem,
+}
+
+impl AttachmentTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttachmentTarget::Investigation => "investigation",
+            AttachmentTarget::ActionItem => "action_item",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<AttachmentTarget> {
+        match s {
+            "investigation" => Some(AttachmentTarget::Investigation),
+            "action_item" => Some(AttachmentTarget::ActionItem),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub uploader: Uuid,
+    pub investigation: Uuid,
+    pub target: Uuid,
+    pub target_type: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: i64,
+    // hex-encoded, computed when the file is received and checked again every time it's read
+    pub sha256: String,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+}
+
+// extracted from images attached to investigations; the storage keys point at a copy with all
+// metadata removed, for sharing publicly, and a thumbnail
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct AttachmentMetadata {
+    pub attachment: Uuid,
+    pub captured: Option<DateTime<Utc>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub exif: serde_json::Value,
+    pub xmp: Option<String>,
+    #[serde(skip_serializing)]
+    pub stripped_key: Option<String>,
+    pub stripped_sha256: Option<String>,
+    pub stripped_mime_type: Option<String>,
+    #[serde(skip_serializing)]
+    pub thumbnail_key: Option<String>,
+    pub thumbnail_sha256: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct MetadataSearch {
+    pub investigation: Option<Uuid>,
+    pub captured_after: Option<DateTime<Utc>>,
+    pub captured_before: Option<DateTime<Utc>>,
+    pub has_location: Option<bool>,
+    // matched against the camera make/model and every EXIF and XMP value
+    pub text: Option<String>,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+impl Attachment {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Attachment> {
+        sqlx::query_as!(Attachment, "SELECT * FROM attachments WHERE id = $1", id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(Error::from)
+    }
+
+    // the same as `get`, but only if `user` can see the investigation it's attached to
+    pub async fn get_for_user(
+        State(state): State<AppState>,
+        id: Uuid,
+        user: Uuid,
+    ) -> Result<Attachment> {
+        sqlx::query_as!(
+            Attachment,
+            "SELECT * FROM attachments WHERE id = $1 AND investigation_visible_to(investigation, $2)",
+            id,
+            user
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn get_for_target(
+        State(state): State<AppState>,
+        user: Uuid,
+        target_type: AttachmentTarget,
+        target: Uuid,
+    ) -> Result<Vec<Attachment>> {
+        sqlx::query_as!(
+            Attachment,
+            "SELECT * FROM attachments WHERE target = $1 AND target_type = $2 AND investigation_visible_to(investigation, $3) ORDER BY created ASC",
+            target,
+            target_type.as_str(),
+            user
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        target_type: AttachmentTarget,
+        target: Uuid,
+        filename: &str,
+        content_type: Option<&str>,
+        data: Bytes,
+    ) -> Result<Attachment> {
+        let investigation = {
+            let mut conn = state.db.acquire().await?;
+            let investigation =
+                investigations::investigation_of(&mut conn, target_type.as_str(), target)
+                    .await?
+                    .ok_or(Error::msg("No such attachment target"))?;
+            if !investigations::is_visible_to(&mut conn, investigation, user.id).await? {
+                return Err(Error::msg("No such attachment target"));
+            }
+            investigation
+        };
+
+        // we trust the file's contents over whatever the client claims it is
+        let mime_type = infer::get(&data)
+            .map(|t| t.mime_type())
+            .or(content_type)
+            .unwrap_or("application/octet-stream");
+
+        let id = Uuid::new_v7(Timestamp::now(NoContext));
+        let storage_key = format!("attachments/{}/{}", investigation, id);
+        let sha256 = sha256_hex(&data);
+        let size = data.len() as i64;
+
+        state
+            .storage
+            .put(&Path::from(storage_key.as_str()), data.clone().into())
+            .await?;
+
+        let attachment = sqlx::query_as!(
+            Attachment,
+            "INSERT INTO attachments (id, created, uploader, investigation, target, target_type, filename, mime_type, size, sha256, storage_key) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *",
+            id,
+            Utc::now(),
+            user.id,
+            investigation,
+            target,
+            target_type.as_str(),
+            filename,
+            mime_type,
+            size,
+            sha256,
+            storage_key,
+        )
+        .fetch_one(&state.db)
+        .await;
+        let attachment = match attachment {
+            Ok(attachment) => attachment,
+            Err(e) => {
+                storage::delete_all(state.storage.as_ref(), &[storage_key]).await;
+                return Err(e.into());
+            }
+        };
+
+        log::record(
+            State(state.clone()),
+            user.id,
+            attachment.id,
+            "attachment",
+            None,
+            Some(&attachment),
+            "Attachment uploaded",
+        )
+        .await?;
+
+        // processing failures shouldn't lose the original upload
+        if target_type == AttachmentTarget::Investigation
+            && attachment.mime_type.starts_with("image/")
+        {
+            if let Err(e) = attachment.process_image(State(state), data).await {
+                warn!(
+                    "Unable to process image attachment {}: {}",
+                    attachment.id, e
+                );
+            }
+        }
+
+        Ok(attachment)
+    }
+
+    pub(crate) async fn process_image(
+        &self,
+        State(state): State<AppState>,
+        data: Bytes,
+    ) -> Result<()> {
+        // decoding and re-encoding is CPU-bound, so keep it off the async runtime
+        let (metadata, stripped, thumbnail) = tokio::task::spawn_blocking(move || {
+            let metadata = media::extract_metadata(&data);
+            let stripped = media::strip_metadata(&data, metadata.orientation);
+            let thumbnail = media::thumbnail(&data, metadata.orientation);
+            (metadata, stripped, thumbnail)
+        })
+        .await?;
+
+        let (stripped_key, stripped_sha256, stripped_mime_type) = match stripped {
+            Ok(stripped) => {
+                let key = format!("{}.stripped", self.storage_key);
+                let sha256 = sha256_hex(&stripped.data);
+                state
+                    .storage
+                    .put(&Path::from(key.as_str()), stripped.data.into())
+                    .await?;
+                (Some(key), Some(sha256), Some(stripped.mime_type))
+            }
+            Err(e) => {
+                warn!(
+                    "Unable to strip metadata from attachment {}: {}",
+                    self.id, e
+                );
+                (None, None, None)
+            }
+        };
+
+        let (thumbnail_key, thumbnail_sha256) = match thumbnail {
+            Ok(thumbnail) => {
+                let key = format!("{}.thumbnail", self.storage_key);
+                let sha256 = sha256_hex(&thumbnail.data);
+                state
+                    .storage
+                    .put(&Path::from(key.as_str()), thumbnail.data.into())
+                    .await?;
+                (Some(key), Some(sha256))
+            }
+            Err(e) => {
+                warn!(
+                    "Unable to create thumbnail for attachment {}: {}",
+                    self.id, e
+                );
+                (None, None)
+            }
+        };
+
+        let result = sqlx::query!(
+            "INSERT INTO attachment_metadata (attachment, captured, latitude, longitude, altitude, camera_make, camera_model, exif, xmp, stripped_key, stripped_sha256, stripped_mime_type, thumbnail_key, thumbnail_sha256) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+            self.id,
+            metadata.captured,
+            metadata.latitude,
+            metadata.longitude,
+            metadata.altitude,
+            metadata.camera_make,
+            metadata.camera_model,
+            metadata.exif,
+            metadata.xmp,
+            stripped_key,
+            stripped_sha256,
+            stripped_mime_type,
+            thumbnail_key,
+            thumbnail_sha256,
+        )
+        .execute(&state.db)
+        .await;
+        if let Err(e) = result {
+            let keys = stripped_key
+                .into_iter()
+                .chain(thumbnail_key)
+                .collect::<Vec<_>>();
+            storage::delete_all(state.storage.as_ref(), &keys).await;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_metadata(&self, State(state): State<AppState>) -> Result<AttachmentMetadata> {
+        sqlx::query_as!(
+            AttachmentMetadata,
+            "SELECT * FROM attachment_metadata WHERE attachment = $1",
+            self.id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // only from investigations `user` can see
+    pub async fn search_metadata(
+        State(state): State<AppState>,
+        user: Uuid,
+        search: MetadataSearch,
+    ) -> Result<Vec<AttachmentMetadata>> {
+        sqlx::query_as!(
+            AttachmentMetadata,
+            r#"SELECT attachment_metadata.* FROM attachment_metadata
+            JOIN attachments ON attachments.id = attachment_metadata.attachment
+            WHERE investigation_visible_to(attachments.investigation, $6)
+                AND ($1::uuid IS NULL OR attachments.investigation = $1)
+                AND ($2::timestamptz IS NULL OR captured >= $2)
+                AND ($3::timestamptz IS NULL OR captured <= $3)
+                AND ($4::boolean IS NULL OR (latitude IS NOT NULL AND longitude IS NOT NULL) = $4)
+                AND (
+                    $5::text IS NULL
+                    OR camera_make ILIKE '%' || $5 || '%'
+                    OR camera_model ILIKE '%' || $5 || '%'
+                    OR exif::text ILIKE '%' || $5 || '%'
+                    OR xmp ILIKE '%' || $5 || '%'
+                )
+            ORDER BY captured ASC NULLS LAST"#,
+            search.investigation,
+            search.captured_after,
+            search.captured_before,
+            search.has_location,
+            search.text,
+            user,
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn read(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<Bytes> {
+        self.read_verified(
+            State(state),
+            Extension(user),
+            &self.storage_key,
+            &self.sha256,
+            "Attachment downloaded",
+        )
+        .await
+    }
+
+    pub async fn read_stripped(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<(Bytes, String)> {
+        let metadata = self.get_metadata(State(state.clone())).await?;
+        let (Some(key), Some(sha256), Some(mime_type)) = (
+            metadata.stripped_key,
+            metadata.stripped_sha256,
+            metadata.stripped_mime_type,
+        ) else {
+            return Err(Error::msg("Attachment has no stripped copy"));
+        };
+        let data = self
+            .read_verified(
+                State(state),
+                Extension(user),
+                &key,
+                &sha256,
+                "Stripped attachment downloaded",
+            )
+            .await?;
+        Ok((data, mime_type))
+    }
+
+    pub async fn read_thumbnail(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<Bytes> {
+        let metadata = self.get_metadata(State(state.clone())).await?;
+        let (Some(key), Some(sha256)) = (metadata.thumbnail_key, metadata.thumbnail_sha256) else {
+            return Err(Error::msg("Attachment has no thumbnail"));
+        };
+        self.read_verified(
+            State(state),
+            Extension(user),
+            &key,
+            &sha256,
+            "Attachment thumbnail downloaded",
+        )
+        .await
+    }
+
+    // every read is logged and re-hashed so the chain of custody can be demonstrated
+    async fn read_verified(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        key: &str,
+        expected_sha256: &str,
+        message: &str,
+    ) -> Result<Bytes> {
+        let data = state.storage.get(&Path::from(key)).await?.bytes().await?;
+
+        let sha256 = sha256_hex(&data);
+        if sha256 != expected_sha256 {
+            error!(
+                "Integrity check failed for {}: expected {}, got {}",
+                key, expected_sha256, sha256
+            );
+            log::record(
+                State(state),
+                user.id,
+                self.id,
+                "attachment",
+                None,
+                Some(&sha256),
+                "Attachment failed integrity check",
+            )
+            .await?;
+            return Err(Error::msg("Attachment failed integrity check"));
+        }
+
+        log::record::<Attachment>(
+            State(state),
+            user.id,
+            self.id,
+            "attachment",
+            None,
+            None,
+            message,
+        )
+        .await?;
+
+        Ok(data)
+    }
+}
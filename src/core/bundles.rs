@@ -0,0 +1,869 @@
+use anyhow::{Error, Result};
+use axum::{body::Bytes, extract::State, Extension};
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Read, Write};
+use tracing::warn;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::core::attachments::{sha256_hex, Attachment};
+use crate::core::duplicates::{self, DuplicateCandidate};
+use crate::core::{log, storage, users::User};
+use crate::AppState;
+
+// the layout of a bundle and every field in it is documented in docs/case_bundle.md - changes to
+// anything below need a new version and a note there
+pub const BUNDLE_FORMAT: &str = "intricase-case-bundle";
+pub const BUNDLE_VERSION: u32 = 2;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const SIGNATURE_FILE: &str = "signature";
+const CASE_FILE: &str = "case.json";
+
+// bundles are signed with a key derived from SIGNING_KEY rather than the key itself, so a bundle
+// signature can't be used to attack the cookie key
+const KEY_CONTEXT: &[u8] = b"intricase case bundle signing key v1";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: String,
+    pub version: u32,
+    pub exported: DateTime<Utc>,
+    // the BASE_URL of the instance the bundle came from
+    pub source: Option<String>,
+    pub investigation: Uuid,
+    // every other file in the bundle and its hex-encoded SHA-256 hash; signing the manifest covers
+    // all of them
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaseBundle {
+    pub investigation: BundleInvestigation,
+    pub questions: Vec<BundleQuestion>,
+    pub action_items: Vec<BundleActionItem>,
+    pub comments: Vec<BundleComment>,
+    pub comment_revisions: Vec<BundleCommentRevision>,
+    pub attachments: Vec<BundleAttachment>,
+    pub logs: Vec<BundleLogEntry>,
+    // everyone referenced above, so they can be matched up with users on the importing instance
+    pub users: Vec<BundleUser>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleInvestigation {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub internal_id: Option<String>,
+    pub first_name: String,
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    pub date_of_birth: NaiveDate,
+    pub namus_id: Option<String>,
+    pub missing_since: NaiveDate,
+    pub synopsis: String,
+    // added in version 2; earlier bundles are imported as open
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default)]
+    pub status_changed: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub closure_outcome: Option<String>,
+    #[serde(default)]
+    pub closure_notes: Option<String>,
+}
+
+fn default_status() -> String {
+    "open".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleQuestion {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub pretty_id: String,
+    pub summary: String,
+    pub details: Option<String>,
+    pub outcome: Option<String>,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleActionItem {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub question: Uuid,
+    pub pretty_id: String,
+    pub summary: String,
+    pub details: Option<String>,
+    pub outcome: Option<String>,
+    pub assignee: Option<Uuid>,
+    pub status: String,
+    pub assigned: Option<DateTime<Utc>>,
+    pub resolved: Option<DateTime<Utc>>,
+    pub due_date: Option<NaiveDate>,
+    pub priority: String,
+    pub estimated_hours: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleComment {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub author: Uuid,
+    pub target: Uuid,
+    pub target_type: String,
+    pub parent: Option<Uuid>,
+    pub body: String,
+    pub edited: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleCommentRevision {
+    pub id: Uuid,
+    pub comment: Uuid,
+    pub created: DateTime<Utc>,
+    pub body: String,
+}
+
+// the file itself is stored in the bundle as `attachments/{id}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleAttachment {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub uploader: Uuid,
+    pub target: Uuid,
+    pub target_type: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub sha256: String,
+}
+
+// `previous_data` and `data` are kept exactly as they were logged, so ids inside them are the
+// exporting instance's
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleLogEntry {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub actor: Option<Uuid>,
+    pub target: Option<Uuid>,
+    pub target_type: Option<String>,
+    pub previous_data: Option<String>,
+    pub data: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleUser {
+    pub id: Uuid,
+    pub email: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct BundleImport {
+    pub id: Uuid,
+    // users in the bundle with no account here, whose work is credited to whoever imported it
+    pub unmatched_users: Vec<String>,
+    pub possible_duplicates: Vec<DuplicateCandidate>,
+}
+
+// a bundle that's been checked against its signature and hashes, with the attachments still to
+// be stored
+pub struct VerifiedBundle {
+    pub manifest: BundleManifest,
+    pub case: CaseBundle,
+    pub files: HashMap<Uuid, Bytes>,
+}
+
+fn signing_key() -> Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(std::env::var("SIGNING_KEY")?.as_bytes())?;
+    mac.update(KEY_CONTEXT);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn sign(key: &[u8], manifest: &[u8]) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(manifest);
+    Ok(mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn verify_signature(key: &[u8], manifest: &[u8], signature: &str) -> Result<bool> {
+    let signature = signature.trim();
+    if !signature.len().is_multiple_of(2) || !signature.is_ascii() {
+        return Ok(false);
+    }
+    let Ok(signature) = (0..signature.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&signature[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+    else {
+        return Ok(false);
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(manifest);
+    Ok(mac.verify_slice(&signature).is_ok())
+}
+
+fn max_file_size() -> u64 {
+    std::env::var("MAX_UPLOAD_MB")
+        .ok()
+        .and_then(|mb| mb.parse::<u64>().ok())
+        .unwrap_or(50)
+        * 1024
+        * 1024
+}
+
+// the sizes in a zip's directory can't be trusted, so reads are cut off at the limit regardless
+fn read_file(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>> {
+    let limit = max_file_size();
+    let mut data = vec![];
+    archive
+        .by_name(name)?
+        .take(limit + 1)
+        .read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(Error::msg(format!("{} is too large", name)));
+    }
+    Ok(data)
+}
+
+pub async fn export(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    investigation: Uuid,
+) -> Result<Vec<u8>> {
+    let inv = sqlx::query_as!(
+        BundleInvestigation,
+        r#"SELECT id, created, creator, internal_id, first_name, middle_name, last_name, date_of_birth, namus_id, missing_since, synopsis,
+            status, status_changed as "status_changed?", closure_outcome, closure_notes
+        FROM investigations WHERE id = $1"#,
+        investigation
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    let questions = sqlx::query_as!(
+        BundleQuestion,
+        "SELECT id, created, creator, pretty_id, summary, details, outcome, status FROM questions WHERE investigation = $1 ORDER BY created ASC",
+        inv.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let action_items = sqlx::query_as!(
+        BundleActionItem,
+        r#"SELECT action_items.id, action_items.created, action_items.creator, question, action_items.pretty_id,
+            action_items.summary, action_items.details, action_items.outcome, assignee, action_items.status,
+            assigned, resolved, due_date, priority, estimated_hours
+        FROM action_items
+        JOIN questions ON questions.id = action_items.question
+        WHERE questions.investigation = $1
+        ORDER BY action_items.created ASC"#,
+        inv.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let comments = sqlx::query_as!(
+        BundleComment,
+        "SELECT id, created, author, target, target_type, parent, body, edited FROM comments WHERE investigation = $1 ORDER BY created ASC",
+        inv.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let comment_revisions = sqlx::query_as!(
+        BundleCommentRevision,
+        r#"SELECT comment_revisions.id, comment, comment_revisions.created, comment_revisions.body
+        FROM comment_revisions
+        JOIN comments ON comments.id = comment_revisions.comment
+        WHERE comments.investigation = $1
+        ORDER BY comment_revisions.created ASC"#,
+        inv.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let attachments = sqlx::query_as!(
+        Attachment,
+        "SELECT * FROM attachments WHERE investigation = $1 ORDER BY created ASC",
+        inv.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    // the audit trail for everything in the bundle
+    let targets = std::iter::once(inv.id)
+        .chain(questions.iter().map(|q| q.id))
+        .chain(action_items.iter().map(|a| a.id))
+        .chain(comments.iter().map(|c| c.id))
+        .chain(attachments.iter().map(|a| a.id))
+        .collect::<Vec<Uuid>>();
+    let logs = sqlx::query_as!(
+        BundleLogEntry,
+        "SELECT id, timestamp, actor, target, target_type, previous_data, data, message FROM logs WHERE target = ANY($1) ORDER BY timestamp ASC",
+        &targets
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let user_ids = std::iter::once(inv.creator)
+        .chain(questions.iter().map(|q| q.creator))
+        .chain(
+            action_items
+                .iter()
+                .flat_map(|a| std::iter::once(a.creator).chain(a.assignee)),
+        )
+        .chain(comments.iter().map(|c| c.author))
+        .chain(attachments.iter().map(|a| a.uploader))
+        .chain(logs.iter().filter_map(|l| l.actor))
+        .collect::<Vec<Uuid>>();
+    let users = sqlx::query_as!(
+        BundleUser,
+        "SELECT id, email, display_name FROM users WHERE id = ANY($1)",
+        &user_ids
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut files = BTreeMap::new();
+
+    let mut bundle_attachments = vec![];
+    for attachment in attachments {
+        let data = attachment
+            .read(State(state.clone()), Extension(user.clone()))
+            .await?;
+        let name = format!("attachments/{}", attachment.id);
+        writer.start_file(name.as_str(), options)?;
+        writer.write_all(&data)?;
+        files.insert(name, attachment.sha256.clone());
+        bundle_attachments.push(BundleAttachment {
+            id: attachment.id,
+            created: attachment.created,
+            uploader: attachment.uploader,
+            target: attachment.target,
+            target_type: attachment.target_type,
+            filename: attachment.filename,
+            mime_type: attachment.mime_type,
+            size: attachment.size,
+            sha256: attachment.sha256,
+        });
+    }
+
+    let case = serde_json::to_vec_pretty(&CaseBundle {
+        investigation: inv,
+        questions,
+        action_items,
+        comments,
+        comment_revisions,
+        attachments: bundle_attachments,
+        logs,
+        users,
+    })?;
+    writer.start_file(CASE_FILE, options)?;
+    writer.write_all(&case)?;
+    files.insert(CASE_FILE.to_string(), sha256_hex(&case));
+
+    let manifest = serde_json::to_vec_pretty(&BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported: Utc::now(),
+        source: std::env::var("BASE_URL").ok(),
+        investigation,
+        files,
+    })?;
+    writer.start_file(MANIFEST_FILE, options)?;
+    writer.write_all(&manifest)?;
+    writer.start_file(SIGNATURE_FILE, options)?;
+    writer.write_all(sign(&signing_key()?, &manifest)?.as_bytes())?;
+
+    let bundle = writer.finish()?.into_inner();
+
+    log::record(
+        State(state),
+        user.id,
+        investigation,
+        "investigation",
+        None::<&BundleManifest>,
+        None,
+        "Investigation exported as a case bundle",
+    )
+    .await?;
+
+    Ok(bundle)
+}
+
+// checks the signature before anything else in the bundle is parsed, and returns `None` if it
+// doesn't match
+pub fn verify(data: &[u8]) -> Result<Option<VerifiedBundle>> {
+    verify_with(&signing_key()?, data)
+}
+
+fn verify_with(key: &[u8], data: &[u8]) -> Result<Option<VerifiedBundle>> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let manifest_data = read_file(&mut archive, MANIFEST_FILE)?;
+    let signature = String::from_utf8(read_file(&mut archive, SIGNATURE_FILE)?)?;
+    if !verify_signature(key, &manifest_data, &signature)? {
+        return Ok(None);
+    }
+
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_data)?;
+    if manifest.format != BUNDLE_FORMAT || manifest.version > BUNDLE_VERSION {
+        return Err(Error::msg("Unsupported bundle format or version"));
+    }
+
+    // only files listed in the manifest are read, and each has to match its hash
+    let mut read = |name: &str| -> Result<Vec<u8>> {
+        let expected = manifest
+            .files
+            .get(name)
+            .ok_or(Error::msg(format!("{} is missing from the manifest", name)))?;
+        let data = read_file(&mut archive, name)?;
+        if sha256_hex(&data) != *expected {
+            return Err(Error::msg(format!("{} doesn't match the manifest", name)));
+        }
+        Ok(data)
+    };
+
+    let case: CaseBundle = serde_json::from_slice(&read(CASE_FILE)?)?;
+    if case.investigation.id != manifest.investigation {
+        return Err(Error::msg("Bundle contents don't match the manifest"));
+    }
+    let mut files = HashMap::new();
+    for attachment in &case.attachments {
+        let data = read(&format!("attachments/{}", attachment.id))?;
+        if sha256_hex(&data) != attachment.sha256 {
+            return Err(Error::msg("Attachment doesn't match its recorded hash"));
+        }
+        files.insert(attachment.id, Bytes::from(data));
+    }
+
+    Ok(Some(VerifiedBundle {
+        manifest,
+        case,
+        files,
+    }))
+}
+
+// everything gets a new id, so a bundle can be imported next to the investigation it came from,
+// or more than once
+struct IdMap(HashMap<Uuid, Uuid>);
+
+impl IdMap {
+    fn assign(&mut self, old: Uuid) -> Uuid {
+        *self
+            .0
+            .entry(old)
+            .or_insert_with(|| Uuid::new_v7(Timestamp::now(NoContext)))
+    }
+
+    fn get(&self, old: Uuid) -> Result<Uuid> {
+        self.0
+            .get(&old)
+            .copied()
+            .ok_or(Error::msg("Bundle refers to something it doesn't contain"))
+    }
+}
+
+pub async fn import(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    bundle: VerifiedBundle,
+) -> Result<BundleImport> {
+    let VerifiedBundle {
+        manifest,
+        case,
+        mut files,
+    } = bundle;
+
+    // users are matched by email address; anyone without an account here is credited to the
+    // importer rather than having an account made for them
+    let mut users = HashMap::new();
+    let mut unmatched_users = vec![];
+    for bundle_user in &case.users {
+        match User::get_by_email(State(state.clone()), &bundle_user.email).await {
+            Ok(existing) => {
+                users.insert(bundle_user.id, existing.id);
+            }
+            Err(_) => {
+                users.insert(bundle_user.id, user.id);
+                unmatched_users.push(bundle_user.email.clone());
+            }
+        }
+    }
+    let user_id = |id: Uuid| users.get(&id).copied().unwrap_or(user.id);
+
+    let mut ids = IdMap(HashMap::new());
+    let investigation = ids.assign(case.investigation.id);
+    // attachments are stored as they're inserted, so any that were stored are removed if the
+    // import is rolled back
+    let mut written = vec![];
+    let mut attachments: Vec<(Attachment, Bytes)> = vec![];
+    let result: Result<()> = async {
+        let mut tx = state.db.begin().await?;
+
+        let inv = &case.investigation;
+        sqlx::query!(
+            "INSERT INTO investigations (id, created, creator, internal_id, first_name, middle_name, last_name, date_of_birth, namus_id, missing_since, synopsis, status, status_changed, closure_outcome, closure_notes) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+            investigation,
+            inv.created,
+            user_id(inv.creator),
+            inv.internal_id,
+            inv.first_name,
+            inv.middle_name,
+            inv.last_name,
+            inv.date_of_birth,
+            inv.namus_id,
+            inv.missing_since,
+            inv.synopsis,
+            inv.status,
+            inv.status_changed.unwrap_or(inv.created),
+            inv.closure_outcome,
+            inv.closure_notes,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for question in &case.questions {
+            sqlx::query!(
+                "INSERT INTO questions (id, created, creator, pretty_id, summary, details, investigation, outcome, status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                ids.assign(question.id),
+                question.created,
+                user_id(question.creator),
+                question.pretty_id,
+                question.summary,
+                question.details,
+                investigation,
+                question.outcome,
+                question.status,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for action_item in &case.action_items {
+            let question = ids.get(action_item.question)?;
+            sqlx::query!(
+                "INSERT INTO action_items (id, created, creator, pretty_id, summary, details, outcome, assignee, question, status, assigned, resolved, due_date, priority, estimated_hours) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+                ids.assign(action_item.id),
+                action_item.created,
+                user_id(action_item.creator),
+                action_item.pretty_id,
+                action_item.summary,
+                action_item.details,
+                action_item.outcome,
+                action_item.assignee.map(user_id),
+                question,
+                action_item.status,
+                action_item.assigned,
+                action_item.resolved,
+                action_item.due_date,
+                action_item.priority,
+                action_item.estimated_hours,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // replies always come after what they're replying to
+        let mut comments = case.comments.iter().collect::<Vec<_>>();
+        comments.sort_by_key(|c| c.created);
+        for comment in comments {
+            let target = ids.get(comment.target)?;
+            let parent = comment.parent.map(|p| ids.get(p)).transpose()?;
+            sqlx::query!(
+                "INSERT INTO comments (id, created, author, investigation, target, target_type, parent, body, edited) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                ids.assign(comment.id),
+                comment.created,
+                user_id(comment.author),
+                investigation,
+                target,
+                comment.target_type,
+                parent,
+                comment.body,
+                comment.edited,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for revision in &case.comment_revisions {
+            sqlx::query!(
+                "INSERT INTO comment_revisions (id, comment, created, body) VALUES ($1, $2, $3, $4)",
+                ids.assign(revision.id),
+                ids.get(revision.comment)?,
+                revision.created,
+                revision.body,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for attachment in &case.attachments {
+            let id = ids.assign(attachment.id);
+            let data = files
+                .remove(&attachment.id)
+                .ok_or(Error::msg("Attachment is missing from the bundle"))?;
+            let storage_key = format!("attachments/{}/{}", investigation, id);
+            state
+                .storage
+                .put(&Path::from(storage_key.as_str()), data.clone().into())
+                .await?;
+            written.push(storage_key.clone());
+            let created = sqlx::query_as!(
+                Attachment,
+                "INSERT INTO attachments (id, created, uploader, investigation, target, target_type, filename, mime_type, size, sha256, storage_key) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *",
+                id,
+                attachment.created,
+                user_id(attachment.uploader),
+                investigation,
+                ids.get(attachment.target)?,
+                attachment.target_type,
+                attachment.filename,
+                attachment.mime_type,
+                attachment.size,
+                attachment.sha256,
+                storage_key,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            attachments.push((created, data));
+        }
+
+        // the original audit trail comes along, pointed at the new ids
+        for entry in &case.logs {
+            sqlx::query!(
+                "INSERT INTO logs (id, timestamp, actor, target, target_type, previous_data, data, message) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                Uuid::new_v7(Timestamp::now(NoContext)),
+                entry.timestamp,
+                entry.actor.map(user_id),
+                entry.target.map(|t| ids.get(t)).transpose()?,
+                entry.target_type,
+                entry.previous_data,
+                entry.data,
+                entry.message,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        storage::delete_all(state.storage.as_ref(), &written).await;
+        return Err(e);
+    }
+
+    log::record(
+        State(state.clone()),
+        user.id,
+        investigation,
+        "investigation",
+        None,
+        Some(&manifest),
+        "Investigation imported from a case bundle",
+    )
+    .await?;
+
+    // as with uploads, processing failures shouldn't undo the import
+    for (attachment, data) in attachments {
+        if attachment.target_type == "investigation" && attachment.mime_type.starts_with("image/") {
+            if let Err(e) = attachment.process_image(State(state.clone()), data).await {
+                warn!(
+                    "Unable to process image attachment {}: {}",
+                    attachment.id, e
+                );
+            }
+        }
+    }
+
+    let possible_duplicates = duplicates::find_duplicates_of(State(state), investigation)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Unable to check investigation {} for duplicates: {}",
+                investigation, e
+            );
+            vec![]
+        });
+
+    Ok(BundleImport {
+        id: investigation,
+        unmatched_users,
+        possible_duplicates,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"bundle test signing key";
+
+    fn case(investigation: Uuid) -> Vec<u8> {
+        serde_json::to_vec(&CaseBundle {
+            investigation: BundleInvestigation {
+                id: investigation,
+                created: Utc::now(),
+                creator: Uuid::nil(),
+                internal_id: None,
+                first_name: "Jane".to_string(),
+                middle_name: None,
+                last_name: "Doe".to_string(),
+                date_of_birth: NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(),
+                namus_id: None,
+                missing_since: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                synopsis: "".to_string(),
+                status: "closed".to_string(),
+                status_changed: Some(Utc::now()),
+                closure_outcome: Some("other".to_string()),
+                closure_notes: Some("Closed at the family's request".to_string()),
+            },
+            questions: vec![],
+            action_items: vec![],
+            comments: vec![],
+            comment_revisions: vec![],
+            attachments: vec![],
+            logs: vec![],
+            users: vec![],
+        })
+        .unwrap()
+    }
+
+    fn manifest(investigation: Uuid, case: &[u8]) -> Vec<u8> {
+        serde_json::to_vec(&BundleManifest {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported: Utc::now(),
+            source: None,
+            investigation,
+            files: BTreeMap::from([(CASE_FILE.to_string(), sha256_hex(case))]),
+        })
+        .unwrap()
+    }
+
+    fn zip(manifest: &[u8], signature: &str, case: &[u8]) -> Vec<u8> {
+        let options = SimpleFileOptions::default();
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in [
+            (CASE_FILE, case),
+            (MANIFEST_FILE, manifest),
+            (SIGNATURE_FILE, signature.as_bytes()),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn signatures_round_trip() {
+        let manifest = b"{\"format\":\"intricase-case-bundle\"}";
+        let signature = sign(KEY, manifest).unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(verify_signature(KEY, manifest, &signature).unwrap());
+        // a trailing newline from an editor doesn't matter
+        assert!(verify_signature(KEY, manifest, &format!("{}\n", signature)).unwrap());
+    }
+
+    #[test]
+    fn rejects_tampered_signatures() {
+        let manifest = b"{\"format\":\"intricase-case-bundle\"}";
+        let signature = sign(KEY, manifest).unwrap();
+
+        assert!(!verify_signature(KEY, b"{\"format\":\"something-else\"}", &signature).unwrap());
+
+        let flipped = format!(
+            "{}{}",
+            if signature.starts_with('0') { '1' } else { '0' },
+            &signature[1..]
+        );
+        assert!(!verify_signature(KEY, manifest, &flipped).unwrap());
+        assert!(!verify_signature(KEY, manifest, &signature[..62]).unwrap());
+        assert!(!verify_signature(KEY, manifest, &signature[1..]).unwrap());
+        assert!(!verify_signature(KEY, manifest, &"zz".repeat(32)).unwrap());
+        assert!(!verify_signature(KEY, manifest, "").unwrap());
+    }
+
+    #[test]
+    fn verifies_signed_bundles() {
+        let investigation = Uuid::new_v7(Timestamp::now(NoContext));
+        let case = case(investigation);
+        let manifest = manifest(investigation, &case);
+        let bundle = zip(&manifest, &sign(KEY, &manifest).unwrap(), &case);
+
+        let verified = verify_with(KEY, &bundle).unwrap().unwrap();
+        assert_eq!(verified.manifest.investigation, investigation);
+        assert_eq!(verified.case.investigation.id, investigation);
+        assert_eq!(verified.case.investigation.status, "closed");
+        assert!(verified.files.is_empty());
+    }
+
+    #[test]
+    fn reads_version_1_investigations_as_open() {
+        let investigation: BundleInvestigation = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "created": Utc::now(),
+            "creator": Uuid::nil(),
+            "internal_id": null,
+            "first_name": "Jane",
+            "middle_name": null,
+            "last_name": "Doe",
+            "date_of_birth": "1990-01-01",
+            "namus_id": null,
+            "missing_since": "2020-01-01",
+            "synopsis": "",
+        }))
+        .unwrap();
+        assert_eq!(investigation.status, "open");
+        assert!(investigation.status_changed.is_none());
+        assert!(investigation.closure_outcome.is_none());
+    }
+
+    #[test]
+    fn rejects_tampered_bundles() {
+        let investigation = Uuid::new_v7(Timestamp::now(NoContext));
+        let case = case(investigation);
+        let manifest = manifest(investigation, &case);
+        let signature = sign(KEY, &manifest).unwrap();
+
+        // a manifest that's been changed no longer matches its signature
+        let other = self::manifest(Uuid::new_v7(Timestamp::now(NoContext)), &case);
+        assert!(verify_with(KEY, &zip(&other, &signature, &case))
+            .unwrap()
+            .is_none());
+
+        // and a signature for one manifest can't be moved to another
+        let other_signature = sign(KEY, &other).unwrap();
+        assert!(verify_with(KEY, &zip(&manifest, &other_signature, &case))
+            .unwrap()
+            .is_none());
+
+        // changing a file the manifest covers is caught by its hash
+        let mut changed = case.clone();
+        changed.push(b' ');
+        assert!(verify_with(KEY, &zip(&manifest, &signature, &changed)).is_err());
+    }
+}
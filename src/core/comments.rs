@@ -0,0 +1,301 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{investigations, log, notifications, users::User};
+use crate::AppState;
+
+// why a comment couldn't be created, for the errors that are the client's fault
+#[derive(Debug, thiserror::Error)]
+pub enum CommentError {
+    #[error("No such comment target")]
+    TargetNotFound,
+    #[error("Parent comment doesn't exist or belongs to a different target")]
+    InvalidParent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum CommentTarget {
+    Investigation,
+    Question,
+    ActionItem,
+}
+
+impl CommentTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentTarget::Investigation => "investigation",
+            CommentTarget::Question => "question",
+            CommentTarget::ActionItem => "action_item",
+        }
+    }
+}
+
+// bodies are stored as raw markdown and rendered by the front-end
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct Comment {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub author: Uuid,
+    // every comment is tied back to its investigation so followers can be notified
+    pub investigation: Uuid,
+    pub target: Uuid,
+    pub target_type: String,
+    pub parent: Option<Uuid>,
+    pub body: String,
+    pub edited: Option<DateTime<Utc>>,
+}
+
+// a previous body of a comment, saved each time the comment is edited
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct CommentRevision {
+    pub id: Uuid,
+    pub comment: Uuid,
+    pub created: DateTime<Utc>,
+    pub body: String,
+}
+
+impl Comment {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Comment> {
+        sqlx::query_as!(Comment, "SELECT * FROM comments WHERE id = $1", id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(Error::from)
+    }
+
+    // ordered oldest-first, so replies can be threaded under their `parent` as they're read
+    pub async fn get_for_target(
+        State(state): State<AppState>,
+        target_type: CommentTarget,
+        target: Uuid,
+    ) -> Result<Vec<Comment>> {
+        sqlx::query_as!(
+            Comment,
+            "SELECT * FROM comments WHERE target = $1 AND target_type = $2 ORDER BY created ASC",
+            target,
+            target_type.as_str()
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        target_type: CommentTarget,
+        target: Uuid,
+        parent: Option<Uuid>,
+        body: &str,
+    ) -> Result<Comment> {
+        let mut tx = state.db.begin().await?;
+
+        let investigation = investigations::investigation_of(&mut tx, target_type.as_str(), target)
+            .await?
+            .ok_or(CommentError::TargetNotFound)?;
+
+        // replies must stay within the thread they're replying to
+        if let Some(parent) = parent {
+            let parent = sqlx::query_as!(Comment, "SELECT * FROM comments WHERE id = $1", parent)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(CommentError::InvalidParent)?;
+            if parent.target != target || parent.target_type != target_type.as_str() {
+                return Err(CommentError::InvalidParent.into());
+            }
+        }
+
+        let comment = sqlx::query_as!(
+            Comment,
+            "INSERT INTO comments (id, created, author, investigation, target, target_type, parent, body) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            Utc::now(),
+            user.id,
+            investigation,
+            target,
+            target_type.as_str(),
+            parent,
+            body,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        log::record_in(
+            &mut tx,
+            user.id,
+            comment.id,
+            "comment",
+            None,
+            Some(&comment),
+            "Comment created",
+        )
+        .await?;
+        tx.commit().await?;
+
+        notifications::comment_created(State(state), &user, &comment).await;
+
+        Ok(comment)
+    }
+
+    pub async fn edit(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        body: &str,
+    ) -> Result<()> {
+        if self.author != user.id {
+            return Err(Error::msg("Only the author can edit a comment"));
+        }
+
+        let previous = self.clone();
+
+        let mut tx = state.db.begin().await?;
+        sqlx::query!(
+            "INSERT INTO comment_revisions (id, comment, created, body) VALUES ($1, $2, $3, $4)",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            self.id,
+            Utc::now(),
+            self.body,
+        )
+        .execute(&mut *tx)
+        .await?;
+        let comment = sqlx::query_as!(
+            Comment,
+            "UPDATE comments SET body = $1, edited = $2 WHERE id = $3 RETURNING *",
+            body,
+            Utc::now(),
+            self.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        log::record_in(
+            &mut tx,
+            user.id,
+            self.id,
+            "comment",
+            Some(&previous),
+            Some(&comment),
+            "Comment edited",
+        )
+        .await?;
+        tx.commit().await?;
+
+        *self = comment;
+
+        // only newly-added mentions are notified, so fixing a typo doesn't re-notify everyone
+        let previous_mentions = parse_mentions(&previous.body);
+        let new_mentions = parse_mentions(&self.body)
+            .into_iter()
+            .filter(|m| !previous_mentions.contains(m))
+            .collect::<Vec<String>>();
+        notifications::mentioned(State(state), &user, self, &new_mentions).await;
+
+        Ok(())
+    }
+
+    pub async fn get_revisions(
+        &self,
+        State(state): State<AppState>,
+    ) -> Result<Vec<CommentRevision>> {
+        sqlx::query_as!(
+            CommentRevision,
+            "SELECT * FROM comment_revisions WHERE comment = $1 ORDER BY created ASC",
+            self.id
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+}
+
+fn is_address_char(c: char) -> bool {
+    c.is_alphanumeric() || "._%+-@".contains(c)
+}
+
+// mentions are written as `@` followed by the user's email address, e.g. `@jane@example.com`.
+// They can be wrapped in punctuation, like `(@jane@example.com)` or `@jane@example.com.`
+pub fn parse_mentions(body: &str) -> Vec<String> {
+    let mut mentions = body
+        .split(|c: char| !is_address_char(c))
+        .filter_map(|word| word.strip_prefix('@'))
+        .map(|word| word.trim_end_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| {
+            word.split_once('@')
+                .is_some_and(|(name, domain)| !name.is_empty() && !domain.is_empty())
+        })
+        .map(|word| word.to_string())
+        .collect::<Vec<String>>();
+    mentions.sort();
+    mentions.dedup();
+    mentions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_mentions;
+
+    #[test]
+    fn mention_at_end_of_body() {
+        assert_eq!(
+            parse_mentions("thanks @jane@example.com"),
+            vec!["jane@example.com"]
+        );
+        assert_eq!(
+            parse_mentions("@jane@example.com"),
+            vec!["jane@example.com"]
+        );
+    }
+
+    #[test]
+    fn mention_followed_by_punctuation() {
+        for body in [
+            "ask @jane@example.com.",
+            "ask @jane@example.com, then wait",
+            "did you ask @jane@example.com?",
+            "ask @jane@example.com!",
+            "@jane@example.com: can you check?",
+            "ask @jane@example.com's team",
+        ] {
+            assert_eq!(parse_mentions(body), vec!["jane@example.com"], "{}", body);
+        }
+    }
+
+    #[test]
+    fn mention_wrapped_in_punctuation() {
+        assert_eq!(
+            parse_mentions("(cc @jane@example.com)"),
+            vec!["jane@example.com"]
+        );
+        assert_eq!(
+            parse_mentions("@jane@example.com,@bob@example.org"),
+            vec!["bob@example.org", "jane@example.com"]
+        );
+    }
+
+    #[test]
+    fn repeated_mentions_are_returned_once() {
+        assert_eq!(
+            parse_mentions("@jane@example.com and @jane@example.com again"),
+            vec!["jane@example.com"]
+        );
+    }
+
+    #[test]
+    fn not_mentions() {
+        assert!(parse_mentions("email jane@example.com directly").is_empty());
+        assert!(parse_mentions("@jane on its own").is_empty());
+        assert!(parse_mentions("@@example.com").is_empty());
+        assert!(parse_mentions("@jane@ is incomplete").is_empty());
+        assert!(parse_mentions("").is_empty());
+    }
+}
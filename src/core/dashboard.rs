@@ -0,0 +1,198 @@
+use anyhow::Result;
+use axum::extract::State;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::investigations::{full_name, ActionItem};
+use crate::AppState;
+
+use std::collections::HashMap;
+
+// an action item along with enough of its question and investigation to display it on its own
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ActionItemWithContext {
+    pub action_item: ActionItem,
+    pub question_pretty_id: String,
+    pub question_summary: String,
+    pub investigation: Uuid,
+    pub investigation_name: String,
+}
+
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct WorkCounts {
+    pub assigned: usize,
+    pub overdue: usize,
+    pub unassigned_in_my_cases: usize,
+    pub by_status: HashMap<String, usize>,
+}
+
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct MyWork {
+    pub counts: WorkCounts,
+    // each group is ordered by urgency
+    pub by_status: HashMap<String, Vec<ActionItemWithContext>>,
+    pub unassigned_in_my_cases: Vec<ActionItemWithContext>,
+}
+
+// flat row shared by the queries below, since `query_as!` can't build nested structs
+struct ContextRow {
+    id: Uuid,
+    created: DateTime<Utc>,
+    creator: Uuid,
+    pretty_id: String,
+    summary: String,
+    details: Option<String>,
+    outcome: Option<String>,
+    assignee: Option<Uuid>,
+    question: Uuid,
+    status: String,
+    assigned: Option<DateTime<Utc>>,
+    resolved: Option<DateTime<Utc>>,
+    due_date: Option<NaiveDate>,
+    priority: String,
+    estimated_hours: Option<f32>,
+    question_pretty_id: String,
+    question_summary: String,
+    investigation: Uuid,
+    first_name: String,
+    middle_name: Option<String>,
+    last_name: String,
+}
+
+impl From<ContextRow> for ActionItemWithContext {
+    fn from(row: ContextRow) -> Self {
+        ActionItemWithContext {
+            investigation_name: full_name(
+                &row.first_name,
+                row.middle_name.as_deref(),
+                &row.last_name,
+            ),
+            question_pretty_id: row.question_pretty_id,
+            question_summary: row.question_summary,
+            investigation: row.investigation,
+            action_item: ActionItem {
+                id: row.id,
+                created: row.created,
+                creator: row.creator,
+                pretty_id: row.pretty_id,
+                summary: row.summary,
+                details: row.details,
+                outcome: row.outcome,
+                assignee: row.assignee,
+                question: row.question,
+                status: row.status,
+                assigned: row.assigned,
+                resolved: row.resolved,
+                due_date: row.due_date,
+                priority: row.priority,
+                estimated_hours: row.estimated_hours,
+            },
+        }
+    }
+}
+
+pub async fn get_assigned(
+    State(state): State<AppState>,
+    user: Uuid,
+) -> Result<Vec<ActionItemWithContext>> {
+    let mut res = sqlx::query_as!(
+        ContextRow,
+        r#"SELECT
+            action_items.*,
+            questions.pretty_id         as question_pretty_id,
+            questions.summary           as question_summary,
+            investigations.id           as investigation,
+            investigations.first_name,
+            investigations.middle_name,
+            investigations.last_name
+        FROM action_items
+        JOIN questions ON questions.id = action_items.question
+        JOIN investigations ON investigations.id = questions.investigation
+        WHERE action_items.assignee = $1"#,
+        user
+    )
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .map(ActionItemWithContext::from)
+    .collect::<Vec<ActionItemWithContext>>();
+    res.sort_by(|a, b| ActionItem::cmp_urgency(&a.action_item, &b.action_item));
+    Ok(res)
+}
+
+// "my cases" are the investigations the user created or follows
+pub async fn get_unassigned_in_my_cases(
+    State(state): State<AppState>,
+    user: Uuid,
+) -> Result<Vec<ActionItemWithContext>> {
+    let mut res = sqlx::query_as!(
+        ContextRow,
+        r#"SELECT
+            action_items.*,
+            questions.pretty_id         as question_pretty_id,
+            questions.summary           as question_summary,
+            investigations.id           as investigation,
+            investigations.first_name,
+            investigations.middle_name,
+            investigations.last_name
+        FROM action_items
+        JOIN questions ON questions.id = action_items.question
+        JOIN investigations ON investigations.id = questions.investigation
+        WHERE action_items.assignee IS NULL
+            AND action_items.resolved IS NULL
+            AND (
+                investigations.creator = $1
+                OR investigations.id IN (
+                    SELECT investigation FROM investigation_followers WHERE "user" = $1
+                )
+            )"#,
+        user
+    )
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .map(ActionItemWithContext::from)
+    .collect::<Vec<ActionItemWithContext>>();
+    res.sort_by(|a, b| ActionItem::cmp_urgency(&a.action_item, &b.action_item));
+    Ok(res)
+}
+
+pub async fn get_my_work(State(state): State<AppState>, user: Uuid) -> Result<MyWork> {
+    let assigned = get_assigned(State(state.clone()), user).await?;
+    let unassigned_in_my_cases = get_unassigned_in_my_cases(State(state), user).await?;
+
+    let mut counts = WorkCounts {
+        assigned: assigned.len(),
+        overdue: assigned
+            .iter()
+            .filter(|a| a.action_item.is_overdue())
+            .count(),
+        unassigned_in_my_cases: unassigned_in_my_cases.len(),
+        by_status: HashMap::new(),
+    };
+
+    // `assigned` is already sorted, so each group stays in urgency order
+    let mut by_status: HashMap<String, Vec<ActionItemWithContext>> = HashMap::new();
+    for item in assigned {
+        *counts
+            .by_status
+            .entry(item.action_item.status.clone())
+            .or_default() += 1;
+        by_status
+            .entry(item.action_item.status.clone())
+            .or_default()
+            .push(item);
+    }
+
+    Ok(MyWork {
+        counts,
+        by_status,
+        unassigned_in_my_cases,
+    })
+}
@@ -0,0 +1,385 @@
+use anyhow::Result;
+use axum::{extract::State, Extension};
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::PgConnection;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{aliases::Alias, investigations::Investigation, log, users::User};
+use crate::AppState;
+
+use std::collections::HashSet;
+
+// why two investigations couldn't be merged, for the errors that are the client's fault
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("Cannot merge an investigation into itself")]
+    SameInvestigation,
+    #[error("No such investigation")]
+    NotFound,
+    #[error("Investigation has already been merged")]
+    AlreadyMerged,
+    #[error("Both investigations have a profile")]
+    ProfilesConflict,
+}
+
+// candidates scoring below this aren't worth a human's time
+const DUPLICATE_THRESHOLD: f32 = 0.45;
+
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct DuplicateCandidate {
+    pub id: Uuid,
+    pub first_name: String,
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    pub date_of_birth: NaiveDate,
+    pub missing_since: NaiveDate,
+    // the alias that matched, or none if it was the investigation's own name
+    pub matched_alias: Option<Uuid>,
+    // trigram similarity of the full names, from 0 to 1
+    pub name_similarity: f32,
+    // whether the names sound alike (Double Metaphone on the last name, plus Double Metaphone or
+    // Soundex on the first name)
+    pub phonetic_match: bool,
+    pub date_of_birth_days_apart: i32,
+    // overall likelihood of being the same person, from 0 to 1
+    pub score: f32,
+}
+
+impl DuplicateCandidate {
+    fn calculate_score(&mut self) {
+        // dates of birth are often misreported by a few days or months, but rarely by years
+        let dob_score = (1.0 - self.date_of_birth_days_apart as f32 / 365.0).max(0.0);
+        let phonetic_score = if self.phonetic_match { 1.0 } else { 0.0 };
+        self.score = 0.5 * self.name_similarity + 0.25 * phonetic_score + 0.25 * dob_score;
+    }
+}
+
+// `exclude` keeps an investigation from matching itself. Every alias of an existing investigation
+// is matched as well, with any missing name or date of birth filled in from the investigation
+pub async fn find_duplicates(
+    State(state): State<AppState>,
+    first_name: &str,
+    last_name: &str,
+    date_of_birth: NaiveDate,
+    exclude: Option<Uuid>,
+) -> Result<Vec<DuplicateCandidate>> {
+    let mut candidates = sqlx::query_as!(
+        DuplicateCandidate,
+        r#"WITH identities AS (
+            SELECT id AS investigation, NULL::uuid AS alias, first_name, last_name, date_of_birth
+            FROM investigations
+            WHERE merged_into IS NULL
+            UNION ALL
+            SELECT
+                investigations.id,
+                investigation_aliases.id,
+                coalesce(investigation_aliases.first_name, investigations.first_name),
+                coalesce(investigation_aliases.last_name, investigations.last_name),
+                coalesce(investigation_aliases.date_of_birth, investigations.date_of_birth)
+            FROM investigation_aliases
+            JOIN investigations ON investigations.id = investigation_aliases.investigation
+            WHERE investigations.merged_into IS NULL
+        )
+        SELECT
+            investigations.id,
+            investigations.first_name,
+            investigations.middle_name,
+            investigations.last_name,
+            investigations.date_of_birth,
+            investigations.missing_since,
+            identities.alias as matched_alias,
+            similarity(lower(identities.first_name || ' ' || identities.last_name), lower($1::text || ' ' || $2::text)) as "name_similarity!",
+            (
+                dmetaphone(identities.last_name) = dmetaphone($2::text)
+                AND (dmetaphone(identities.first_name) = dmetaphone($1::text) OR soundex(identities.first_name) = soundex($1::text))
+            ) as "phonetic_match!",
+            abs(identities.date_of_birth - $3::date) as "date_of_birth_days_apart!",
+            0::real as "score!"
+        FROM identities
+        JOIN investigations ON investigations.id = identities.investigation
+        WHERE ($4::uuid IS NULL OR investigations.id <> $4)
+            AND (
+                lower(identities.first_name || ' ' || identities.last_name) % lower($1::text || ' ' || $2::text)
+                OR dmetaphone(identities.last_name) = dmetaphone($2::text)
+            )"#,
+        first_name,
+        last_name,
+        date_of_birth,
+        exclude,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    candidates.iter_mut().for_each(|c| c.calculate_score());
+    candidates.retain(|c| c.score >= DUPLICATE_THRESHOLD);
+    Ok(best_per_investigation(candidates))
+}
+
+// checks an existing investigation, under its own name and each of its aliases
+pub async fn find_duplicates_of(
+    State(state): State<AppState>,
+    investigation: Uuid,
+) -> Result<Vec<DuplicateCandidate>> {
+    let investigation =
+        Investigation::get(State(state.clone()), &investigation.to_string(), false).await?;
+    let aliases = Alias::get_for_investigation(State(state.clone()), investigation.id).await?;
+
+    let mut identities = vec![(
+        investigation.first_name.clone(),
+        investigation.last_name.clone(),
+        investigation.date_of_birth,
+    )];
+    for alias in aliases {
+        identities.push((
+            alias.first_name.unwrap_or(investigation.first_name.clone()),
+            alias.last_name.unwrap_or(investigation.last_name.clone()),
+            alias.date_of_birth.unwrap_or(investigation.date_of_birth),
+        ));
+    }
+
+    let mut candidates = vec![];
+    for (first_name, last_name, date_of_birth) in identities {
+        candidates.extend(
+            find_duplicates(
+                State(state.clone()),
+                &first_name,
+                &last_name,
+                date_of_birth,
+                Some(investigation.id),
+            )
+            .await?,
+        );
+    }
+    Ok(best_per_investigation(candidates))
+}
+
+// an investigation can match through several identities, only its strongest match is kept
+fn best_per_investigation(mut candidates: Vec<DuplicateCandidate>) -> Vec<DuplicateCandidate> {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut seen = HashSet::new();
+    candidates.retain(|c| seen.insert(c.id));
+    candidates
+}
+
+// an investigation only has one profile, and there's no sensible way to combine two of them
+// automatically, so one has to be cleared up by hand before merging
+async fn profiles_conflict(conn: &mut PgConnection, source: Uuid, target: Uuid) -> Result<bool> {
+    let profiles = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM investigation_profiles WHERE investigation = $1 OR investigation = $2"#,
+        source,
+        target
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(profiles > 1)
+}
+
+// moves everything attached to `source` into `target`, leaving `source` in place (but hidden) so
+// its history and audit entries still resolve. The source's own revisions and status changes stay
+// with it, since they describe that record rather than the case
+pub async fn merge(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    source: Uuid,
+    target: Uuid,
+) -> Result<()> {
+    if source == target {
+        return Err(MergeError::SameInvestigation.into());
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    // both are locked, in a fixed order so two merges of the same pair can't deadlock, before
+    // they're checked, so neither can be merged elsewhere or given a profile part way through
+    let mut locked: Vec<Investigation> = sqlx::query_as(
+        "SELECT * FROM investigations WHERE id = $1 OR id = $2 ORDER BY id FOR UPDATE",
+    )
+    .bind(source)
+    .bind(target)
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut take = |id: Uuid| {
+        let index = locked.iter().position(|inv| inv.id == id);
+        index
+            .map(|index| locked.swap_remove(index))
+            .ok_or(MergeError::NotFound)
+    };
+    let source_investigation = take(source)?;
+    let target_investigation = take(target)?;
+    if source_investigation.merged_into.is_some() || target_investigation.merged_into.is_some() {
+        return Err(MergeError::AlreadyMerged.into());
+    }
+    if profiles_conflict(&mut tx, source, target).await? {
+        return Err(MergeError::ProfilesConflict.into());
+    }
+
+    // action items follow their questions
+    sqlx::query!(
+        "UPDATE questions SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE comments SET target = $1 WHERE target = $2 AND target_type = 'investigation'",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "UPDATE comments SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE attachments SET target = $1 WHERE target = $2 AND target_type = 'investigation'",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "UPDATE attachments SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO investigation_entities (investigation, entity, created, creator, role, notes)
+        SELECT $1, entity, created, creator, role, notes FROM investigation_entities WHERE investigation = $2
+        ON CONFLICT DO NOTHING"#,
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM investigation_entities WHERE investigation = $1",
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "UPDATE entity_relationships SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE timeline_events SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE tips SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // only one of them can have a profile by now
+    sqlx::query!(
+        "UPDATE investigation_profiles SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "UPDATE investigation_profile_revisions SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO investigation_followers (investigation, "user", created)
+        SELECT $1, "user", created FROM investigation_followers WHERE investigation = $2
+        ON CONFLICT DO NOTHING"#,
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM investigation_followers WHERE investigation = $1",
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // the merged record's identity is most likely another name the subject went by, so it's kept
+    // as an alias of the surviving investigation
+    sqlx::query!(
+        "UPDATE investigation_aliases SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO investigation_aliases (id, created, creator, investigation, kind, first_name, middle_name, last_name, date_of_birth, notes) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        Uuid::new_v7(Timestamp::now(NoContext)),
+        Utc::now(),
+        user.id,
+        target,
+        "alternate_identity",
+        source_investigation.first_name,
+        source_investigation.middle_name,
+        source_investigation.last_name,
+        source_investigation.date_of_birth,
+        format!("Merged from investigation {}", source),
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE investigations SET merged_into = $1 WHERE id = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // the triggers re-index anything that was updated above, but action items weren't touched
+    sqlx::query!(
+        "UPDATE search_documents SET investigation = $1 WHERE investigation = $2",
+        target,
+        source
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    log::record_in(
+        &mut tx,
+        user.id,
+        source,
+        "investigation",
+        Some(&source_investigation),
+        Some(&target_investigation),
+        "Investigation merged",
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,585 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{
+    investigations::{full_name, Investigation},
+    log,
+    users::User,
+};
+use crate::AppState;
+
+use std::collections::HashSet;
+
+pub const ENTITY_KINDS: [&str; 2] = ["person", "organization"];
+
+// how an entity is involved in a particular investigation
+pub const ROLES: [&str; 8] = [
+    "family",
+    "friend",
+    "witness",
+    "person_of_interest",
+    "reporting_party",
+    "agency",
+    "media",
+    "other",
+];
+
+// read as "<from> <kind> <to>", e.g. "Jane Doe parent_of <subject>"
+pub const RELATIONSHIP_KINDS: [&str; 17] = [
+    "parent_of",
+    "child_of",
+    "sibling_of",
+    "spouse_of",
+    "partner_of",
+    "relative_of",
+    "friend_of",
+    "associate_of",
+    "employer_of",
+    "employee_of",
+    "member_of",
+    "last_seen_with",
+    "reported_missing",
+    "reporting_agency",
+    "investigating_agency",
+    "media_contact",
+    "other",
+];
+
+const MAX_SEARCH_RESULTS: i64 = 50;
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct Entity {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub kind: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub website: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct EntityDetails {
+    pub kind: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub website: Option<String>,
+    pub notes: Option<String>,
+}
+
+// an entity as it's involved in one investigation
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct LinkedEntity {
+    pub entity: Entity,
+    pub role: String,
+    pub notes: Option<String>,
+}
+
+// an investigation an entity is involved in
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct EntityInvestigation {
+    pub investigation: Uuid,
+    pub investigation_name: String,
+    pub role: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct Relationship {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub investigation: Uuid,
+    pub from_entity: Uuid,
+    // none when the relationship is with the subject of the investigation
+    pub to_entity: Option<Uuid>,
+    pub kind: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum GraphNodeKind {
+    // the missing person the graph is for
+    Subject,
+    Person,
+    Organization,
+    // another investigation sharing an entity with this one
+    Investigation,
+}
+
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct GraphNode {
+    // the entity's id, or the investigation's for subject and investigation nodes
+    pub id: Uuid,
+    pub kind: GraphNodeKind,
+    pub label: String,
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct GraphEdge {
+    // the relationship's id; edges to other investigations don't have one
+    pub id: Option<Uuid>,
+    pub from: Uuid,
+    pub to: Uuid,
+    pub kind: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct EntityGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl EntityDetails {
+    pub fn is_valid(&self) -> bool {
+        ENTITY_KINDS.contains(&self.kind.as_str()) && !self.name.trim().is_empty()
+    }
+}
+
+impl Entity {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Entity> {
+        sqlx::query_as!(Entity, "SELECT * FROM entities WHERE id = $1", id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(Error::from)
+    }
+
+    // matches names loosely so existing entities are found and reused instead of duplicated
+    pub async fn search(State(state): State<AppState>, q: Option<&str>) -> Result<Vec<Entity>> {
+        sqlx::query_as!(
+            Entity,
+            r#"SELECT * FROM entities
+            WHERE $1::text IS NULL OR lower(name) % lower($1) OR name ILIKE '%' || $1 || '%'
+            ORDER BY similarity(lower(name), lower(coalesce($1, ''))) DESC, name ASC
+            LIMIT $2"#,
+            q,
+            MAX_SEARCH_RESULTS,
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: EntityDetails,
+    ) -> Result<Entity> {
+        if !details.is_valid() {
+            return Err(Error::msg("Invalid entity"));
+        }
+
+        let entity = sqlx::query_as!(
+            Entity,
+            "INSERT INTO entities (id, created, creator, kind, name, email, phone, address, website, notes) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            Utc::now(),
+            user.id,
+            details.kind,
+            details.name.trim(),
+            details.email,
+            details.phone,
+            details.address,
+            details.website,
+            details.notes,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            entity.id,
+            "entity",
+            None,
+            Some(&entity),
+            "Entity created",
+        )
+        .await?;
+
+        Ok(entity)
+    }
+
+    pub async fn update(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: EntityDetails,
+    ) -> Result<()> {
+        if !details.is_valid() {
+            return Err(Error::msg("Invalid entity"));
+        }
+
+        let previous = self.clone();
+        *self = sqlx::query_as!(
+            Entity,
+            "UPDATE entities SET kind = $1, name = $2, email = $3, phone = $4, address = $5, website = $6, notes = $7 WHERE id = $8 RETURNING *",
+            details.kind,
+            details.name.trim(),
+            details.email,
+            details.phone,
+            details.address,
+            details.website,
+            details.notes,
+            self.id,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "entity",
+            Some(&previous),
+            Some(self),
+            "Entity updated",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_investigations(
+        &self,
+        State(state): State<AppState>,
+    ) -> Result<Vec<EntityInvestigation>> {
+        sqlx::query_as!(
+            EntityInvestigation,
+            r#"SELECT
+                investigations.id as investigation,
+                concat_ws(' ', investigations.first_name, investigations.middle_name, investigations.last_name) as "investigation_name!",
+                investigation_entities.role,
+                investigation_entities.notes
+            FROM investigation_entities
+            JOIN investigations ON investigations.id = investigation_entities.investigation
+            WHERE investigation_entities.entity = $1 AND investigations.merged_into IS NULL
+            ORDER BY investigations.last_name, investigations.first_name"#,
+            self.id
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+}
+
+pub async fn get_linked(
+    State(state): State<AppState>,
+    investigation: Uuid,
+) -> Result<Vec<LinkedEntity>> {
+    let rows = sqlx::query!(
+        r#"SELECT entities.*, investigation_entities.role, investigation_entities.notes as link_notes
+        FROM investigation_entities
+        JOIN entities ON entities.id = investigation_entities.entity
+        WHERE investigation_entities.investigation = $1
+        ORDER BY entities.name"#,
+        investigation
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LinkedEntity {
+            entity: Entity {
+                id: row.id,
+                created: row.created,
+                creator: row.creator,
+                kind: row.kind,
+                name: row.name,
+                email: row.email,
+                phone: row.phone,
+                address: row.address,
+                website: row.website,
+                notes: row.notes,
+            },
+            role: row.role,
+            notes: row.link_notes,
+        })
+        .collect())
+}
+
+// linking an entity that's already linked just updates its role
+pub async fn link(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    investigation: Uuid,
+    entity: Uuid,
+    role: &str,
+    notes: Option<String>,
+) -> Result<()> {
+    if !ROLES.contains(&role) {
+        return Err(Error::msg("Invalid role"));
+    }
+
+    sqlx::query!(
+        r#"INSERT INTO investigation_entities (investigation, entity, created, creator, role, notes) VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (investigation, entity) DO UPDATE SET role = excluded.role, notes = excluded.notes"#,
+        investigation,
+        entity,
+        Utc::now(),
+        user.id,
+        role,
+        notes,
+    )
+    .execute(&state.db)
+    .await?;
+
+    log::record(
+        State(state),
+        user.id,
+        investigation,
+        "investigation",
+        None,
+        Some(&entity),
+        &format!("Entity linked as {}", role),
+    )
+    .await?;
+
+    Ok(())
+}
+
+// the entity's relationships in this investigation no longer make sense without it, so they go too
+pub async fn unlink(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    investigation: Uuid,
+    entity: Uuid,
+) -> Result<()> {
+    let mut tx = state.db.begin().await?;
+    sqlx::query!(
+        "DELETE FROM entity_relationships WHERE investigation = $1 AND (from_entity = $2 OR to_entity = $2)",
+        investigation,
+        entity
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM investigation_entities WHERE investigation = $1 AND entity = $2",
+        investigation,
+        entity
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    log::record(
+        State(state),
+        user.id,
+        investigation,
+        "investigation",
+        Some(&entity),
+        None,
+        "Entity unlinked",
+    )
+    .await?;
+
+    Ok(())
+}
+
+impl Relationship {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Relationship> {
+        sqlx::query_as!(
+            Relationship,
+            "SELECT * FROM entity_relationships WHERE id = $1",
+            id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn get_for_investigation(
+        State(state): State<AppState>,
+        investigation: Uuid,
+    ) -> Result<Vec<Relationship>> {
+        sqlx::query_as!(
+            Relationship,
+            "SELECT * FROM entity_relationships WHERE investigation = $1 ORDER BY created ASC",
+            investigation
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // both entities have to already be linked to the investigation
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        investigation: Uuid,
+        from_entity: Uuid,
+        to_entity: Option<Uuid>,
+        kind: &str,
+        description: Option<String>,
+    ) -> Result<Relationship> {
+        if !RELATIONSHIP_KINDS.contains(&kind) {
+            return Err(Error::msg("Invalid relationship kind"));
+        }
+        if Some(from_entity) == to_entity {
+            return Err(Error::msg(
+                "An entity can't have a relationship with itself",
+            ));
+        }
+        let linked = sqlx::query_scalar!(
+            "SELECT entity FROM investigation_entities WHERE investigation = $1",
+            investigation
+        )
+        .fetch_all(&state.db)
+        .await?;
+        if !linked.contains(&from_entity) || to_entity.is_some_and(|e| !linked.contains(&e)) {
+            return Err(Error::msg("Entity isn't linked to the investigation"));
+        }
+
+        let relationship = sqlx::query_as!(
+            Relationship,
+            "INSERT INTO entity_relationships (id, created, creator, investigation, from_entity, to_entity, kind, description) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            Utc::now(),
+            user.id,
+            investigation,
+            from_entity,
+            to_entity,
+            kind,
+            description,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            relationship.id,
+            "relationship",
+            None,
+            Some(&relationship),
+            "Relationship created",
+        )
+        .await?;
+
+        Ok(relationship)
+    }
+
+    pub async fn delete(
+        self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<()> {
+        sqlx::query!("DELETE FROM entity_relationships WHERE id = $1", self.id)
+            .execute(&state.db)
+            .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "relationship",
+            Some(&self),
+            None,
+            "Relationship deleted",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+// the subject, every entity linked to the investigation and the relationships between them. With
+// `linked_cases`, other investigations the same entities are involved in are included as well
+pub async fn get_graph(
+    State(state): State<AppState>,
+    investigation: Uuid,
+    linked_cases: bool,
+) -> Result<EntityGraph> {
+    let investigation =
+        Investigation::get(State(state.clone()), &investigation.to_string(), false).await?;
+
+    let mut nodes = vec![GraphNode {
+        id: investigation.id,
+        kind: GraphNodeKind::Subject,
+        label: full_name(
+            &investigation.first_name,
+            investigation.middle_name.as_deref(),
+            &investigation.last_name,
+        ),
+        role: None,
+    }];
+    let linked = get_linked(State(state.clone()), investigation.id).await?;
+    for linked_entity in &linked {
+        nodes.push(GraphNode {
+            id: linked_entity.entity.id,
+            kind: match linked_entity.entity.kind.as_str() {
+                "organization" => GraphNodeKind::Organization,
+                _ => GraphNodeKind::Person,
+            },
+            label: linked_entity.entity.name.clone(),
+            role: Some(linked_entity.role.clone()),
+        });
+    }
+
+    let mut edges = Relationship::get_for_investigation(State(state.clone()), investigation.id)
+        .await?
+        .into_iter()
+        .map(|r| GraphEdge {
+            id: Some(r.id),
+            from: r.from_entity,
+            to: r.to_entity.unwrap_or(investigation.id),
+            kind: r.kind,
+            description: r.description,
+        })
+        .collect::<Vec<GraphEdge>>();
+
+    if linked_cases {
+        let mut seen = HashSet::new();
+        for linked_entity in &linked {
+            for other in linked_entity
+                .entity
+                .get_investigations(State(state.clone()))
+                .await?
+                .into_iter()
+                .filter(|i| i.investigation != investigation.id)
+            {
+                if seen.insert(other.investigation) {
+                    nodes.push(GraphNode {
+                        id: other.investigation,
+                        kind: GraphNodeKind::Investigation,
+                        label: other.investigation_name.clone(),
+                        role: None,
+                    });
+                }
+                edges.push(GraphEdge {
+                    id: None,
+                    from: linked_entity.entity.id,
+                    to: other.investigation,
+                    kind: "involved_in".to_string(),
+                    description: Some(other.role),
+                });
+            }
+        }
+    }
+
+    Ok(EntityGraph { nodes, edges })
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use tracing::*;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::AppState;
+
+// the channel the database triggers notify on
+const CHANNEL: &str = "changes";
+
+// how long to wait before listening again after losing the database connection
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+// something changed on an investigation; clients refetch whatever they're showing of it
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Event {
+    pub target: Uuid,
+    // "investigation", "question", "action_item" or "comment"
+    pub target_type: String,
+    // missing when an action item is deleted along with its question, since the question is
+    // already gone by the time the action item's notification is sent
+    pub investigation: Option<Uuid>,
+    // "created", "updated" or "deleted"
+    pub action: String,
+}
+
+async fn listen(state: &AppState) -> Result<()> {
+    let mut listener = PgListener::connect_with(&state.db).await?;
+    listener.listen(CHANNEL).await?;
+
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<Event>(notification.payload()) {
+            // it's fine for nobody to be connected
+            Ok(event) => {
+                let _ = state.events.send(event);
+            }
+            Err(e) => warn!("Ignoring malformed change notification: {}", e),
+        }
+    }
+}
+
+// runs forever, relaying the database's change notifications to this instance's clients. Changes
+// made while reconnecting are missed, so clients should refetch after a gap
+pub async fn run_relay(state: AppState) {
+    loop {
+        if let Err(e) = listen(&state).await {
+            warn!("Stopped receiving change notifications: {}", e);
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
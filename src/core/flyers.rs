@@ -0,0 +1,745 @@
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{NaiveDate, Utc};
+use handlebars::Handlebars;
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, ImageFormat, Rgb, RgbImage};
+use printpdf::{
+    ColorBits, ColorSpace, Image, ImageFilter, ImageTransform, ImageXObject, Mm, PdfDocument, Px,
+};
+use qrcode::{Color, QrCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::sync::Arc;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::attachments::Attachment;
+use crate::core::investigations::{full_name, Investigation};
+use crate::core::profiles::Profile;
+use crate::core::{helpers, log, users::User};
+use crate::AppState;
+
+const TEMPLATE_DIR: &str = "templates/flyers";
+pub const DEFAULT_TEMPLATE: &str = "missing";
+
+// US letter at print resolution
+const DPI: f32 = 300.0;
+const PAGE_WIDTH: u32 = 2550;
+const PAGE_HEIGHT: u32 = 3300;
+const MARGIN: u32 = 150;
+const PAGE_WIDTH_MM: f32 = 215.9;
+const PAGE_HEIGHT_MM: f32 = 279.4;
+
+// the photo shrinks to make room for the text, but never below this
+const MIN_PHOTO_HEIGHT: u32 = 500;
+const MAX_PHOTO_HEIGHT: u32 = 1500;
+const QR_SIZE: u32 = 420;
+const GAP: u32 = 40;
+
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+const GREY: Rgb<u8> = Rgb([90, 90, 90]);
+const RED: Rgb<u8> = Rgb([178, 18, 18]);
+
+// marks the start of every value filled into a template, so `parse` never reads one as markup
+const LITERAL: char = '\u{E000}';
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum FlyerFormat {
+    #[default]
+    Pdf,
+    Png,
+}
+
+impl FlyerFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FlyerFormat::Pdf => "application/pdf",
+            FlyerFormat::Png => "image/png",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FlyerFormat::Pdf => "pdf",
+            FlyerFormat::Png => "png",
+        }
+    }
+}
+
+// everything a template can put on a flyer; flyers are meant to be posted in public, so nothing
+// internal belongs in here
+#[derive(Debug, Serialize)]
+struct Flyer {
+    name: String,
+    age: u32,
+    age_at_disappearance: u32,
+    date_of_birth: String,
+    missing_since: String,
+    sex: Option<String>,
+    race: Option<String>,
+    height: Option<String>,
+    weight: Option<String>,
+    hair_color: Option<String>,
+    eye_color: Option<String>,
+    distinguishing_marks: Vec<String>,
+    clothing: Option<String>,
+    vehicles: Vec<String>,
+    medical_conditions: Option<String>,
+    last_seen: Option<String>,
+    organization: String,
+    contact: Option<String>,
+    tip_url: String,
+}
+
+// the templates that can be chosen, named after their files
+pub fn templates() -> Result<Vec<String>> {
+    let mut templates = std::fs::read_dir(TEMPLATE_DIR)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            match path.extension().is_some_and(|e| e == "hbs") {
+                true => path.file_stem().map(|s| s.to_string_lossy().to_string()),
+                false => None,
+            }
+        })
+        .collect::<Vec<_>>();
+    templates.sort();
+    Ok(templates)
+}
+
+fn organization() -> String {
+    std::env::var("REPORT_ORGANIZATION").unwrap_or("IntriCase".to_string())
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%B %-d, %Y").to_string()
+}
+
+fn feet_and_inches(cm: f32) -> String {
+    let inches = (cm / 2.54).round() as i32;
+    format!("{}'{}\"", inches / 12, inches % 12)
+}
+
+// heights and weights are stored as ranges, but most of the time both ends are the same
+fn range(min: Option<String>, max: Option<String>) -> Option<String> {
+    match (min, max) {
+        (Some(min), Some(max)) if min != max => Some(format!("{} - {}", min, max)),
+        (Some(value), _) | (None, Some(value)) => Some(value),
+        (None, None) => None,
+    }
+}
+
+fn height(profile: &Profile) -> Option<String> {
+    let imperial = range(
+        profile.height_min_cm.map(feet_and_inches),
+        profile.height_max_cm.map(feet_and_inches),
+    )?;
+    let metric = range(
+        profile.height_min_cm.map(|cm| format!("{:.0}", cm)),
+        profile.height_max_cm.map(|cm| format!("{:.0}", cm)),
+    )?;
+    Some(format!("{} ({} cm)", imperial, metric))
+}
+
+fn weight(profile: &Profile) -> Option<String> {
+    let imperial = range(
+        profile
+            .weight_min_kg
+            .map(|kg| format!("{:.0}", kg * 2.20462)),
+        profile
+            .weight_max_kg
+            .map(|kg| format!("{:.0}", kg * 2.20462)),
+    )?;
+    let metric = range(
+        profile.weight_min_kg.map(|kg| format!("{:.0}", kg)),
+        profile.weight_max_kg.map(|kg| format!("{:.0}", kg)),
+    )?;
+    Some(format!("{} lbs ({} kg)", imperial, metric))
+}
+
+fn join_present(parts: &[&Option<String>], separator: &str) -> Option<String> {
+    let joined = parts
+        .iter()
+        .filter_map(|part| part.as_deref())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator);
+    Some(joined).filter(|j| !j.is_empty())
+}
+
+async fn gather(State(state): State<AppState>, investigation: Uuid) -> Result<Flyer> {
+    let investigation =
+        Investigation::get(State(state.clone()), &investigation.to_string(), false).await?;
+    let profile = Profile::get(State(state), investigation.id).await?;
+    let today = Utc::now().date_naive();
+
+    Ok(Flyer {
+        name: full_name(
+            &investigation.first_name,
+            investigation.middle_name.as_deref(),
+            &investigation.last_name,
+        ),
+        age: today
+            .years_since(investigation.date_of_birth)
+            .unwrap_or_default(),
+        age_at_disappearance: investigation
+            .missing_since
+            .years_since(investigation.date_of_birth)
+            .unwrap_or_default(),
+        date_of_birth: format_date(investigation.date_of_birth),
+        missing_since: format_date(investigation.missing_since),
+        sex: profile.as_ref().and_then(|p| p.sex.clone()),
+        race: profile.as_ref().and_then(|p| p.race.clone()),
+        height: profile.as_ref().and_then(height),
+        weight: profile.as_ref().and_then(weight),
+        hair_color: profile.as_ref().and_then(|p| p.hair_color.clone()),
+        eye_color: profile.as_ref().and_then(|p| p.eye_color.clone()),
+        distinguishing_marks: profile
+            .as_ref()
+            .map(|p| {
+                p.distinguishing_marks
+                    .iter()
+                    .map(|mark| match &mark.location {
+                        Some(location) => {
+                            format!("{} ({}): {}", mark.kind, location, mark.description)
+                        }
+                        None => format!("{}: {}", mark.kind, mark.description),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        clothing: profile.as_ref().and_then(|p| p.clothing.clone()),
+        vehicles: profile
+            .as_ref()
+            .map(|p| {
+                p.vehicles
+                    .iter()
+                    .filter_map(|vehicle| {
+                        let description = join_present(
+                            &[
+                                &vehicle.year.map(|y| y.to_string()),
+                                &vehicle.color,
+                                &vehicle.make,
+                                &vehicle.model,
+                            ],
+                            " ",
+                        );
+                        let plate =
+                            join_present(&[&vehicle.license_plate, &vehicle.license_state], " ")
+                                .map(|plate| format!("plate {}", plate));
+                        join_present(&[&description, &plate], ", ")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        medical_conditions: profile.as_ref().and_then(|p| p.medical_conditions.clone()),
+        last_seen: profile.as_ref().and_then(|p| {
+            join_present(
+                &[
+                    &p.last_known_city,
+                    &p.last_known_state,
+                    &p.last_known_country,
+                ],
+                ", ",
+            )
+        }),
+        organization: organization(),
+        contact: std::env::var("FLYER_CONTACT")
+            .ok()
+            .filter(|c| !c.is_empty()),
+        tip_url: helpers::ui_url(&format!("/tip/{}", investigation.id))?.to_string(),
+    })
+}
+
+// renders a flyer for the investigation from the named template, using the metadata-free copy of
+// the chosen photo so nothing embedded in the original ends up on a public poster
+pub async fn flyer(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    investigation: Uuid,
+    template: &str,
+    photo: Option<Attachment>,
+    format: FlyerFormat,
+) -> Result<Vec<u8>> {
+    let flyer = gather(State(state.clone()), investigation).await?;
+
+    let mut hb = Handlebars::new();
+    // the output is laid out as text rather than displayed as HTML, so values only need to be
+    // kept from turning into markup
+    hb.register_escape_fn(escape);
+    hb.register_template_file(template, format!("{}/{}.hbs", TEMPLATE_DIR, template))?;
+    let text = hb.render(template, &flyer)?;
+
+    let photo = match photo {
+        Some(photo) => Some(
+            photo
+                .read_stripped(State(state.clone()), Extension(user.clone()))
+                .await?
+                .0,
+        ),
+        None => None,
+    };
+
+    let fonts = state.fonts.clone();
+    let title = format!("Missing: {}", flyer.name);
+    let tip_url = flyer.tip_url.clone();
+    let output = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let photo = photo.map(|p| image::load_from_memory(&p)).transpose()?;
+        let page = render(&fonts, &text, photo.map(|p| p.to_rgb8()), &tip_url)?;
+        match format {
+            FlyerFormat::Png => {
+                let mut png = Cursor::new(vec![]);
+                page.write_to(&mut png, ImageFormat::Png)?;
+                Ok(png.into_inner())
+            }
+            FlyerFormat::Pdf => to_pdf(&title, &page),
+        }
+    })
+    .await??;
+
+    log::record(
+        State(state),
+        user.id,
+        investigation,
+        "investigation",
+        None::<&Investigation>,
+        None,
+        &format!("Flyer exported ({}, {})", template, format.extension()),
+    )
+    .await?;
+
+    Ok(output)
+}
+
+pub struct Fonts {
+    regular: FontVec,
+    bold: FontVec,
+}
+
+impl Fonts {
+    // the fonts aren't bundled, since whatever's on the server is usually fine and licenses vary,
+    // so they're loaded once at startup and a missing one stops the server there
+    pub fn load() -> Result<Arc<Fonts>> {
+        let load = |var: &str, default: &str| -> Result<FontVec> {
+            let path = std::env::var(var).unwrap_or(default.to_string());
+            let data = std::fs::read(&path).map_err(|e| {
+                Error::msg(format!(
+                    "Unable to read flyer font {} ({} sets a different one): {}",
+                    path, var, e
+                ))
+            })?;
+            FontVec::try_from_vec(data)
+                .map_err(|_| Error::msg(format!("{} ({}) isn't a usable font", path, var)))
+        };
+        Ok(Arc::new(Fonts {
+            regular: load(
+                "FLYER_FONT",
+                "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+            )?,
+            bold: load(
+                "FLYER_BOLD_FONT",
+                "/usr/share/fonts/truetype/dejavu/DejaVuSans-Bold.ttf",
+            )?,
+        }))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Banner,
+    Title,
+    Heading,
+    Body,
+    Note,
+}
+
+impl Style {
+    // in points
+    fn size(&self) -> f32 {
+        match self {
+            Style::Banner => 60.0,
+            Style::Title => 36.0,
+            Style::Heading => 22.0,
+            Style::Body => 14.0,
+            Style::Note => 11.0,
+        }
+    }
+
+    fn px(&self) -> f32 {
+        self.size() * DPI / 72.0
+    }
+
+    fn line_height(&self) -> u32 {
+        (self.px() * 1.25).ceil() as u32
+    }
+
+    fn font<'a>(&self, fonts: &'a Fonts) -> &'a FontVec {
+        match self {
+            Style::Banner | Style::Title | Style::Heading => &fonts.bold,
+            Style::Body | Style::Note => &fonts.regular,
+        }
+    }
+
+    fn color(&self) -> Rgb<u8> {
+        match self {
+            Style::Banner => WHITE,
+            Style::Title | Style::Heading | Style::Body => BLACK,
+            Style::Note => GREY,
+        }
+    }
+}
+
+enum Block {
+    Text { style: Style, lines: Vec<String> },
+    Gap,
+    Photo,
+    Qr,
+}
+
+impl Block {
+    // the photo is sized last, to whatever room the rest leaves
+    fn height(&self) -> u32 {
+        match self {
+            Block::Text { style, lines } => {
+                let height = style.line_height() * lines.len() as u32;
+                match style {
+                    // the banner has padding above and below the text
+                    Style::Banner => height + style.line_height() / 2,
+                    _ => height,
+                }
+            }
+            Block::Gap => GAP,
+            Block::Photo => 0,
+            Block::Qr => QR_SIZE,
+        }
+    }
+}
+
+fn text_width(font: &FontVec, px: f32, text: &str) -> f32 {
+    let font = font.as_scaled(PxScale::from(px));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, glyph);
+        }
+        width += font.h_advance(glyph);
+        previous = Some(glyph);
+    }
+    width
+}
+
+fn wrap(font: &FontVec, px: f32, text: &str, width: f32) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = match line.is_empty() {
+            true => word.to_string(),
+            false => format!("{} {}", line, word),
+        };
+        if !line.is_empty() && text_width(font, px, &candidate) > width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// keeps a value on the line the template put it on, and marks it so a name like "# MISSING" or a
+// description of "@qr" is drawn as written
+fn escape(value: &str) -> String {
+    let value = value
+        .chars()
+        .filter(|c| *c != LITERAL)
+        .map(|c| match c {
+            '\r' | '\n' => ' ',
+            c => c,
+        })
+        .collect::<String>();
+    format!("{}{}", LITERAL, value)
+}
+
+// turns the rendered template into blocks, following the markup described at the top of each
+// template
+fn parse(fonts: &Fonts, text: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let width = (PAGE_WIDTH - MARGIN * 2) as f32;
+    for raw in text.lines() {
+        let raw = raw.trim();
+        // only the template itself can start a line with markup
+        let literal = raw.starts_with(LITERAL);
+        let raw = raw.replace(LITERAL, "");
+        let raw = raw.trim();
+        if raw.is_empty() {
+            if !matches!(blocks.last(), None | Some(Block::Gap)) {
+                blocks.push(Block::Gap);
+            }
+            continue;
+        }
+        let (style, content) = match raw {
+            _ if literal => (Style::Body, raw),
+            "@photo" => {
+                blocks.push(Block::Photo);
+                continue;
+            }
+            "@qr" => {
+                blocks.push(Block::Qr);
+                continue;
+            }
+            _ if raw.starts_with("# ") => (Style::Banner, &raw[2..]),
+            _ if raw.starts_with("## ") => (Style::Title, &raw[3..]),
+            _ if raw.starts_with("### ") => (Style::Heading, &raw[4..]),
+            _ if raw.starts_with("> ") => (Style::Note, &raw[2..]),
+            _ => (Style::Body, raw),
+        };
+        // the banner runs edge to edge within the margins, so its text gets some padding
+        let width = match style {
+            Style::Banner => width - style.px(),
+            _ => width,
+        };
+        let lines = wrap(style.font(fonts), style.px(), content, width);
+        blocks.push(Block::Text { style, lines });
+    }
+    blocks
+}
+
+// cuts a line short with an ellipsis, dropping words from the end until it fits
+fn ellipsize(font: &FontVec, px: f32, line: &str, width: f32) -> String {
+    let mut words = line.split_whitespace().collect::<Vec<_>>();
+    loop {
+        let candidate = format!("{}…", words.join(" "));
+        if words.len() <= 1 || text_width(font, px, &candidate) <= width {
+            return candidate;
+        }
+        words.pop();
+    }
+}
+
+// long descriptions can run past the bottom of the page, so the longest body text is shortened a
+// line at a time until everything fits around the smallest photo
+fn fit(fonts: &Fonts, blocks: &mut [Block]) -> Result<()> {
+    let available = PAGE_HEIGHT - MARGIN * 2;
+    let photos = blocks.iter().filter(|b| matches!(b, Block::Photo)).count() as u32;
+    let mut shortened = HashSet::new();
+    while blocks.iter().map(Block::height).sum::<u32>() + photos * MIN_PHOTO_HEIGHT > available {
+        let longest = blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| match block {
+                Block::Text {
+                    style: Style::Body,
+                    lines,
+                } if lines.len() > 1 => Some((i, lines.len())),
+                _ => None,
+            })
+            .max_by_key(|(_, lines)| *lines);
+        // only headings and the like are left, which is down to the template
+        let Some((i, _)) = longest else {
+            return Err(Error::msg("Flyer content doesn't fit on one page"));
+        };
+        if let Block::Text { lines, .. } = &mut blocks[i] {
+            lines.pop();
+        }
+        shortened.insert(i);
+    }
+
+    let width = (PAGE_WIDTH - MARGIN * 2) as f32;
+    for i in shortened {
+        if let Block::Text { style, lines } = &mut blocks[i] {
+            if let Some(last) = lines.last_mut() {
+                *last = ellipsize(style.font(fonts), style.px(), last, width);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn fill(page: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for py in y..(y + height).min(page.height()) {
+        for px in x..(x + width).min(page.width()) {
+            page.put_pixel(px, py, color);
+        }
+    }
+}
+
+// draws a line of text centred on the page, with its top at `top`
+fn draw_line(page: &mut RgbImage, fonts: &Fonts, style: Style, top: u32, text: &str) {
+    let px = style.px();
+    let font = style.font(fonts);
+    let scaled = font.as_scaled(PxScale::from(px));
+    let color = style.color();
+    let width = text_width(font, px, text);
+    let mut x = (PAGE_WIDTH as f32 - width) / 2.0;
+    // centre the glyphs vertically in the line
+    let baseline = top as f32
+        + (style.line_height() as f32 - (scaled.ascent() - scaled.descent())) / 2.0
+        + scaled.ascent();
+
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            x += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(PxScale::from(px), point(x, baseline));
+        x += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let (px, py) = (
+                bounds.min.x as i32 + gx as i32,
+                bounds.min.y as i32 + gy as i32,
+            );
+            if px < 0 || py < 0 || px >= page.width() as i32 || py >= page.height() as i32 {
+                return;
+            }
+            let pixel = page.get_pixel_mut(px as u32, py as u32);
+            for i in 0..3 {
+                pixel.0[i] = (pixel.0[i] as f32 * (1.0 - coverage) + color.0[i] as f32 * coverage)
+                    .round() as u8;
+            }
+        });
+    }
+}
+
+fn draw_qr(page: &mut RgbImage, top: u32, url: &str) -> Result<()> {
+    let code = QrCode::new(url.as_bytes())?;
+    // scanners need a light border of a few modules around the code
+    let modules = code.width() as u32 + 8;
+    let module = QR_SIZE / modules;
+    let size = module * modules;
+    let left = (PAGE_WIDTH - size) / 2;
+    let top = top + (QR_SIZE - size) / 2;
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            let (mx, my) = (
+                i as u32 % code.width() as u32,
+                i as u32 / code.width() as u32,
+            );
+            fill(
+                page,
+                left + (mx + 4) * module,
+                top + (my + 4) * module,
+                module,
+                module,
+                BLACK,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn draw_photo(page: &mut RgbImage, fonts: &Fonts, top: u32, height: u32, photo: Option<RgbImage>) {
+    let max_width = PAGE_WIDTH - MARGIN * 2;
+    let Some(photo) = photo else {
+        // leave a box, so the template doesn't fall apart without a photo
+        let width = height * 3 / 4;
+        let left = (PAGE_WIDTH - width) / 2;
+        fill(page, left, top, width, height, Rgb([225, 225, 225]));
+        let line_top = top + (height - Style::Body.line_height()) / 2;
+        draw_line(page, fonts, Style::Body, line_top, "Photo not available");
+        return;
+    };
+    let scale = f32::min(
+        max_width as f32 / photo.width() as f32,
+        height as f32 / photo.height() as f32,
+    );
+    let width = ((photo.width() as f32 * scale).round() as u32).max(1);
+    let scaled_height = ((photo.height() as f32 * scale).round() as u32).max(1);
+    let photo = image::imageops::resize(&photo, width, scaled_height, FilterType::Lanczos3);
+    let left = (PAGE_WIDTH - width) / 2;
+    image::imageops::overlay(
+        page,
+        &photo,
+        left as i64,
+        (top + (height - scaled_height) / 2) as i64,
+    );
+}
+
+fn render(fonts: &Fonts, text: &str, photo: Option<RgbImage>, tip_url: &str) -> Result<RgbImage> {
+    let mut blocks = parse(fonts, text);
+    fit(fonts, &mut blocks)?;
+    let available = PAGE_HEIGHT - MARGIN * 2;
+    let used: u32 = blocks.iter().map(Block::height).sum();
+    let photos = blocks.iter().filter(|b| matches!(b, Block::Photo)).count() as u32;
+    let photo_height = match photos {
+        0 => 0,
+        n => (available.saturating_sub(used) / n).clamp(MIN_PHOTO_HEIGHT, MAX_PHOTO_HEIGHT),
+    };
+
+    let mut page = RgbImage::from_pixel(PAGE_WIDTH, PAGE_HEIGHT, WHITE);
+    let bottom = PAGE_HEIGHT - MARGIN;
+    let mut y = MARGIN;
+    for block in blocks {
+        let height = match block {
+            Block::Photo => photo_height,
+            _ => block.height(),
+        };
+        if y + height > bottom {
+            return Err(Error::msg("Flyer content doesn't fit on one page"));
+        }
+        match block {
+            Block::Text { style, lines } => {
+                let mut line_top = y;
+                if let Style::Banner = style {
+                    fill(&mut page, MARGIN, y, PAGE_WIDTH - MARGIN * 2, height, RED);
+                    line_top += style.line_height() / 4;
+                }
+                for line in lines {
+                    draw_line(&mut page, fonts, style, line_top, &line);
+                    line_top += style.line_height();
+                }
+            }
+            Block::Gap => {}
+            Block::Photo => draw_photo(&mut page, fonts, y, height, photo.clone()),
+            Block::Qr => draw_qr(&mut page, y, tip_url)?,
+        }
+        y += height;
+    }
+    Ok(page)
+}
+
+// the page is embedded as one JPEG, which keeps the PDF small and identical to the PNG
+fn to_pdf(title: &str, page: &RgbImage) -> Result<Vec<u8>> {
+    let mut jpeg = vec![];
+    JpegEncoder::new_with_quality(&mut jpeg, 90).encode_image(page)?;
+
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(title, Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), "Flyer");
+    let layer = doc.get_page(first_page).get_layer(first_layer);
+    Image::from(ImageXObject {
+        width: Px(page.width() as usize),
+        height: Px(page.height() as usize),
+        color_space: ColorSpace::Rgb,
+        bits_per_component: ColorBits::Bit8,
+        interpolate: true,
+        image_data: jpeg,
+        image_filter: Some(ImageFilter::DCT),
+        smask: None,
+        clipping_bbox: None,
+    })
+    .add_to_layer(
+        layer,
+        ImageTransform {
+            dpi: Some(DPI),
+            ..Default::default()
+        },
+    );
+
+    doc.save_to_bytes().map_err(Error::from)
+}
@@ -7,6 +7,7 @@ use lettre::{
 };
 
 use std::collections::HashMap;
+use url::Url;
 
 //TODO: we can apply these with serde during deserialization
 pub fn parse_form_time(time: &str) -> Result<NaiveTime> {
@@ -17,6 +18,13 @@ pub fn parse_form_date(date: &str) -> Result<NaiveDate> {
     Ok(NaiveDate::parse_from_str(date, "%m/%d/%Y")?)
 }
 
+// builds a link to a route in the front-end, which uses hash-based routing
+pub fn ui_url(route: &str) -> Result<Url> {
+    let mut url = Url::parse(&std::env::var("BASE_URL")?)?;
+    url.set_fragment(Some(route));
+    Ok(url)
+}
+
 pub fn smtp_enabled() -> bool {
     (std::env::var("USE_SMTP").unwrap_or("false".to_string())).to_lowercase() == "true"
 }
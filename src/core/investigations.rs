@@ -1,19 +1,31 @@
+use crate::api::investigations::{InvestigationSort, ListInvestigationsQuery};
 use crate::{api::admin::investigations::CreateInvestigationDetails, AppState};
 use anyhow::{Error, Result};
 use axum::{
     extract::{Request, State},
     Extension,
 };
-use chrono::{DateTime, NaiveDate, Utc};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Months, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Postgres, QueryBuilder};
+use tracing::*;
 use ts_rs::TS;
+use utoipa::ToSchema;
 use uuid::{NoContext, Timestamp, Uuid};
 
-use crate::api::admin::investigations::CreateQuestionDetails;
+use crate::api::admin::investigations::{CreateActionItemDetails, CreateQuestionDetails};
+use crate::core::aliases::Alias;
+use crate::core::duplicates::{self, DuplicateCandidate};
+use crate::core::log;
+use crate::core::notifications;
+use crate::core::profiles::Profile;
+use crate::core::question_templates::QuestionTemplate;
+use crate::core::revisions::{self, RevisionTarget, Versioned};
 use crate::core::users::User;
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS)]
+#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS, ToSchema)]
 #[ts(export)]
 pub struct Investigation {
     // core fields
@@ -29,33 +41,294 @@ pub struct Investigation {
     pub namus_id: Option<String>,
     pub missing_since: NaiveDate,
     pub synopsis: String,
+    // set when this investigation was found to be a duplicate and merged into another
+    pub merged_into: Option<Uuid>,
+    pub status: String,
+    // when the status last changed
+    pub status_changed: DateTime<Utc>,
+    // only set while the investigation is resolved or closed
+    pub closure_outcome: Option<String>,
+    pub closure_notes: Option<String>,
+    // not a database column, populated by `get_questions`
+    #[sqlx(skip)]
     pub questions: Option<HashMap<Uuid, Question>>,
+    // not a database column, populated by `get_aliases`
+    #[sqlx(skip)]
+    pub aliases: Option<Vec<Alias>>,
+    // not a database column, populated by `get_profile`
+    #[sqlx(skip)]
+    pub profile: Option<Profile>,
+}
+
+pub fn full_name(first_name: &str, middle_name: Option<&str>, last_name: &str) -> String {
+    match middle_name {
+        Some(middle_name) if !middle_name.is_empty() => {
+            format!("{} {} {}", first_name, middle_name, last_name)
+        }
+        _ => format!("{} {}", first_name, last_name),
+    }
+}
+
+// see the `investigation_visible_to` database function for the rules, which queries that list
+// things from many investigations filter on directly
+pub async fn is_visible_to(
+    conn: &mut PgConnection,
+    investigation: Uuid,
+    user: Uuid,
+) -> Result<bool> {
+    let visible = sqlx::query_scalar!(
+        r#"SELECT investigation_visible_to($1, $2) as "visible!""#,
+        investigation,
+        user
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(visible)
 }
 
-pub async fn get_all(State(state): State<AppState>) -> Result<Vec<Investigation>, sqlx::Error> {
-    let res = sqlx::query!("SELECT * FROM investigations ORDER BY last_name ASC")
+// the investigation that something commented on or attached to belongs to, or `None` if there's
+// no such target
+pub async fn investigation_of(
+    conn: &mut PgConnection,
+    target_type: &str,
+    target: Uuid,
+) -> Result<Option<Uuid>> {
+    let investigation = match target_type {
+        "investigation" => {
+            sqlx::query_scalar!("SELECT id FROM investigations WHERE id = $1", target)
+                .fetch_optional(&mut *conn)
+                .await?
+        }
+        "question" => {
+            sqlx::query_scalar!("SELECT investigation FROM questions WHERE id = $1", target)
+                .fetch_optional(&mut *conn)
+                .await?
+        }
+        "action_item" => {
+            sqlx::query_scalar!(
+                "SELECT questions.investigation FROM action_items JOIN questions ON questions.id = action_items.question WHERE action_items.id = $1",
+                target
+            )
+            .fetch_optional(&mut *conn)
+            .await?
+        }
+        _ => return Err(Error::msg("Unknown target type")),
+    };
+    Ok(investigation)
+}
+
+// a filter or cursor the client got wrong, as opposed to the listing failing
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidListQuery(&'static str);
+
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct InvestigationPage {
+    pub investigations: Vec<Investigation>,
+    // pass this back as `after` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+// every matching investigation in one go, ignoring `after` and `limit`; this is what the listing
+// returned before it was paged, and existing clients still rely on it
+pub async fn list_all(
+    State(state): State<AppState>,
+    query: ListInvestigationsQuery,
+) -> Result<Vec<Investigation>> {
+    let direction = query.direction();
+    let mut qb = filtered(&query)?;
+    qb.push(format!(
+        " ORDER BY {} {}, id {}",
+        query.sort.column(),
+        direction,
+        direction
+    ));
+
+    qb.build_query_as::<Investigation>()
         .fetch_all(&state.db)
-        .await?
-        .into_iter()
-        // we do this because the questions field does not exist in the database
-        // possibly some sqlx trickery possible to make this cleaner, but query_as! does not
-        // work with #[sqlx(skip)], for example
-        .map(|row| Investigation {
-            id: row.id,
-            created: row.created,
-            creator: row.creator,
-            internal_id: row.internal_id,
-            first_name: row.first_name,
-            middle_name: row.middle_name,
-            last_name: row.last_name,
-            date_of_birth: row.date_of_birth,
-            namus_id: row.namus_id,
-            missing_since: row.missing_since,
-            synopsis: row.synopsis,
-            questions: None,
-        })
-        .collect::<Vec<Investigation>>();
-    Ok(res)
+        .await
+        .map_err(Error::from)
+}
+
+// cursors are the sort column's value and the id of the last row on the page, so pages stay stable
+// while investigations are being added. They're encoded so they can go in a URL as they are
+fn encode_cursor(value: &str, id: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}~{}", value, id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<(String, Uuid)> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let (value, id) = decoded.rsplit_once('~')?;
+    Some((value.to_string(), Uuid::parse_str(id).ok()?))
+}
+
+pub async fn list(
+    State(state): State<AppState>,
+    query: ListInvestigationsQuery,
+) -> Result<InvestigationPage> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let column = query.sort.column();
+    let direction = query.direction();
+    let comparison = match query.descending.unwrap_or(false) {
+        true => "<",
+        false => ">",
+    };
+
+    let mut qb = filtered(&query)?;
+
+    if let Some(after) = &query.after {
+        let invalid = || InvalidListQuery("Invalid cursor");
+        let (value, id) = decode_cursor(after).ok_or_else(invalid)?;
+        qb.push(format!(" AND ({}, id) {} (", column, comparison));
+        match query.sort {
+            InvestigationSort::LastName | InvestigationSort::FirstName => {
+                qb.push_bind(value);
+            }
+            InvestigationSort::MissingSince | InvestigationSort::DateOfBirth => {
+                qb.push_bind(NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| invalid())?);
+            }
+            InvestigationSort::Created => {
+                let created = DateTime::parse_from_rfc3339(&value).map_err(|_| invalid())?;
+                qb.push_bind(created.with_timezone(&Utc));
+            }
+        }
+        qb.push(", ").push_bind(id).push(")");
+    }
+
+    // fetch one extra row to find out whether there's another page
+    qb.push(format!(
+        " ORDER BY {} {}, id {} LIMIT ",
+        column, direction, direction
+    ))
+    .push_bind(limit + 1);
+
+    let mut investigations = qb
+        .build_query_as::<Investigation>()
+        .fetch_all(&state.db)
+        .await?;
+
+    let mut next_cursor = None;
+    if investigations.len() as i64 > limit {
+        investigations.truncate(limit as usize);
+        next_cursor = investigations
+            .last()
+            .map(|last| encode_cursor(&query.sort.cursor_value(last), last.id));
+    }
+
+    Ok(InvestigationPage {
+        investigations,
+        next_cursor,
+    })
+}
+
+// the `WHERE` clause shared by the paged and unpaged listings
+fn filtered(query: &ListInvestigationsQuery) -> Result<QueryBuilder<'static, Postgres>> {
+    let today = Utc::now().date_naive();
+    let mut qb: QueryBuilder<Postgres> =
+        QueryBuilder::new("SELECT * FROM investigations WHERE merged_into IS NULL");
+
+    if let Some(creator) = query.creator {
+        qb.push(" AND creator = ").push_bind(creator);
+    }
+    if let Some(missing_after) = query.missing_after {
+        qb.push(" AND missing_since >= ").push_bind(missing_after);
+    }
+    if let Some(missing_before) = query.missing_before {
+        qb.push(" AND missing_since <= ").push_bind(missing_before);
+    }
+    if let Some(born_after) = query.born_after {
+        qb.push(" AND date_of_birth >= ").push_bind(born_after);
+    }
+    if let Some(born_before) = query.born_before {
+        qb.push(" AND date_of_birth <= ").push_bind(born_before);
+    }
+    // ages are as of today, not as of when the person went missing
+    if let Some(min_age) = query.min_age {
+        let latest_birth = min_age
+            .checked_mul(12)
+            .and_then(|months| today.checked_sub_months(Months::new(months)))
+            .ok_or(InvalidListQuery("Invalid minimum age"))?;
+        qb.push(" AND date_of_birth <= ").push_bind(latest_birth);
+    }
+    if let Some(max_age) = query.max_age {
+        let earliest_birth = max_age
+            .checked_add(1)
+            .and_then(|age| age.checked_mul(12))
+            .and_then(|months| today.checked_sub_months(Months::new(months)))
+            .ok_or(InvalidListQuery("Invalid maximum age"))?;
+        qb.push(" AND date_of_birth > ").push_bind(earliest_birth);
+    }
+    if let Some(has_open_questions) = query.has_open_questions {
+        qb.push(match has_open_questions {
+            true => " AND EXISTS",
+            false => " AND NOT EXISTS",
+        });
+        qb.push(" (SELECT 1 FROM questions WHERE questions.investigation = investigations.id AND questions.status <> 'completed')");
+    }
+    if let Some(internal_id) = &query.internal_id {
+        qb.push(" AND internal_id = ")
+            .push_bind(internal_id.clone());
+    }
+    if let Some(namus_id) = &query.namus_id {
+        qb.push(" AND namus_id = ").push_bind(namus_id.clone());
+    }
+    // a comma separated list, e.g. `open,active`
+    if let Some(status) = &query.status {
+        let statuses: Vec<String> = status.split(',').map(|s| s.trim().to_string()).collect();
+        if statuses.iter().any(|s| !STATUSES.contains(&s.as_str())) {
+            return Err(InvalidListQuery("Invalid status").into());
+        }
+        qb.push(" AND status = ANY(").push_bind(statuses).push(")");
+    }
+
+    Ok(qb)
+}
+
+impl ListInvestigationsQuery {
+    fn direction(&self) -> &'static str {
+        match self.descending.unwrap_or(false) {
+            true => "DESC",
+            false => "ASC",
+        }
+    }
+}
+
+impl InvestigationSort {
+    // only ever returns a fixed column name, so it's safe to format into a query
+    fn column(&self) -> &'static str {
+        match self {
+            InvestigationSort::LastName => "last_name",
+            InvestigationSort::FirstName => "first_name",
+            InvestigationSort::Created => "created",
+            InvestigationSort::MissingSince => "missing_since",
+            InvestigationSort::DateOfBirth => "date_of_birth",
+        }
+    }
+
+    fn cursor_value(&self, investigation: &Investigation) -> String {
+        match self {
+            InvestigationSort::LastName => investigation.last_name.clone(),
+            InvestigationSort::FirstName => investigation.first_name.clone(),
+            InvestigationSort::Created => investigation.created.to_rfc3339(),
+            InvestigationSort::MissingSince => investigation.missing_since.to_string(),
+            InvestigationSort::DateOfBirth => investigation.date_of_birth.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct CreatedInvestigation {
+    pub id: Uuid,
+    // existing investigations that may be the same person, most likely first
+    pub possible_duplicates: Vec<DuplicateCandidate>,
 }
 
 impl Investigation {
@@ -63,9 +336,18 @@ impl Investigation {
         State(state): State<AppState>,
         Extension(user): Extension<User>,
         details: CreateInvestigationDetails,
-    ) -> Result<Uuid> {
+    ) -> Result<CreatedInvestigation> {
+        // look the templates up first, so a missing one doesn't leave a half-created investigation
+        let mut templates = vec![];
+        for template in &details.templates {
+            templates.push(QuestionTemplate::get(State(state.clone()), *template).await?);
+        }
+
+        // everything below is saved together, so a failure part way through leaves nothing behind
+        let mut tx = state.db.begin().await?;
+
         let inv = sqlx::query!(
-            "INSERT INTO investigations (id, created, creator, internal_id, first_name, middle_name, last_name, date_of_birth, namus_id, missing_since, synopsis) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING id;",
+            "INSERT INTO investigations (id, created, creator, internal_id, first_name, middle_name, last_name, date_of_birth, namus_id, missing_since, synopsis, status_changed) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $2) RETURNING id;",
             Uuid::new_v7(Timestamp::now(NoContext)),
             Utc::now(),
             user.id,
@@ -77,24 +359,88 @@ impl Investigation {
             details.namus_id,
             details.missing_since,
             details.synopsis,
-        ).fetch_one(&state.db).await?;
-
-        for question in details.questions {
-            let question =
-                Question::create(State(state.clone()), Extension(user.clone()), question).await?;
-            for (_id, action_item) in question.action_items {
-                ActionItem::create(
-                    State(state.clone()),
-                    Extension(user.clone()),
-                    question.id,
-                    action_item.summary,
-                    action_item.details,
-                )
-                .await?;
+        ).fetch_one(&mut *tx).await?;
+
+        let mut assigned = vec![];
+        for mut question in details.questions {
+            let action_items = std::mem::take(&mut question.action_items);
+            // the investigation doesn't exist until now, so whatever the client sent is ignored
+            question.investigation = inv.id;
+            let question = Question::insert(&mut tx, user.id, question).await?;
+            for action_item in action_items {
+                let action_item =
+                    ActionItem::create(&mut tx, user.id, question.id, action_item).await?;
+                if action_item.assignee.is_some() {
+                    assigned.push(action_item);
+                }
             }
         }
 
-        Ok(inv.id)
+        // expanded after the questions that were typed in, so they're numbered after them
+        for template in &templates {
+            template.insert_questions(&mut tx, user.id, inv.id).await?;
+        }
+
+        let profile = match details.profile {
+            Some(profile) => Some(Profile::write(&mut tx, user.id, inv.id, None, profile).await?),
+            None => None,
+        };
+
+        let mut aliases = vec![];
+        for alias in details.aliases {
+            aliases.push(Alias::insert(&mut tx, user.id, inv.id, alias).await?);
+        }
+
+        tx.commit().await?;
+
+        for action_item in &assigned {
+            notifications::action_item_assigned(State(state.clone()), action_item).await;
+        }
+        for template in &templates {
+            template
+                .record_applied(State(state.clone()), user.id, inv.id)
+                .await?;
+        }
+        if let Some(profile) = &profile {
+            log::record(
+                State(state.clone()),
+                user.id,
+                inv.id,
+                "investigation_profile",
+                None,
+                Some(profile),
+                "Profile created",
+            )
+            .await?;
+        }
+        for alias in &aliases {
+            log::record(
+                State(state.clone()),
+                user.id,
+                alias.id,
+                "alias",
+                None,
+                Some(alias),
+                "Alias created",
+            )
+            .await?;
+        }
+
+        // the investigation is already saved, so failing to check it shouldn't fail the request
+        let possible_duplicates = duplicates::find_duplicates_of(State(state), inv.id)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Unable to check investigation {} for duplicates: {}",
+                    inv.id, e
+                );
+                vec![]
+            });
+
+        Ok(CreatedInvestigation {
+            id: inv.id,
+            possible_duplicates,
+        })
     }
 
     pub async fn get(
@@ -122,16 +468,35 @@ impl Investigation {
             namus_id: inv.namus_id,
             missing_since: inv.missing_since,
             synopsis: inv.synopsis,
+            merged_into: inv.merged_into,
+            status: inv.status,
+            status_changed: inv.status_changed,
+            closure_outcome: inv.closure_outcome,
+            closure_notes: inv.closure_notes,
             questions: None,
+            aliases: None,
+            profile: None,
         };
 
         if details {
-            investigation.get_questions(State(state)).await?;
+            investigation.get_questions(State(state.clone())).await?;
+            investigation.get_aliases(State(state.clone())).await?;
+            investigation.get_profile(State(state)).await?;
         }
 
         Ok(investigation)
     }
 
+    pub async fn get_aliases(&mut self, State(state): State<AppState>) -> Result<()> {
+        self.aliases = Some(Alias::get_for_investigation(State(state), self.id).await?);
+        Ok(())
+    }
+
+    pub async fn get_profile(&mut self, State(state): State<AppState>) -> Result<()> {
+        self.profile = Profile::get(State(state), self.id).await?;
+        Ok(())
+    }
+
     pub async fn get_questions(&mut self, State(state): State<AppState>) -> Result<()> {
         // if we're doing this we almost certainly want the action items as well
         let questions_data = sqlx::query!(
@@ -158,7 +523,10 @@ impl Investigation {
                 action_items.question   as action_item_question,
                 action_items.status     as action_item_status,
                 action_items.assigned   as action_item_assigned,
-                action_items.resolved   as action_item_resolved
+                action_items.resolved   as action_item_resolved,
+                action_items.due_date   as action_item_due_date,
+                action_items.priority   as action_item_priority,
+                action_items.estimated_hours as action_item_estimated_hours
 
                 FROM questions
                 LEFT JOIN action_items ON action_items.question = questions.id
@@ -200,6 +568,9 @@ impl Investigation {
                     status: record.action_item_status,
                     assigned: record.action_item_assigned,
                     resolved: record.action_item_resolved,
+                    due_date: record.action_item_due_date,
+                    priority: record.action_item_priority,
+                    estimated_hours: record.action_item_estimated_hours,
                 };
                 question.action_items.insert(action_item_id, action_item);
             }
@@ -211,7 +582,301 @@ impl Investigation {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS)]
+pub const STATUSES: [&str; 7] = [
+    "open",
+    "active",
+    "cold",
+    "suspended",
+    "resolved_located_safe",
+    "resolved_deceased",
+    "closed",
+];
+
+// statuses that end an investigation, which need an outcome and notes
+pub const CLOSED_STATUSES: [&str; 3] = ["resolved_located_safe", "resolved_deceased", "closed"];
+
+pub fn is_closed_status(status: &str) -> bool {
+    CLOSED_STATUSES.contains(&status)
+}
+
+// how a resolved or closed investigation ended; the notes have the details
+pub const CLOSURE_OUTCOMES: [&str; 8] = [
+    "located_by_law_enforcement",
+    "located_by_family",
+    "located_by_volunteers",
+    "returned_voluntarily",
+    "remains_identified",
+    "referred_elsewhere",
+    "insufficient_information",
+    "other",
+];
+
+#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct StatusChange {
+    pub id: Uuid,
+    pub investigation: Uuid,
+    pub changed: DateTime<Utc>,
+    pub changed_by: Uuid,
+    pub previous_status: String,
+    pub status: String,
+    pub outcome: Option<String>,
+    pub notes: Option<String>,
+    pub open_action_items: i32,
+}
+
+#[derive(Debug, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct InvestigationStatusDetails {
+    pub status: String,
+    pub outcome: Option<String>,
+    pub notes: Option<String>,
+    // closing with action items still open has to be asked for explicitly
+    #[serde(default)]
+    pub override_open_action_items: bool,
+}
+
+impl InvestigationStatusDetails {
+    pub fn is_valid(&self) -> bool {
+        let filled = |s: &Option<String>| s.as_deref().is_some_and(|s| !s.trim().is_empty());
+        let known_outcome = self
+            .outcome
+            .as_deref()
+            .is_some_and(|o| CLOSURE_OUTCOMES.contains(&o));
+        STATUSES.contains(&self.status.as_str())
+            && (!is_closed_status(&self.status) || (known_outcome && filled(&self.notes)))
+    }
+}
+
+impl Investigation {
+    pub async fn open_action_items(&self, State(state): State<AppState>) -> Result<i64> {
+        sqlx::query_scalar!(
+            r#"SELECT count(*) as "count!" FROM action_items
+            JOIN questions ON questions.id = action_items.question
+            WHERE questions.investigation = $1 AND action_items.resolved IS NULL"#,
+            self.id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn set_status(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: InvestigationStatusDetails,
+    ) -> Result<StatusChange> {
+        let open_action_items = self.open_action_items(State(state.clone())).await?;
+        if is_closed_status(&details.status)
+            && open_action_items > 0
+            && !details.override_open_action_items
+        {
+            return Err(Error::msg(
+                "Investigation has open action items and the override wasn't given",
+            ));
+        }
+
+        // reopening clears the outcome, the history still has it
+        let (outcome, notes) = match is_closed_status(&details.status) {
+            true => (details.outcome, details.notes),
+            false => (None, None),
+        };
+        let now = Utc::now();
+
+        let previous = self.snapshot()?;
+        let mut tx = state.db.begin().await?;
+        sqlx::query!(
+            "UPDATE investigations SET status = $1, status_changed = $2, closure_outcome = $3, closure_notes = $4 WHERE id = $5",
+            details.status,
+            now,
+            outcome,
+            notes,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let change = sqlx::query_as!(
+            StatusChange,
+            "INSERT INTO investigation_status_changes (id, investigation, changed, changed_by, previous_status, status, outcome, notes, open_action_items)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            self.id,
+            now,
+            user.id,
+            self.status,
+            details.status,
+            outcome,
+            notes,
+            open_action_items as i32,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        self.status = change.status.clone();
+        self.status_changed = change.changed;
+        self.closure_outcome = change.outcome.clone();
+        self.closure_notes = change.notes.clone();
+        revisions::record(&mut tx, user.id, previous, self).await?;
+        tx.commit().await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "investigation",
+            None,
+            Some(&change),
+            &format!(
+                "Investigation status changed from {} to {}",
+                change.previous_status, change.status
+            ),
+        )
+        .await?;
+
+        Ok(change)
+    }
+
+    pub async fn status_history(&self, State(state): State<AppState>) -> Result<Vec<StatusChange>> {
+        sqlx::query_as!(
+            StatusChange,
+            "SELECT * FROM investigation_status_changes WHERE investigation = $1 ORDER BY changed",
+            self.id
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+}
+
+// the fields of an investigation that can be edited once it's created; the status has its own
+// transitions, so restoring an earlier version leaves it alone
+#[derive(Debug, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct InvestigationDetails {
+    pub internal_id: Option<String>,
+    pub first_name: String,
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    pub date_of_birth: NaiveDate,
+    pub namus_id: Option<String>,
+    pub missing_since: NaiveDate,
+    pub synopsis: String,
+}
+
+impl InvestigationDetails {
+    pub fn is_valid(&self) -> bool {
+        !self.first_name.trim().is_empty() && !self.last_name.trim().is_empty()
+    }
+}
+
+impl Versioned for Investigation {
+    const TARGET: RevisionTarget = RevisionTarget::Investigation;
+    const DERIVED: &'static [&'static str] = &["questions", "aliases", "profile"];
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    fn creator(&self) -> Uuid {
+        self.creator
+    }
+}
+
+impl Investigation {
+    pub async fn edit(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: InvestigationDetails,
+    ) -> Result<()> {
+        self.save(
+            State(state),
+            Extension(user),
+            details,
+            "Investigation edited",
+        )
+        .await
+    }
+
+    // saves the earlier version's fields as a new version, so the restore can be undone too
+    pub async fn restore(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        version: i32,
+    ) -> Result<()> {
+        let revision = revisions::get(
+            State(state.clone()),
+            RevisionTarget::Investigation,
+            self.id,
+            version,
+        )
+        .await?;
+        self.save(
+            State(state),
+            Extension(user),
+            revision.details()?,
+            &format!("Investigation restored to version {}", version),
+        )
+        .await
+    }
+
+    async fn save(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: InvestigationDetails,
+        message: &str,
+    ) -> Result<()> {
+        let previous = self.snapshot()?;
+
+        let mut tx = state.db.begin().await?;
+        sqlx::query!(
+            "UPDATE investigations SET internal_id = $1, first_name = $2, middle_name = $3, last_name = $4, date_of_birth = $5, namus_id = $6, missing_since = $7, synopsis = $8 WHERE id = $9",
+            details.internal_id,
+            details.first_name,
+            details.middle_name,
+            details.last_name,
+            details.date_of_birth,
+            details.namus_id,
+            details.missing_since,
+            details.synopsis,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        self.internal_id = details.internal_id;
+        self.first_name = details.first_name;
+        self.middle_name = details.middle_name;
+        self.last_name = details.last_name;
+        self.date_of_birth = details.date_of_birth;
+        self.namus_id = details.namus_id;
+        self.missing_since = details.missing_since;
+        self.synopsis = details.synopsis;
+        revisions::record(&mut tx, user.id, previous.clone(), self).await?;
+        tx.commit().await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "investigation",
+            Some(&previous),
+            Some(&self.snapshot()?),
+            message,
+        )
+        .await
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS, ToSchema)]
 #[ts(export)]
 pub struct Question {
     pub id: Uuid,
@@ -226,7 +891,7 @@ pub struct Question {
     pub action_items: HashMap<Uuid, ActionItem>,
 }
 
-#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS)]
+#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, TS, ToSchema)]
 #[ts(export)]
 pub struct ActionItem {
     pub id: Uuid,
@@ -241,9 +906,36 @@ pub struct ActionItem {
     pub status: String,
     pub assigned: Option<DateTime<Utc>>,
     pub resolved: Option<DateTime<Utc>>,
+    pub due_date: Option<NaiveDate>,
+    pub priority: String,
+    pub estimated_hours: Option<f32>,
 }
 
+// ordered from least to most urgent
+pub const PRIORITIES: [&str; 4] = ["low", "normal", "high", "urgent"];
+
+// shared by questions and action items
+pub const WORK_STATUSES: [&str; 4] = ["unassigned", "not_started", "in_progress", "completed"];
+
 impl Question {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Question> {
+        let res = sqlx::query!("SELECT * FROM questions WHERE id = $1", id)
+            .fetch_one(&state.db)
+            .await?;
+        Ok(Question {
+            id: res.id,
+            creator: res.creator,
+            created: res.created,
+            pretty_id: res.pretty_id,
+            summary: res.summary,
+            details: res.details,
+            investigation: res.investigation,
+            outcome: res.outcome,
+            status: res.status,
+            action_items: HashMap::new(),
+        })
+    }
+
     pub async fn get_action_items(&self, State(state): State<AppState>) -> Result<Vec<ActionItem>> {
         sqlx::query_as!(
             ActionItem,
@@ -255,22 +947,23 @@ impl Question {
         .map_err(Error::from)
     }
 
-    pub async fn create(
-        State(state): State<AppState>,
-        Extension(user): Extension<User>,
+    // the question's action items are left to the caller, see `ActionItem::create`
+    pub async fn insert(
+        conn: &mut PgConnection,
+        creator: Uuid,
         details: CreateQuestionDetails,
     ) -> Result<Question> {
         let res = sqlx::query!(
             "INSERT INTO questions (id, created, creator, pretty_id, summary, details, investigation, status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *;",
             Uuid::new_v7(Timestamp::now(NoContext)),
             Utc::now(),
-            user.id,
+            creator,
             details.pretty_id,
             details.summary,
             details.details,
             details.investigation,
             details.status,
-        ).fetch_one(&state.db).await?;
+        ).fetch_one(&mut *conn).await?;
         Ok(Question {
             id: res.id,
             creator: res.creator,
@@ -287,56 +980,539 @@ impl Question {
     }
 
     pub async fn add_action_item(&self, State(state): State<AppState>) {}
-    pub async fn status(&self, State(state): State<AppState>) {}
+
+    pub async fn set_status(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        status: &str,
+    ) -> Result<()> {
+        if !WORK_STATUSES.contains(&status) {
+            return Err(Error::msg("Invalid question status"));
+        }
+        let previous = self.snapshot()?;
+
+        let mut tx = state.db.begin().await?;
+        sqlx::query!(
+            "UPDATE questions SET status = $1 WHERE id = $2",
+            status,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let previous_status = std::mem::replace(&mut self.status, status.to_string());
+        revisions::record(&mut tx, user.id, previous, self).await?;
+        tx.commit().await?;
+
+        if previous_status != self.status {
+            notifications::question_status_changed(State(state), &user, self, &previous_status)
+                .await;
+        }
+        Ok(())
+    }
+
+    pub async fn edit(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: QuestionDetails,
+    ) -> Result<()> {
+        self.save(State(state), Extension(user), details, "Question edited")
+            .await
+    }
+
+    pub async fn restore(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        version: i32,
+    ) -> Result<()> {
+        let revision = revisions::get(
+            State(state.clone()),
+            RevisionTarget::Question,
+            self.id,
+            version,
+        )
+        .await?;
+        self.save(
+            State(state),
+            Extension(user),
+            revision.details()?,
+            &format!("Question restored to version {}", version),
+        )
+        .await
+    }
+
+    async fn save(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: QuestionDetails,
+        message: &str,
+    ) -> Result<()> {
+        let previous = self.snapshot()?;
+
+        let mut tx = state.db.begin().await?;
+        sqlx::query!(
+            "UPDATE questions SET summary = $1, details = $2, outcome = $3 WHERE id = $4",
+            details.summary,
+            details.details,
+            details.outcome,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        self.summary = details.summary;
+        self.details = details.details;
+        self.outcome = details.outcome;
+        revisions::record(&mut tx, user.id, previous.clone(), self).await?;
+        tx.commit().await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "question",
+            Some(&previous),
+            Some(&self.snapshot()?),
+            message,
+        )
+        .await
+    }
 }
 
 impl ActionItem {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<ActionItem> {
+        sqlx::query_as!(ActionItem, "SELECT * FROM action_items WHERE id = $1", id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(Error::from)
+    }
+
+    // unless given a number, numbered after the question's existing action items, e.g. "3.2".
+    // The question is locked while numbering, so run this in a transaction when adding to a
+    // question others can see
     pub async fn create(
-        State(state): State<AppState>,
-        Extension(user): Extension<User>,
+        conn: &mut PgConnection,
+        creator: Uuid,
         question: Uuid,
-        summary: String,
-        details: Option<String>,
+        details: CreateActionItemDetails,
     ) -> Result<ActionItem> {
+        if !details.is_valid() {
+            return Err(Error::msg("Invalid action item"));
+        }
+        let question_pretty_id = sqlx::query_scalar!(
+            "SELECT pretty_id FROM questions WHERE id = $1 FOR UPDATE",
+            question
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let pretty_id = match details.pretty_id {
+            Some(pretty_id) => {
+                let taken = sqlx::query_scalar!(
+                    r#"SELECT EXISTS (SELECT 1 FROM action_items WHERE question = $1 AND pretty_id = $2) as "taken!""#,
+                    question,
+                    pretty_id
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+                if taken {
+                    return Err(Error::msg("Action item number is already in use"));
+                }
+                pretty_id
+            }
+            None => {
+                let existing = sqlx::query_scalar!(
+                    r#"SELECT count(*) as "count!" FROM action_items WHERE question = $1"#,
+                    question
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+                format!("{}.{}", question_pretty_id, existing + 1)
+            }
+        };
+
+        let now = Utc::now();
+        let status = details.status.unwrap_or_else(|| {
+            match details.assignee {
+                Some(_) => "not_started",
+                None => "unassigned",
+            }
+            .to_string()
+        });
+        let resolved = match status.as_str() {
+            "completed" => Some(details.resolved.unwrap_or(now)),
+            _ => None,
+        };
+
         let res = sqlx::query_as!(
             ActionItem,
-            "INSERT INTO action_items (id, created, creator, pretty_id, summary, details, question, status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *;",
+            "INSERT INTO action_items (id, created, creator, pretty_id, summary, details, outcome, question, status, assignee, assigned, resolved, due_date, priority, estimated_hours) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) RETURNING *;",
             Uuid::new_v7(Timestamp::now(NoContext)),
-            Utc::now(),
-            user.id,
-            "",
-            summary,
-            details,
+            now,
+            creator,
+            pretty_id,
+            details.summary,
+            details.details,
+            details.outcome,
             question,
-            "open"
-        ).fetch_one(&state.db).await?;
+            status,
+            details.assignee,
+            details.assignee.map(|_| now),
+            resolved,
+            details.due_date,
+            details.priority.unwrap_or("normal".to_string()),
+            details.estimated_hours,
+        ).fetch_one(&mut *conn).await?;
         Ok(res)
     }
 
+    // returned with the most urgent items first
     pub async fn get_by_user(State(state): State<AppState>, user: Uuid) -> Result<Vec<ActionItem>> {
-        sqlx::query_as!(
+        let mut action_items = sqlx::query_as!(
             ActionItem,
             "SELECT * FROM action_items WHERE assignee = $1",
             user
         )
         .fetch_all(&state.db)
-        .await
-        .map_err(Error::from)
+        .await?;
+        action_items.sort_by(ActionItem::cmp_urgency);
+        Ok(action_items)
+    }
+
+    pub async fn get_overdue(State(state): State<AppState>) -> Result<Vec<ActionItem>> {
+        let mut action_items = sqlx::query_as!(
+            ActionItem,
+            "SELECT * FROM action_items WHERE action_item_is_overdue(resolved, due_date, $1)",
+            Utc::now().date_naive()
+        )
+        .fetch_all(&state.db)
+        .await?;
+        action_items.sort_by(ActionItem::cmp_urgency);
+        Ok(action_items)
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        ActionItem::overdue(self.due_date, self.resolved)
+    }
+
+    // for rows that only have the dates, rather than a whole action item. Keep this in sync with
+    // the `action_item_is_overdue` database function
+    pub fn overdue(due_date: Option<NaiveDate>, resolved: Option<DateTime<Utc>>) -> bool {
+        match due_date {
+            Some(due_date) => resolved.is_none() && due_date < Utc::now().date_naive(),
+            None => false,
+        }
+    }
+
+    pub fn priority_rank(&self) -> usize {
+        // unrecognized priorities are treated as "normal"
+        PRIORITIES
+            .iter()
+            .position(|p| *p == self.priority)
+            .unwrap_or(1)
+    }
+
+    // overdue items first, then by priority, then by the nearest due date, with undated items last
+    pub fn cmp_urgency(a: &ActionItem, b: &ActionItem) -> std::cmp::Ordering {
+        b.is_overdue()
+            .cmp(&a.is_overdue())
+            .then(b.priority_rank().cmp(&a.priority_rank()))
+            .then(match (a.due_date, b.due_date) {
+                (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            })
+            .then(a.created.cmp(&b.created))
+    }
+
+    pub async fn set_schedule(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        due_date: Option<NaiveDate>,
+        priority: &str,
+        estimated_hours: Option<f32>,
+    ) -> Result<ActionItem> {
+        let mut tx = state.db.begin().await?;
+        let updated = sqlx::query_as!(
+            ActionItem,
+            "UPDATE action_items SET due_date = $1, priority = $2, estimated_hours = $3 WHERE id = $4 RETURNING *",
+            due_date,
+            priority,
+            estimated_hours,
+            self.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        revisions::record(&mut tx, user.id, self.snapshot()?, &updated).await?;
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    // completing an item is what resolves it, and moving it back out of completed reopens it
+    pub async fn set_status(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        status: &str,
+    ) -> Result<ActionItem> {
+        if !WORK_STATUSES.contains(&status) {
+            return Err(Error::msg("Invalid action item status"));
+        }
+        let mut tx = state.db.begin().await?;
+        let updated = sqlx::query_as!(
+            ActionItem,
+            r#"UPDATE action_items SET status = $1,
+                resolved = CASE WHEN $1 = 'completed' THEN coalesce(resolved, $2) END
+            WHERE id = $3 RETURNING *"#,
+            status,
+            Utc::now(),
+            self.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        revisions::record(&mut tx, user.id, self.snapshot()?, &updated).await?;
+        tx.commit().await?;
+        Ok(updated)
     }
 
     pub async fn update(
         &self,
         State(state): State<AppState>,
+        Extension(user): Extension<User>,
         assignee: Uuid,
     ) -> Result<ActionItem> {
-        sqlx::query_as!(
+        let mut tx = state.db.begin().await?;
+        let updated = sqlx::query_as!(
             ActionItem,
-            "UPDATE action_items SET assignee = $1 WHERE id = $2 RETURNING *",
+            // assigning an item is what gets it started
+            r#"UPDATE action_items SET assignee = $1, assigned = $2,
+                status = CASE WHEN status = 'unassigned' THEN 'not_started' ELSE status END
+            WHERE id = $3 RETURNING *"#,
             assignee,
+            Utc::now(),
             self.id
         )
-        .fetch_one(&state.db)
+        .fetch_one(&mut *tx)
+        .await?;
+        revisions::record(&mut tx, user.id, self.snapshot()?, &updated).await?;
+        tx.commit().await?;
+
+        // covers both a first assignment and a reassignment to someone else
+        if self.assignee != Some(assignee) {
+            notifications::action_item_assigned(State(state), &updated).await;
+        }
+        Ok(updated)
+    }
+
+    pub async fn edit(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: ActionItemDetails,
+    ) -> Result<ActionItem> {
+        self.save(State(state), Extension(user), details, "Action item edited")
+            .await
+    }
+
+    pub async fn restore(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        version: i32,
+    ) -> Result<ActionItem> {
+        let revision = revisions::get(
+            State(state.clone()),
+            RevisionTarget::ActionItem,
+            self.id,
+            version,
+        )
+        .await?;
+        self.save(
+            State(state),
+            Extension(user),
+            revision.details()?,
+            &format!("Action item restored to version {}", version),
+        )
         .await
-        .map_err(Error::from)
+    }
+
+    async fn save(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: ActionItemDetails,
+        message: &str,
+    ) -> Result<ActionItem> {
+        let mut tx = state.db.begin().await?;
+        let updated = sqlx::query_as!(
+            ActionItem,
+            "UPDATE action_items SET summary = $1, details = $2, outcome = $3 WHERE id = $4 RETURNING *",
+            details.summary,
+            details.details,
+            details.outcome,
+            self.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        revisions::record(&mut tx, user.id, self.snapshot()?, &updated).await?;
+        tx.commit().await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "action_item",
+            Some(self),
+            Some(&updated),
+            message,
+        )
+        .await?;
+
+        Ok(updated)
+    }
+}
+
+// the fields of a question that can be edited, also used to restore an earlier version
+#[derive(Debug, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct QuestionDetails {
+    pub summary: String,
+    pub details: Option<String>,
+    pub outcome: Option<String>,
+}
+
+impl QuestionDetails {
+    pub fn is_valid(&self) -> bool {
+        !self.summary.trim().is_empty()
+    }
+}
+
+// the fields of an action item that can be edited; scheduling and assignment have their own
+// endpoints, so restoring an earlier version leaves them alone
+#[derive(Debug, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ActionItemDetails {
+    pub summary: String,
+    pub details: Option<String>,
+    pub outcome: Option<String>,
+}
+
+impl ActionItemDetails {
+    pub fn is_valid(&self) -> bool {
+        !self.summary.trim().is_empty()
+    }
+}
+
+impl Versioned for Question {
+    const TARGET: RevisionTarget = RevisionTarget::Question;
+    const DERIVED: &'static [&'static str] = &["action_items"];
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    fn creator(&self) -> Uuid {
+        self.creator
+    }
+}
+
+impl Versioned for ActionItem {
+    const TARGET: RevisionTarget = RevisionTarget::ActionItem;
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    fn creator(&self) -> Uuid {
+        self.creator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use InvestigationStatusDetails as StatusDetails;
+
+    fn status(status: &str, outcome: Option<&str>, notes: Option<&str>) -> StatusDetails {
+        StatusDetails {
+            status: status.to_string(),
+            outcome: outcome.map(str::to_string),
+            notes: notes.map(str::to_string),
+            override_open_action_items: false,
+        }
+    }
+
+    #[test]
+    fn validates_status_changes() {
+        assert!(status("active", None, None).is_valid());
+        assert!(!status("archived", None, None).is_valid());
+        // an outcome given when reopening is ignored rather than checked
+        assert!(status("open", Some("anything"), None).is_valid());
+    }
+
+    #[test]
+    fn closing_needs_a_known_outcome_and_notes() {
+        assert!(status("closed", Some("other"), Some("Family asked us to stop")).is_valid());
+        let found = status(
+            "resolved_located_safe",
+            Some("located_by_family"),
+            Some("Home"),
+        );
+        assert!(found.is_valid());
+        assert!(!status("closed", None, Some("Family asked us to stop")).is_valid());
+        assert!(!status("closed", Some("they came home"), Some("Home")).is_valid());
+        assert!(!status("resolved_deceased", Some("remains_identified"), Some("  ")).is_valid());
+        assert!(!status("resolved_deceased", Some("remains_identified"), None).is_valid());
+    }
+
+    #[test]
+    fn cursors_are_url_safe() {
+        let id = Uuid::new_v7(Timestamp::now(NoContext));
+        let created = "2024-03-01T12:30:00.123+00:00";
+        let cursor = encode_cursor(created, id);
+        assert!(cursor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_cursor(&cursor), Some((created.to_string(), id)));
+        // names can contain the separator
+        let cursor = encode_cursor("O~Brien", id);
+        assert_eq!(decode_cursor(&cursor), Some(("O~Brien".to_string(), id)));
+        assert_eq!(decode_cursor("not a cursor"), None);
+    }
+
+    #[test]
+    fn rejects_ages_that_overflow() {
+        for query in [
+            ListInvestigationsQuery {
+                min_age: Some(u32::MAX),
+                ..Default::default()
+            },
+            ListInvestigationsQuery {
+                max_age: Some(u32::MAX),
+                ..Default::default()
+            },
+        ] {
+            let err = filtered(&query).err().unwrap();
+            assert!(err.downcast_ref::<InvalidListQuery>().is_some());
+        }
+        let query = ListInvestigationsQuery {
+            min_age: Some(18),
+            max_age: Some(65),
+            ..Default::default()
+        };
+        assert!(filtered(&query).is_ok());
     }
 }
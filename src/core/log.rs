@@ -0,0 +1,59 @@
+use anyhow::Result;
+use axum::extract::State;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::PgConnection;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::AppState;
+
+// writes an entry to the audit log - `previous` and `current` are stored as JSON so the log can
+// show exactly what changed
+pub async fn record<T: Serialize>(
+    State(state): State<AppState>,
+    actor: Uuid,
+    target: Uuid,
+    target_type: &str,
+    previous: Option<&T>,
+    current: Option<&T>,
+    message: &str,
+) -> Result<()> {
+    record_in(
+        &mut *state.db.acquire().await?,
+        actor,
+        target,
+        target_type,
+        previous,
+        current,
+        message,
+    )
+    .await
+}
+
+// the same as `record`, for changes made in a transaction: the entry is only kept if the change is
+pub async fn record_in<T: Serialize>(
+    conn: &mut PgConnection,
+    actor: Uuid,
+    target: Uuid,
+    target_type: &str,
+    previous: Option<&T>,
+    current: Option<&T>,
+    message: &str,
+) -> Result<()> {
+    let previous_data = previous.map(serde_json::to_string).transpose()?;
+    let data = current.map(serde_json::to_string).transpose()?;
+    sqlx::query!(
+        "INSERT INTO logs (id, timestamp, actor, target, target_type, previous_data, data, message) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        Uuid::new_v7(Timestamp::now(NoContext)),
+        Utc::now(),
+        actor,
+        target,
+        target_type,
+        previous_data,
+        data,
+        message,
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
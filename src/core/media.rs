@@ -0,0 +1,182 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use exif::{Exif, In, Tag, Value};
+use image::{DynamicImage, ImageFormat};
+use serde_json::{Map, Value as JsonValue};
+
+use std::io::Cursor;
+
+const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Debug, Default)]
+pub struct ExtractedMetadata {
+    pub captured: Option<DateTime<Utc>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    // every primary EXIF field, keyed by tag name, as human-readable values
+    pub exif: JsonValue,
+    pub xmp: Option<String>,
+    pub orientation: u32,
+}
+
+pub struct Derivative {
+    pub data: Vec<u8>,
+    pub mime_type: &'static str,
+}
+
+pub fn extract_metadata(data: &[u8]) -> ExtractedMetadata {
+    let mut metadata = ExtractedMetadata {
+        exif: JsonValue::Object(Map::new()),
+        xmp: extract_xmp(data),
+        orientation: 1,
+        ..Default::default()
+    };
+
+    // plenty of images have no EXIF at all, which isn't an error
+    let Ok(exif) = exif::Reader::new().read_from_container(&mut Cursor::new(data)) else {
+        return metadata;
+    };
+
+    let mut fields = Map::new();
+    for field in exif.fields().filter(|f| f.ifd_num == In::PRIMARY) {
+        fields.insert(
+            field.tag.to_string(),
+            JsonValue::String(field.display_value().with_unit(&exif).to_string()),
+        );
+    }
+    metadata.exif = JsonValue::Object(fields);
+
+    metadata.captured = captured(&exif);
+    metadata.latitude = gps_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S');
+    metadata.longitude = gps_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W');
+    metadata.altitude = altitude(&exif);
+    metadata.camera_make = ascii(&exif, Tag::Make);
+    metadata.camera_model = ascii(&exif, Tag::Model);
+    metadata.orientation = exif
+        .get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        .unwrap_or(1);
+
+    metadata
+}
+
+// re-encoding only carries over pixel data, which drops EXIF, XMP and any other embedded metadata
+pub fn strip_metadata(data: &[u8], orientation: u32) -> Result<Derivative> {
+    let format = image::guess_format(data)?;
+    // the orientation tag is lost along with everything else, so it's applied to the pixels instead
+    let img = apply_orientation(
+        image::load_from_memory_with_format(data, format)?,
+        orientation,
+    );
+
+    match format {
+        ImageFormat::Jpeg => encode(DynamicImage::ImageRgb8(img.to_rgb8()), ImageFormat::Jpeg),
+        _ => encode(img, ImageFormat::Png),
+    }
+}
+
+pub fn thumbnail(data: &[u8], orientation: u32) -> Result<Derivative> {
+    let img = apply_orientation(image::load_from_memory(data)?, orientation)
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    encode(DynamicImage::ImageRgb8(img.to_rgb8()), ImageFormat::Jpeg)
+}
+
+fn encode(img: DynamicImage, format: ImageFormat) -> Result<Derivative> {
+    let mut data = vec![];
+    img.write_to(&mut Cursor::new(&mut data), format)?;
+    Ok(Derivative {
+        data,
+        mime_type: format.to_mime_type(),
+    })
+}
+
+fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(ref values) => values
+            .first()
+            .map(|v| String::from_utf8_lossy(v).trim().to_string())
+            .filter(|v| !v.is_empty()),
+        _ => None,
+    }
+}
+
+fn captured(exif: &Exif) -> Option<DateTime<Utc>> {
+    let Value::Ascii(ref values) = exif.get_field(Tag::DateTimeOriginal, In::PRIMARY)?.value else {
+        return None;
+    };
+    let mut dt = exif::DateTime::from_ascii(values.first()?).ok()?;
+    if let Some(field) = exif.get_field(Tag::OffsetTimeOriginal, In::PRIMARY) {
+        if let Value::Ascii(ref offsets) = field.value {
+            if let Some(offset) = offsets.first() {
+                let _ = dt.parse_offset(offset);
+            }
+        }
+    }
+
+    let naive = NaiveDate::from_ymd_opt(dt.year as i32, dt.month as u32, dt.day as u32)?
+        .and_hms_opt(dt.hour as u32, dt.minute as u32, dt.second as u32)?;
+    // without an offset we can't know the camera's time zone, so the time is stored as-is
+    let offset = Duration::minutes(dt.offset.unwrap_or(0) as i64);
+    Some((naive - offset).and_utc())
+}
+
+// GPS coordinates are stored as degrees/minutes/seconds plus a hemisphere reference
+fn gps_coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative_ref: u8) -> Option<f64> {
+    let Value::Rational(ref parts) = exif.get_field(tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    if parts.len() < 3 {
+        return None;
+    }
+    let mut value = parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0;
+
+    if let Some(field) = exif.get_field(ref_tag, In::PRIMARY) {
+        if let Value::Ascii(ref refs) = field.value {
+            if refs.first().and_then(|r| r.first()) == Some(&negative_ref) {
+                value = -value;
+            }
+        }
+    }
+    Some(value)
+}
+
+fn altitude(exif: &Exif) -> Option<f64> {
+    let Value::Rational(ref parts) = exif.get_field(Tag::GPSAltitude, In::PRIMARY)?.value else {
+        return None;
+    };
+    let altitude = parts.first()?.to_f64();
+    // a reference of 1 means below sea level
+    let below = exif
+        .get_field(Tag::GPSAltitudeRef, In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        == Some(1);
+    Some(if below { -altitude } else { altitude })
+}
+
+// XMP is embedded as a plain XML packet, so it can be found without parsing the container
+fn extract_xmp(data: &[u8]) -> Option<String> {
+    let end_tag = b"</x:xmpmeta>";
+    let start = find(data, b"<x:xmpmeta")?;
+    let end = start + find(&data[start..], end_tag)? + end_tag.len();
+    Some(String::from_utf8_lossy(&data[start..end]).into_owned())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -1,6 +1,28 @@
+pub mod aliases;
+pub mod attachments;
+pub mod bundles;
+pub mod comments;
 pub mod crypto;
+pub mod dashboard;
+pub mod duplicates;
+pub mod entities;
+pub mod events;
+pub mod flyers;
 pub mod helpers;
 pub mod investigations;
 pub mod log;
+pub mod media;
+pub mod namus;
+pub mod notifications;
+pub mod profiles;
+pub mod question_templates;
+pub mod reports;
+pub mod revisions;
+pub mod search;
 pub mod sessions;
+pub mod spreadsheets;
+pub mod storage;
+pub mod timeline;
+pub mod tips;
+pub mod tokens;
 pub mod users;
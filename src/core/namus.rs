@@ -0,0 +1,735 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::api::admin::investigations::CreateInvestigationDetails;
+use crate::core::aliases::AliasDetails;
+use crate::core::investigations::{CreatedInvestigation, Investigation};
+use crate::core::profiles::{DistinguishingMark, Identifier, ProfileDetails, Vehicle};
+use crate::core::users::User;
+use crate::AppState;
+
+use std::path::PathBuf;
+
+// why a case couldn't be imported, for the errors that are the client's fault
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("NamUs case has already been imported")]
+    AlreadyImported,
+}
+
+const CM_PER_INCH: f32 = 2.54;
+const KG_PER_POUND: f32 = 0.453_592_37;
+
+// a case as exported from NamUs' case JSON. Only the fields we map are modelled, anything else in
+// the export is ignored
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamusCase {
+    pub id: i64,
+    pub subject_identification: SubjectIdentification,
+    #[serde(default)]
+    pub subject_description: SubjectDescription,
+    #[serde(default)]
+    pub physical_description: PhysicalDescription,
+    #[serde(default)]
+    pub physical_feature_descriptions: Vec<PhysicalFeatureDescription>,
+    #[serde(default)]
+    pub clothing_and_accessories_articles: Vec<ClothingArticle>,
+    #[serde(default)]
+    pub vehicles: Vec<NamusVehicle>,
+    pub sighting: Sighting,
+    #[serde(default)]
+    pub circumstances: Circumstances,
+    #[serde(default)]
+    pub case_information: CaseInformation,
+}
+
+// NamUs represents most enumerated values as a lookup object
+#[derive(Debug, Deserialize)]
+pub struct Lookup {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectIdentification {
+    pub first_name: String,
+    pub middle_name: Option<String>,
+    pub last_name: String,
+    // comma-separated
+    pub nicknames: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+}
+
+// heights are in inches and weights in pounds
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectDescription {
+    pub sex: Option<Lookup>,
+    pub primary_ethnicity: Option<Lookup>,
+    pub height_from: Option<f32>,
+    pub height_to: Option<f32>,
+    pub weight_from: Option<f32>,
+    pub weight_to: Option<f32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalDescription {
+    pub hair_color: Option<Lookup>,
+    pub left_eye_color: Option<Lookup>,
+    pub right_eye_color: Option<Lookup>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalFeatureDescription {
+    pub physical_feature: Lookup,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClothingArticle {
+    pub article: Lookup,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamusVehicle {
+    pub vehicle_year: Option<i32>,
+    pub vehicle_make: Option<Lookup>,
+    pub vehicle_model: Option<Lookup>,
+    pub vehicle_color: Option<Lookup>,
+    pub tag_number: Option<String>,
+    pub tag_state: Option<Lookup>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sighting {
+    // the date last seen, which is what we track as `missing_since`
+    pub date: NaiveDate,
+    pub address: Option<Address>,
+    pub public_geolocation: Option<Geolocation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    pub address1: Option<String>,
+    pub city: Option<String>,
+    pub county: Option<Lookup>,
+    pub state: Option<Lookup>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Geolocation {
+    pub coordinates: Coordinates,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Coordinates {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Circumstances {
+    pub circumstances_of_disappearance: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaseInformation {
+    pub ncic_number: Option<String>,
+}
+
+// a field where our record and the NamUs record disagree
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Discrepancy {
+    pub field: String,
+    pub ours: Option<String>,
+    pub namus: Option<String>,
+}
+
+pub fn parse(data: &[u8]) -> Result<NamusCase> {
+    Ok(serde_json::from_slice(data)?)
+}
+
+// NamUs IDs are displayed as "MP12345", we store them lowercased
+pub fn normalize_id(namus_id: &str) -> Option<String> {
+    let namus_id = namus_id.trim().to_lowercase();
+    let number = namus_id.strip_prefix("mp")?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(namus_id)
+}
+
+// exports are saved as e.g. `mp12345.json` in NAMUS_EXPORT_PATH
+pub async fn load_export(namus_id: &str) -> Result<NamusCase> {
+    // normalizing also keeps the ID from being used to read arbitrary paths
+    let namus_id = normalize_id(namus_id).ok_or(Error::msg("Invalid NamUs ID"))?;
+    let path =
+        PathBuf::from(std::env::var("NAMUS_EXPORT_PATH")?).join(format!("{}.json", namus_id));
+    parse(&tokio::fs::read(path).await?)
+}
+
+pub async fn find_investigation(
+    State(state): State<AppState>,
+    namus_id: &str,
+) -> Result<Option<Uuid>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM investigations WHERE lower(namus_id) = lower($1) AND merged_into IS NULL",
+        namus_id
+    )
+    .fetch_optional(&state.db)
+    .await
+    .map_err(Error::from)
+}
+
+// takes the case's `to_investigation_details`; creating the investigation is a single
+// transaction, so a failed import can be retried without tripping over its own NamUs ID. Imports
+// of the same case wait on each other, so only the first one gets past the check
+pub async fn import(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    details: CreateInvestigationDetails,
+) -> Result<CreatedInvestigation> {
+    if !details.is_valid() {
+        return Err(Error::msg("Invalid investigation details"));
+    }
+    let namus_id = details
+        .namus_id
+        .as_deref()
+        .ok_or(Error::msg("Investigation has no NamUs ID"))?;
+
+    // held until the investigation has been committed, and released with the transaction even if
+    // this is dropped part way through
+    let mut lock = state.db.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended('namus:' || lower($1), 0))")
+        .bind(namus_id)
+        .execute(&mut *lock)
+        .await?;
+    if find_investigation(State(state.clone()), namus_id)
+        .await?
+        .is_some()
+    {
+        return Err(ImportError::AlreadyImported.into());
+    }
+    let created = Investigation::create(State(state), Extension(user), details).await?;
+    lock.commit().await?;
+    Ok(created)
+}
+
+// compares an investigation against the given NamUs record, or its saved export if none is given
+pub async fn sync_check(
+    State(state): State<AppState>,
+    investigation: Uuid,
+    case: Option<NamusCase>,
+) -> Result<Vec<Discrepancy>> {
+    let investigation = Investigation::get(State(state), &investigation.to_string(), true).await?;
+    let case = match case {
+        Some(case) => case,
+        None => {
+            let namus_id = investigation
+                .namus_id
+                .as_deref()
+                .ok_or(Error::msg("Investigation has no NamUs ID"))?;
+            load_export(namus_id).await?
+        }
+    };
+    Ok(case.discrepancies(&investigation))
+}
+
+impl NamusCase {
+    pub fn namus_id(&self) -> String {
+        format!("mp{}", self.id)
+    }
+
+    pub fn to_investigation_details(&self) -> Result<CreateInvestigationDetails> {
+        let subject = &self.subject_identification;
+        let date_of_birth = subject
+            .date_of_birth
+            .ok_or(Error::msg("NamUs case has no date of birth"))?;
+
+        let aliases = subject
+            .nicknames
+            .iter()
+            .flat_map(|n| n.split(','))
+            .map(|n| n.trim())
+            .filter(|n| !n.is_empty())
+            .map(|n| AliasDetails {
+                kind: "nickname".to_string(),
+                first_name: Some(n.to_string()),
+                middle_name: None,
+                last_name: None,
+                date_of_birth: None,
+                notes: Some("Imported from NamUs".to_string()),
+            })
+            .collect();
+
+        Ok(CreateInvestigationDetails {
+            internal_id: None,
+            first_name: subject.first_name.trim().to_string(),
+            middle_name: non_empty(subject.middle_name.as_deref()),
+            last_name: subject.last_name.trim().to_string(),
+            date_of_birth,
+            namus_id: Some(self.namus_id()),
+            missing_since: self.sighting.date,
+            synopsis: self
+                .circumstances
+                .circumstances_of_disappearance
+                .clone()
+                .unwrap_or_default(),
+            questions: vec![],
+            aliases,
+            profile: Some(self.to_profile_details()),
+            templates: vec![],
+        })
+    }
+
+    pub fn to_profile_details(&self) -> ProfileDetails {
+        let description = &self.subject_description;
+        let address = self.sighting.address.as_ref();
+        let coordinates = self
+            .sighting
+            .public_geolocation
+            .as_ref()
+            .map(|g| &g.coordinates);
+        let clothing = self
+            .clothing_and_accessories_articles
+            .iter()
+            .map(|a| match &a.description {
+                Some(description) => format!("{}: {}", a.article.name, description),
+                None => a.article.name.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        ProfileDetails {
+            sex: description.sex.as_ref().map(|s| {
+                match s.name.to_lowercase().as_str() {
+                    "female" => "female",
+                    "male" => "male",
+                    _ => "unknown",
+                }
+                .to_string()
+            }),
+            race: lookup_name(description.primary_ethnicity.as_ref()),
+            height_min_cm: description.height_from.map(|h| h * CM_PER_INCH),
+            height_max_cm: description.height_to.map(|h| h * CM_PER_INCH),
+            weight_min_kg: description.weight_from.map(|w| w * KG_PER_POUND),
+            weight_max_kg: description.weight_to.map(|w| w * KG_PER_POUND),
+            hair_color: lookup_name(self.physical_description.hair_color.as_ref()),
+            eye_color: eye_color(&self.physical_description),
+            distinguishing_marks: self
+                .physical_feature_descriptions
+                .iter()
+                .map(|f| {
+                    let kind = f.physical_feature.name.to_lowercase();
+                    match kind.as_str() {
+                        "scar" | "tattoo" | "birthmark" | "piercing" | "amputation" => {
+                            DistinguishingMark {
+                                kind,
+                                location: None,
+                                description: f.description.clone().unwrap_or_default(),
+                            }
+                        }
+                        // keep NamUs' name for features we don't have a kind for
+                        _ => DistinguishingMark {
+                            kind: "other".to_string(),
+                            location: None,
+                            description: format!(
+                                "{}: {}",
+                                f.physical_feature.name,
+                                f.description.as_deref().unwrap_or_default()
+                            ),
+                        },
+                    }
+                })
+                .collect(),
+            clothing: non_empty(Some(clothing.as_str())),
+            vehicles: self
+                .vehicles
+                .iter()
+                .map(|v| Vehicle {
+                    year: v.vehicle_year,
+                    make: lookup_name(v.vehicle_make.as_ref()),
+                    model: lookup_name(v.vehicle_model.as_ref()),
+                    color: lookup_name(v.vehicle_color.as_ref()),
+                    license_plate: v.tag_number.clone(),
+                    license_state: lookup_name(v.tag_state.as_ref()),
+                    notes: v.comment.clone(),
+                })
+                .collect(),
+            medical_conditions: None,
+            identifiers: self
+                .case_information
+                .ncic_number
+                .iter()
+                .filter(|n| !n.trim().is_empty())
+                .map(|n| Identifier {
+                    kind: "ncic".to_string(),
+                    value: n.trim().to_string(),
+                })
+                .collect(),
+            last_known_address: address.and_then(|a| non_empty(a.address1.as_deref())),
+            last_known_city: address.and_then(|a| non_empty(a.city.as_deref())),
+            last_known_county: address.and_then(|a| lookup_name(a.county.as_ref())),
+            last_known_state: address.and_then(|a| lookup_name(a.state.as_ref())),
+            last_known_country: address.map(|_| "United States".to_string()),
+            last_known_latitude: coordinates.map(|c| c.lat),
+            last_known_longitude: coordinates.map(|c| c.lon),
+        }
+    }
+
+    // `investigation` should have been fetched with its details so the profile is compared too
+    pub fn discrepancies(&self, investigation: &Investigation) -> Vec<Discrepancy> {
+        let subject = &self.subject_identification;
+        let theirs = self.to_profile_details();
+        let ours = investigation.profile.as_ref();
+
+        let mut discrepancies = vec![];
+        let mut compare = |field: &str, ours: Option<String>, namus: Option<String>| {
+            let normalize = |v: &Option<String>| {
+                v.as_deref()
+                    .map(|v| v.trim().to_lowercase())
+                    .filter(|v| !v.is_empty())
+            };
+            if normalize(&ours) != normalize(&namus) {
+                discrepancies.push(Discrepancy {
+                    field: field.to_string(),
+                    ours,
+                    namus,
+                });
+            }
+        };
+
+        compare(
+            "namus_id",
+            investigation.namus_id.clone(),
+            Some(self.namus_id()),
+        );
+        compare(
+            "first_name",
+            Some(investigation.first_name.clone()),
+            Some(subject.first_name.clone()),
+        );
+        compare(
+            "middle_name",
+            investigation.middle_name.clone(),
+            subject.middle_name.clone(),
+        );
+        compare(
+            "last_name",
+            Some(investigation.last_name.clone()),
+            Some(subject.last_name.clone()),
+        );
+        compare(
+            "date_of_birth",
+            Some(investigation.date_of_birth.to_string()),
+            subject.date_of_birth.map(|d| d.to_string()),
+        );
+        compare(
+            "missing_since",
+            Some(investigation.missing_since.to_string()),
+            Some(self.sighting.date.to_string()),
+        );
+
+        compare("sex", ours.and_then(|p| p.sex.clone()), theirs.sex);
+        compare("race", ours.and_then(|p| p.race.clone()), theirs.race);
+        // units are converted from inches and pounds, so they're compared to the nearest whole unit
+        compare(
+            "height_min_cm",
+            measurement(ours.and_then(|p| p.height_min_cm)),
+            measurement(theirs.height_min_cm),
+        );
+        compare(
+            "height_max_cm",
+            measurement(ours.and_then(|p| p.height_max_cm)),
+            measurement(theirs.height_max_cm),
+        );
+        compare(
+            "weight_min_kg",
+            measurement(ours.and_then(|p| p.weight_min_kg)),
+            measurement(theirs.weight_min_kg),
+        );
+        compare(
+            "weight_max_kg",
+            measurement(ours.and_then(|p| p.weight_max_kg)),
+            measurement(theirs.weight_max_kg),
+        );
+        compare(
+            "hair_color",
+            ours.and_then(|p| p.hair_color.clone()),
+            theirs.hair_color,
+        );
+        compare(
+            "eye_color",
+            ours.and_then(|p| p.eye_color.clone()),
+            theirs.eye_color,
+        );
+        compare(
+            "last_known_city",
+            ours.and_then(|p| p.last_known_city.clone()),
+            theirs.last_known_city,
+        );
+        compare(
+            "last_known_state",
+            ours.and_then(|p| p.last_known_state.clone()),
+            theirs.last_known_state,
+        );
+
+        discrepancies
+    }
+}
+
+fn lookup_name(lookup: Option<&Lookup>) -> Option<String> {
+    non_empty(lookup.map(|l| l.name.as_str()))
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+fn measurement(value: Option<f32>) -> Option<String> {
+    value.map(|v| format!("{:.0}", v))
+}
+
+// NamUs records each eye separately
+fn eye_color(description: &PhysicalDescription) -> Option<String> {
+    let left = lookup_name(description.left_eye_color.as_ref());
+    let right = lookup_name(description.right_eye_color.as_ref());
+    match (left, right) {
+        (Some(left), Some(right)) if left != right => {
+            Some(format!("{} (left), {} (right)", left, right))
+        }
+        (left, right) => left.or(right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::profiles::Profile;
+    use chrono::Utc;
+    use sqlx::types::Json;
+
+    const FIXTURE: &[u8] = include_bytes!("../../fixtures/namus/mp99999.json");
+
+    fn fixture() -> NamusCase {
+        parse(FIXTURE).unwrap()
+    }
+
+    // an investigation that matches the fixture exactly, as if it had just been imported
+    fn imported(case: &NamusCase) -> Investigation {
+        let details = case.to_investigation_details().unwrap();
+        let profile = details.profile.unwrap();
+        let id = Uuid::nil();
+        Investigation {
+            id,
+            created: Utc::now(),
+            creator: id,
+            internal_id: None,
+            first_name: details.first_name,
+            middle_name: details.middle_name,
+            last_name: details.last_name,
+            date_of_birth: details.date_of_birth,
+            namus_id: details.namus_id,
+            missing_since: details.missing_since,
+            synopsis: details.synopsis,
+            merged_into: None,
+            status: "open".to_string(),
+            status_changed: Utc::now(),
+            closure_outcome: None,
+            closure_notes: None,
+            questions: None,
+            aliases: None,
+            profile: Some(Profile {
+                investigation: id,
+                version: 1,
+                updated: Utc::now(),
+                updated_by: id,
+                sex: profile.sex,
+                race: profile.race,
+                height_min_cm: profile.height_min_cm,
+                height_max_cm: profile.height_max_cm,
+                weight_min_kg: profile.weight_min_kg,
+                weight_max_kg: profile.weight_max_kg,
+                hair_color: profile.hair_color,
+                eye_color: profile.eye_color,
+                distinguishing_marks: Json(profile.distinguishing_marks),
+                clothing: profile.clothing,
+                vehicles: Json(profile.vehicles),
+                medical_conditions: profile.medical_conditions,
+                identifiers: Json(profile.identifiers),
+                last_known_address: profile.last_known_address,
+                last_known_city: profile.last_known_city,
+                last_known_county: profile.last_known_county,
+                last_known_state: profile.last_known_state,
+                last_known_country: profile.last_known_country,
+                last_known_latitude: profile.last_known_latitude,
+                last_known_longitude: profile.last_known_longitude,
+            }),
+        }
+    }
+
+    fn fields(discrepancies: &[Discrepancy]) -> Vec<&str> {
+        discrepancies.iter().map(|d| d.field.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_export() {
+        let case = fixture();
+        assert_eq!(case.id, 99999);
+        assert_eq!(case.namus_id(), "mp99999");
+        assert_eq!(case.subject_identification.first_name, "Janet");
+        assert_eq!(
+            case.sighting.date,
+            NaiveDate::from_ymd_opt(2021, 10, 2).unwrap()
+        );
+        assert_eq!(case.physical_feature_descriptions.len(), 2);
+        assert_eq!(case.vehicles.len(), 1);
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert!(parse(b"{\"id\": 1}").is_err());
+        assert!(parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn maps_investigation_details() {
+        let details = fixture().to_investigation_details().unwrap();
+        assert!(details.is_valid());
+        assert_eq!(details.first_name, "Janet");
+        assert_eq!(details.middle_name.as_deref(), Some("Marie"));
+        assert_eq!(details.last_name, "Example");
+        assert_eq!(
+            details.date_of_birth,
+            NaiveDate::from_ymd_opt(1994, 3, 17).unwrap()
+        );
+        assert_eq!(details.namus_id.as_deref(), Some("mp99999"));
+        assert!(details.synopsis.starts_with("Janet was last seen"));
+
+        let nicknames = details
+            .aliases
+            .iter()
+            .map(|a| (a.kind.as_str(), a.first_name.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nicknames,
+            vec![("nickname", Some("Jan")), ("nickname", Some("JJ"))]
+        );
+
+        let profile = details.profile.unwrap();
+        assert_eq!(profile.sex.as_deref(), Some("female"));
+        assert_eq!(profile.race.as_deref(), Some("White / Caucasian"));
+        assert_eq!(measurement(profile.height_min_cm).as_deref(), Some("163"));
+        assert_eq!(measurement(profile.height_max_cm).as_deref(), Some("168"));
+        assert_eq!(measurement(profile.weight_min_kg).as_deref(), Some("54"));
+        assert_eq!(measurement(profile.weight_max_kg).as_deref(), Some("59"));
+        assert_eq!(profile.hair_color.as_deref(), Some("Brown"));
+        // both eyes are the same colour, so it's only given once
+        assert_eq!(profile.eye_color.as_deref(), Some("Green"));
+        assert_eq!(
+            profile
+                .distinguishing_marks
+                .iter()
+                .map(|m| m.kind.as_str())
+                .collect::<Vec<_>>(),
+            vec!["tattoo", "scar"]
+        );
+        assert_eq!(
+            profile.clothing.as_deref(),
+            Some("Jacket: Dark green rain jacket\nShoes: White sneakers")
+        );
+        assert_eq!(profile.vehicles[0].make.as_deref(), Some("Honda"));
+        assert_eq!(
+            profile.vehicles[0].license_plate.as_deref(),
+            Some("ABC1234")
+        );
+        assert_eq!(profile.identifiers[0].kind, "ncic");
+        assert_eq!(profile.identifiers[0].value, "M000000000");
+        assert_eq!(profile.last_known_city.as_deref(), Some("Example Falls"));
+        assert_eq!(profile.last_known_state.as_deref(), Some("Oregon"));
+        assert_eq!(profile.last_known_country.as_deref(), Some("United States"));
+        assert_eq!(profile.last_known_latitude, Some(44.0521));
+    }
+
+    #[test]
+    fn requires_date_of_birth() {
+        let mut export: serde_json::Value = serde_json::from_slice(FIXTURE).unwrap();
+        export["subjectIdentification"]
+            .as_object_mut()
+            .unwrap()
+            .remove("dateOfBirth");
+        let case = parse(&serde_json::to_vec(&export).unwrap()).unwrap();
+        assert!(case.to_investigation_details().is_err());
+    }
+
+    #[test]
+    fn no_discrepancies_right_after_import() {
+        let case = fixture();
+        assert!(case.discrepancies(&imported(&case)).is_empty());
+    }
+
+    #[test]
+    fn finds_discrepancies() {
+        let case = fixture();
+        let mut investigation = imported(&case);
+        // case and surrounding whitespace aren't worth flagging
+        investigation.first_name = " janet ".to_string();
+        investigation.last_name = "Sample".to_string();
+        let profile = investigation.profile.as_mut().unwrap();
+        profile.hair_color = Some("Black".to_string());
+        // less than a whole unit off once converted
+        profile.height_min_cm = Some(162.9);
+        profile.last_known_city = None;
+
+        let discrepancies = case.discrepancies(&investigation);
+        assert_eq!(
+            fields(&discrepancies),
+            vec!["last_name", "hair_color", "last_known_city"]
+        );
+        assert_eq!(discrepancies[0].ours.as_deref(), Some("Sample"));
+        assert_eq!(discrepancies[0].namus.as_deref(), Some("Example"));
+        assert_eq!(discrepancies[2].ours, None);
+    }
+
+    #[test]
+    fn compares_profile_fields_against_a_missing_profile() {
+        let case = fixture();
+        let mut investigation = imported(&case);
+        investigation.profile = None;
+        assert_eq!(
+            fields(&case.discrepancies(&investigation)),
+            vec![
+                "sex",
+                "race",
+                "height_min_cm",
+                "height_max_cm",
+                "weight_min_kg",
+                "weight_max_kg",
+                "hair_color",
+                "eye_color",
+                "last_known_city",
+                "last_known_state"
+            ]
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use anyhow::Result;
+use axum::extract::State;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::*;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::comments::{self, Comment};
+use crate::core::helpers;
+use crate::core::investigations::{ActionItem, Question};
+use crate::core::users::User;
+use crate::AppState;
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy)]
+pub enum NotificationKind {
+    ActionItemAssigned,
+    QuestionStatusChanged,
+    FollowedCaseComment,
+    OverdueDigest,
+    Mentioned,
+}
+
+// users without a row in `notification_preferences` receive everything
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct NotificationPreferences {
+    pub user: Uuid,
+    pub action_item_assigned: bool,
+    pub question_status_changed: bool,
+    pub followed_case_comment: bool,
+    pub overdue_digest: bool,
+    pub mentioned: bool,
+}
+
+impl NotificationPreferences {
+    fn defaults(user: Uuid) -> NotificationPreferences {
+        NotificationPreferences {
+            user,
+            action_item_assigned: true,
+            question_status_changed: true,
+            followed_case_comment: true,
+            overdue_digest: true,
+            mentioned: true,
+        }
+    }
+
+    pub async fn get(State(state): State<AppState>, user: Uuid) -> Result<NotificationPreferences> {
+        let prefs = sqlx::query_as!(
+            NotificationPreferences,
+            r#"SELECT * FROM notification_preferences WHERE "user" = $1"#,
+            user
+        )
+        .fetch_optional(&state.db)
+        .await?;
+        Ok(prefs.unwrap_or(NotificationPreferences::defaults(user)))
+    }
+
+    pub async fn save(&self, State(state): State<AppState>) -> Result<NotificationPreferences> {
+        let prefs = sqlx::query_as!(
+            NotificationPreferences,
+            r#"INSERT INTO notification_preferences
+                ("user", action_item_assigned, question_status_changed, followed_case_comment, overdue_digest, mentioned)
+            VALUES
+                ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT ("user") DO UPDATE SET
+                action_item_assigned = EXCLUDED.action_item_assigned,
+                question_status_changed = EXCLUDED.question_status_changed,
+                followed_case_comment = EXCLUDED.followed_case_comment,
+                overdue_digest = EXCLUDED.overdue_digest,
+                mentioned = EXCLUDED.mentioned
+            RETURNING *"#,
+            self.user,
+            self.action_item_assigned,
+            self.question_status_changed,
+            self.followed_case_comment,
+            self.overdue_digest,
+            self.mentioned,
+        )
+        .fetch_one(&state.db)
+        .await?;
+        Ok(prefs)
+    }
+
+    pub fn allows(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::ActionItemAssigned => self.action_item_assigned,
+            NotificationKind::QuestionStatusChanged => self.question_status_changed,
+            NotificationKind::FollowedCaseComment => self.followed_case_comment,
+            NotificationKind::OverdueDigest => self.overdue_digest,
+            NotificationKind::Mentioned => self.mentioned,
+        }
+    }
+}
+
+pub async fn follow(State(state): State<AppState>, investigation: Uuid, user: Uuid) -> Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO investigation_followers (investigation, "user", created) VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING"#,
+        investigation,
+        user,
+        Utc::now(),
+    )
+    .execute(&state.db)
+    .await?;
+    Ok(())
+}
+
+pub async fn unfollow(
+    State(state): State<AppState>,
+    investigation: Uuid,
+    user: Uuid,
+) -> Result<()> {
+    sqlx::query!(
+        r#"DELETE FROM investigation_followers WHERE investigation = $1 AND "user" = $2"#,
+        investigation,
+        user,
+    )
+    .execute(&state.db)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_followers(
+    State(state): State<AppState>,
+    investigation: Uuid,
+) -> Result<Vec<Uuid>> {
+    let followers = sqlx::query!(
+        r#"SELECT "user" FROM investigation_followers WHERE investigation = $1"#,
+        investigation
+    )
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .map(|row| row.user)
+    .collect();
+    Ok(followers)
+}
+
+pub async fn action_item_assigned(State(state): State<AppState>, action_item: &ActionItem) {
+    let Some(assignee) = action_item.assignee else {
+        return;
+    };
+
+    let res = async {
+        let question = sqlx::query!(
+            "SELECT investigation FROM questions WHERE id = $1",
+            action_item.question
+        )
+        .fetch_one(&state.db)
+        .await?;
+        let url = helpers::ui_url(&format!("/investigations/{}", question.investigation))?;
+
+        send(
+            State(state.clone()),
+            assignee,
+            NotificationKind::ActionItemAssigned,
+            "templates/email/action_item_assigned.hbs",
+            "You have been assigned an action item",
+            HashMap::from([
+                ("summary", action_item.summary.as_str()),
+                ("actionItemUrl", url.as_str()),
+            ]),
+        )
+        .await
+    }
+    .await;
+
+    if let Err(e) = res {
+        warn!(
+            "Unable to send assignment notification for {}: {}",
+            action_item.id, e
+        );
+    }
+}
+
+pub async fn question_status_changed(
+    State(state): State<AppState>,
+    actor: &User,
+    question: &Question,
+    previous_status: &str,
+) {
+    let res = async {
+        let url = helpers::ui_url(&format!("/investigations/{}", question.investigation))?;
+
+        // the question's creator is always interested, along with anyone following the case
+        let mut recipients = HashSet::from([question.creator]);
+        recipients.extend(get_followers(State(state.clone()), question.investigation).await?);
+        recipients.remove(&actor.id);
+
+        for recipient in recipients {
+            send(
+                State(state.clone()),
+                recipient,
+                NotificationKind::QuestionStatusChanged,
+                "templates/email/question_status_changed.hbs",
+                "A question's status has changed",
+                HashMap::from([
+                    ("summary", question.summary.as_str()),
+                    ("previousStatus", previous_status),
+                    ("status", question.status.as_str()),
+                    ("investigationUrl", url.as_str()),
+                ]),
+            )
+            .await?;
+        }
+        anyhow::Ok(())
+    }
+    .await;
+
+    if let Err(e) = res {
+        warn!(
+            "Unable to send status notification for {}: {}",
+            question.id, e
+        );
+    }
+}
+
+pub async fn comment_created(State(state): State<AppState>, author: &User, comment: &Comment) {
+    let mentions = comments::parse_mentions(&comment.body);
+    let mentioned_ids = mentioned(State(state.clone()), author, comment, &mentions).await;
+
+    let res = async {
+        let url = helpers::ui_url(&format!("/investigations/{}", comment.investigation))?;
+
+        // anyone who was mentioned has already been told about this comment
+        let mut recipients = get_followers(State(state.clone()), comment.investigation)
+            .await?
+            .into_iter()
+            .collect::<HashSet<Uuid>>();
+        recipients.remove(&author.id);
+        for id in mentioned_ids {
+            recipients.remove(&id);
+        }
+
+        let author_name = author.display_name().unwrap_or(&author.email);
+        for recipient in recipients {
+            send(
+                State(state.clone()),
+                recipient,
+                NotificationKind::FollowedCaseComment,
+                "templates/email/followed_case_comment.hbs",
+                "New comment on a case you follow",
+                HashMap::from([
+                    ("author", author_name),
+                    ("body", comment.body.as_str()),
+                    ("investigationUrl", url.as_str()),
+                ]),
+            )
+            .await?;
+        }
+        anyhow::Ok(())
+    }
+    .await;
+
+    if let Err(e) = res {
+        warn!(
+            "Unable to send comment notifications for {}: {}",
+            comment.id, e
+        );
+    }
+}
+
+// returns the ids of the users that were mentioned, whether or not they were actually emailed
+pub async fn mentioned(
+    State(state): State<AppState>,
+    author: &User,
+    comment: &Comment,
+    emails: &[String],
+) -> Vec<Uuid> {
+    let mut mentioned_ids = vec![];
+
+    let url = match helpers::ui_url(&format!("/investigations/{}", comment.investigation)) {
+        Ok(url) => url,
+        Err(e) => {
+            warn!(
+                "Unable to send mention notifications for {}: {}",
+                comment.id, e
+            );
+            return mentioned_ids;
+        }
+    };
+    let author_name = author.display_name().unwrap_or(&author.email);
+
+    for email in emails {
+        // mentions of unknown addresses are just left as text
+        let Ok(user) = User::get_by_email(State(state.clone()), email).await else {
+            continue;
+        };
+        mentioned_ids.push(user.id);
+        if user.id == author.id {
+            continue;
+        }
+
+        let res = send(
+            State(state.clone()),
+            user.id,
+            NotificationKind::Mentioned,
+            "templates/email/mentioned.hbs",
+            "You were mentioned in a comment",
+            HashMap::from([
+                ("author", author_name),
+                ("body", comment.body.as_str()),
+                ("investigationUrl", url.as_str()),
+            ]),
+        )
+        .await;
+        if let Err(e) = res {
+            warn!("Unable to send mention notification to {}: {}", user.id, e);
+        }
+    }
+    mentioned_ids
+}
+
+// does nothing if another instance has already sent today's digest
+pub async fn send_overdue_digests(State(state): State<AppState>) -> Result<()> {
+    let now = Utc::now();
+    let claimed = sqlx::query!(
+        "INSERT INTO overdue_digest_runs (day, started) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        now.date_naive(),
+        now
+    )
+    .execute(&state.db)
+    .await?
+    .rows_affected()
+        > 0;
+    if !claimed {
+        return Ok(());
+    }
+
+    let mut by_assignee: HashMap<Uuid, Vec<ActionItem>> = HashMap::new();
+    for action_item in ActionItem::get_overdue(State(state.clone())).await? {
+        if let Some(assignee) = action_item.assignee {
+            by_assignee.entry(assignee).or_default().push(action_item);
+        }
+    }
+
+    let url = helpers::ui_url("/")?;
+    for (assignee, action_items) in by_assignee {
+        let items = action_items
+            .iter()
+            .map(|a| {
+                let due_date = a.due_date.map(|d| d.to_string()).unwrap_or_default();
+                format!("- [{}] {} (due {})", a.priority, a.summary, due_date)
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let res = send(
+            State(state.clone()),
+            assignee,
+            NotificationKind::OverdueDigest,
+            "templates/email/overdue_digest.hbs",
+            "You have overdue action items",
+            HashMap::from([("items", items.as_str()), ("url", url.as_str())]),
+        )
+        .await;
+        if let Err(e) = res {
+            warn!("Unable to send overdue digest to {}: {}", assignee, e);
+        }
+    }
+    Ok(())
+}
+
+// runs forever, sending the digest once a day at `OVERDUE_DIGEST_HOUR` (UTC)
+pub async fn run_overdue_digest(state: AppState) {
+    loop {
+        tokio::time::sleep(until_next_digest()).await;
+        if let Err(e) = send_overdue_digests(State(state.clone())).await {
+            warn!("Unable to send overdue digests: {}", e);
+        }
+    }
+}
+
+fn until_next_digest() -> std::time::Duration {
+    // TODO: load settings like this into AppState
+    let hour = std::env::var("OVERDUE_DIGEST_HOUR")
+        .ok()
+        .and_then(|h| h.parse::<u32>().ok())
+        .unwrap_or(12)
+        .min(23);
+    let now = Utc::now();
+    let mut next = now
+        .date_naive()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    if next <= now {
+        next += Duration::days(1);
+    }
+    (next - now).to_std().unwrap_or_default()
+}
+
+// notifications are best-effort, so callers should log failures rather than failing the change
+// that triggered them
+async fn send(
+    State(state): State<AppState>,
+    recipient: Uuid,
+    kind: NotificationKind,
+    template_file: &str,
+    subject: &str,
+    values: HashMap<&str, &str>,
+) -> Result<()> {
+    if !helpers::smtp_enabled() {
+        return Ok(());
+    }
+
+    let prefs = NotificationPreferences::get(State(state.clone()), recipient).await?;
+    if !prefs.allows(kind) {
+        return Ok(());
+    }
+
+    let user = User::get_by_id(State(state), &recipient.to_string()).await?;
+    if !user.is_active() || user.is_service_account() {
+        return Ok(());
+    }
+
+    helpers::send_email(&user.email, template_file, subject, values).await?;
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, PgConnection};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{log, users::User};
+use crate::AppState;
+
+pub const SEXES: [&str; 4] = ["female", "male", "other", "unknown"];
+
+pub const MARK_KINDS: [&str; 6] = [
+    "scar",
+    "tattoo",
+    "birthmark",
+    "piercing",
+    "amputation",
+    "other",
+];
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct DistinguishingMark {
+    pub kind: String,
+    // where on the body, e.g. "left forearm"
+    pub location: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Vehicle {
+    pub year: Option<i32>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub color: Option<String>,
+    pub license_plate: Option<String>,
+    pub license_state: Option<String>,
+    pub notes: Option<String>,
+}
+
+// any other number the person can be identified by, e.g. an NCIC number or driver's license
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Identifier {
+    pub kind: String,
+    pub value: String,
+}
+
+// heights and weights are ranges since witnesses rarely agree on exact numbers; use the same value
+// for both ends when it's known
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Profile {
+    pub investigation: Uuid,
+    // starts at 1 and goes up by one with every change
+    pub version: i32,
+    pub updated: DateTime<Utc>,
+    pub updated_by: Uuid,
+    pub sex: Option<String>,
+    pub race: Option<String>,
+    pub height_min_cm: Option<f32>,
+    pub height_max_cm: Option<f32>,
+    pub weight_min_kg: Option<f32>,
+    pub weight_max_kg: Option<f32>,
+    pub hair_color: Option<String>,
+    pub eye_color: Option<String>,
+    #[ts(as = "Vec<DistinguishingMark>")]
+    #[schema(value_type = Vec<DistinguishingMark>)]
+    pub distinguishing_marks: Json<Vec<DistinguishingMark>>,
+    // what they were last seen wearing
+    pub clothing: Option<String>,
+    #[ts(as = "Vec<Vehicle>")]
+    #[schema(value_type = Vec<Vehicle>)]
+    pub vehicles: Json<Vec<Vehicle>>,
+    pub medical_conditions: Option<String>,
+    #[ts(as = "Vec<Identifier>")]
+    #[schema(value_type = Vec<Identifier>)]
+    pub identifiers: Json<Vec<Identifier>>,
+    pub last_known_address: Option<String>,
+    pub last_known_city: Option<String>,
+    pub last_known_county: Option<String>,
+    pub last_known_state: Option<String>,
+    pub last_known_country: Option<String>,
+    pub last_known_latitude: Option<f64>,
+    pub last_known_longitude: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ProfileDetails {
+    pub sex: Option<String>,
+    pub race: Option<String>,
+    pub height_min_cm: Option<f32>,
+    pub height_max_cm: Option<f32>,
+    pub weight_min_kg: Option<f32>,
+    pub weight_max_kg: Option<f32>,
+    pub hair_color: Option<String>,
+    pub eye_color: Option<String>,
+    #[serde(default)]
+    pub distinguishing_marks: Vec<DistinguishingMark>,
+    pub clothing: Option<String>,
+    #[serde(default)]
+    pub vehicles: Vec<Vehicle>,
+    pub medical_conditions: Option<String>,
+    #[serde(default)]
+    pub identifiers: Vec<Identifier>,
+    pub last_known_address: Option<String>,
+    pub last_known_city: Option<String>,
+    pub last_known_county: Option<String>,
+    pub last_known_state: Option<String>,
+    pub last_known_country: Option<String>,
+    pub last_known_latitude: Option<f64>,
+    pub last_known_longitude: Option<f64>,
+}
+
+// a previous version of a profile, as it was before being replaced
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ProfileRevision {
+    pub id: Uuid,
+    pub investigation: Uuid,
+    pub version: i32,
+    pub created: DateTime<Utc>,
+    #[ts(as = "Profile")]
+    #[schema(value_type = Profile)]
+    pub data: Json<Profile>,
+}
+
+impl ProfileDetails {
+    pub fn is_valid(&self) -> bool {
+        let valid_range = |min: Option<f32>, max: Option<f32>| match (min, max) {
+            (Some(min), Some(max)) => min > 0.0 && min <= max,
+            (Some(value), None) | (None, Some(value)) => value > 0.0,
+            (None, None) => true,
+        };
+        // coordinates only make sense as a pair
+        let valid_location = match (self.last_known_latitude, self.last_known_longitude) {
+            (Some(latitude), Some(longitude)) => {
+                (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        self.sex.as_deref().is_none_or(|sex| SEXES.contains(&sex))
+            && valid_range(self.height_min_cm, self.height_max_cm)
+            && valid_range(self.weight_min_kg, self.weight_max_kg)
+            && valid_location
+            && self
+                .distinguishing_marks
+                .iter()
+                .all(|m| MARK_KINDS.contains(&m.kind.as_str()))
+            && self
+                .identifiers
+                .iter()
+                .all(|i| !i.kind.trim().is_empty() && !i.value.trim().is_empty())
+    }
+}
+
+impl Profile {
+    // not every investigation has a profile yet
+    pub async fn get(
+        State(state): State<AppState>,
+        investigation: Uuid,
+    ) -> Result<Option<Profile>> {
+        // the JSON columns need their types spelled out, so every column is listed
+        sqlx::query_as!(
+            Profile,
+            r#"SELECT
+                investigation, version, updated, updated_by, sex, race,
+                height_min_cm, height_max_cm, weight_min_kg, weight_max_kg, hair_color, eye_color,
+                distinguishing_marks as "distinguishing_marks: Json<Vec<DistinguishingMark>>",
+                clothing,
+                vehicles as "vehicles: Json<Vec<Vehicle>>",
+                medical_conditions,
+                identifiers as "identifiers: Json<Vec<Identifier>>",
+                last_known_address, last_known_city, last_known_county, last_known_state,
+                last_known_country, last_known_latitude, last_known_longitude
+            FROM investigation_profiles WHERE investigation = $1"#,
+            investigation
+        )
+        .fetch_optional(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // creates the profile or replaces it with a new version, keeping the previous one as a revision
+    pub async fn save(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        investigation: Uuid,
+        details: ProfileDetails,
+    ) -> Result<Profile> {
+        let previous = Profile::get(State(state.clone()), investigation).await?;
+
+        let mut tx = state.db.begin().await?;
+        let profile =
+            Profile::write(&mut tx, user.id, investigation, previous.as_ref(), details).await?;
+        tx.commit().await?;
+
+        log::record(
+            State(state),
+            user.id,
+            investigation,
+            "investigation_profile",
+            previous.as_ref(),
+            Some(&profile),
+            match previous {
+                Some(_) => "Profile updated",
+                None => "Profile created",
+            },
+        )
+        .await?;
+
+        Ok(profile)
+    }
+
+    // saves a new version of the profile without recording it in the audit log, for callers that
+    // are already in a transaction
+    pub async fn write(
+        conn: &mut PgConnection,
+        updated_by: Uuid,
+        investigation: Uuid,
+        previous: Option<&Profile>,
+        details: ProfileDetails,
+    ) -> Result<Profile> {
+        if !details.is_valid() {
+            return Err(Error::msg("Invalid profile"));
+        }
+
+        if let Some(previous) = previous {
+            sqlx::query!(
+                "INSERT INTO investigation_profile_revisions (id, investigation, version, created, data) VALUES ($1, $2, $3, $4, $5)",
+                Uuid::new_v7(Timestamp::now(NoContext)),
+                investigation,
+                previous.version,
+                Utc::now(),
+                serde_json::to_value(previous)?,
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // the revision's unique version keeps two concurrent saves from both succeeding
+        sqlx::query_as!(
+            Profile,
+            r#"INSERT INTO investigation_profiles (
+                investigation, version, updated, updated_by, sex, race,
+                height_min_cm, height_max_cm, weight_min_kg, weight_max_kg, hair_color, eye_color,
+                distinguishing_marks, clothing, vehicles, medical_conditions, identifiers,
+                last_known_address, last_known_city, last_known_county, last_known_state,
+                last_known_country, last_known_latitude, last_known_longitude
+            ) VALUES (
+                $1, 1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                $19, $20, $21, $22, $23
+            )
+            ON CONFLICT (investigation) DO UPDATE SET
+                version = investigation_profiles.version + 1,
+                updated = excluded.updated,
+                updated_by = excluded.updated_by,
+                sex = excluded.sex,
+                race = excluded.race,
+                height_min_cm = excluded.height_min_cm,
+                height_max_cm = excluded.height_max_cm,
+                weight_min_kg = excluded.weight_min_kg,
+                weight_max_kg = excluded.weight_max_kg,
+                hair_color = excluded.hair_color,
+                eye_color = excluded.eye_color,
+                distinguishing_marks = excluded.distinguishing_marks,
+                clothing = excluded.clothing,
+                vehicles = excluded.vehicles,
+                medical_conditions = excluded.medical_conditions,
+                identifiers = excluded.identifiers,
+                last_known_address = excluded.last_known_address,
+                last_known_city = excluded.last_known_city,
+                last_known_county = excluded.last_known_county,
+                last_known_state = excluded.last_known_state,
+                last_known_country = excluded.last_known_country,
+                last_known_latitude = excluded.last_known_latitude,
+                last_known_longitude = excluded.last_known_longitude
+            RETURNING
+                investigation, version, updated, updated_by, sex, race,
+                height_min_cm, height_max_cm, weight_min_kg, weight_max_kg, hair_color, eye_color,
+                distinguishing_marks as "distinguishing_marks: Json<Vec<DistinguishingMark>>",
+                clothing,
+                vehicles as "vehicles: Json<Vec<Vehicle>>",
+                medical_conditions,
+                identifiers as "identifiers: Json<Vec<Identifier>>",
+                last_known_address, last_known_city, last_known_county, last_known_state,
+                last_known_country, last_known_latitude, last_known_longitude"#,
+            investigation,
+            Utc::now(),
+            updated_by,
+            details.sex,
+            details.race,
+            details.height_min_cm,
+            details.height_max_cm,
+            details.weight_min_kg,
+            details.weight_max_kg,
+            details.hair_color,
+            details.eye_color,
+            serde_json::to_value(&details.distinguishing_marks)?,
+            details.clothing,
+            serde_json::to_value(&details.vehicles)?,
+            details.medical_conditions,
+            serde_json::to_value(&details.identifiers)?,
+            details.last_known_address,
+            details.last_known_city,
+            details.last_known_county,
+            details.last_known_state,
+            details.last_known_country,
+            details.last_known_latitude,
+            details.last_known_longitude,
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn get_revisions(
+        State(state): State<AppState>,
+        investigation: Uuid,
+    ) -> Result<Vec<ProfileRevision>> {
+        sqlx::query_as!(
+            ProfileRevision,
+            r#"SELECT id, investigation, version, created, data as "data: Json<Profile>"
+            FROM investigation_profile_revisions WHERE investigation = $1 ORDER BY version ASC"#,
+            investigation
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+}
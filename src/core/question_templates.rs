@@ -0,0 +1,327 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, PgConnection};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::api::admin::investigations::{CreateActionItemDetails, CreateQuestionDetails};
+use crate::core::investigations::{ActionItem, Investigation, Question, PRIORITIES};
+use crate::core::{log, users::User};
+use crate::AppState;
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TemplateActionItem {
+    pub summary: String,
+    pub details: Option<String>,
+    // defaults to "normal" when not provided
+    pub priority: Option<String>,
+    pub estimated_hours: Option<f32>,
+    // the due date is set this many days after the template is applied
+    pub due_in_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TemplateQuestion {
+    pub summary: String,
+    pub details: Option<String>,
+    #[serde(default)]
+    pub action_items: Vec<TemplateActionItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct QuestionTemplate {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub updated: DateTime<Utc>,
+    pub name: String,
+    pub description: Option<String>,
+    #[ts(as = "Vec<TemplateQuestion>")]
+    #[schema(value_type = Vec<TemplateQuestion>)]
+    pub questions: Json<Vec<TemplateQuestion>>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct QuestionTemplateDetails {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub questions: Vec<TemplateQuestion>,
+}
+
+impl QuestionTemplateDetails {
+    pub fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty()
+            && !self.questions.is_empty()
+            && self.questions.iter().all(|q| {
+                !q.summary.trim().is_empty()
+                    && q.action_items.iter().all(|a| {
+                        !a.summary.trim().is_empty()
+                            && a.priority
+                                .as_deref()
+                                .is_none_or(|p| PRIORITIES.contains(&p))
+                            && a.estimated_hours.is_none_or(|h| h >= 0.0)
+                            && a.due_in_days.is_none_or(|d| d >= 0)
+                    })
+            })
+    }
+}
+
+// question pretty IDs are numbered from 1 and action items are numbered under their question, so
+// applied templates carry on from the highest number already in use
+async fn next_question_number(conn: &mut PgConnection, investigation: Uuid) -> Result<i64> {
+    let highest = sqlx::query_scalar!(
+        r#"SELECT coalesce(max(pretty_id::bigint), 0) as "highest!" FROM questions
+        WHERE investigation = $1 AND pretty_id ~ '^[0-9]{1,18}$'"#,
+        investigation
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(highest + 1)
+}
+
+impl QuestionTemplate {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<QuestionTemplate> {
+        sqlx::query_as!(
+            QuestionTemplate,
+            r#"SELECT id, created, creator, updated, name, description,
+                questions as "questions: Json<Vec<TemplateQuestion>>"
+            FROM question_templates WHERE id = $1"#,
+            id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn list(State(state): State<AppState>) -> Result<Vec<QuestionTemplate>> {
+        sqlx::query_as!(
+            QuestionTemplate,
+            r#"SELECT id, created, creator, updated, name, description,
+                questions as "questions: Json<Vec<TemplateQuestion>>"
+            FROM question_templates ORDER BY name"#
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // names are unique, so admins can tell templates apart when choosing one
+    pub async fn find_by_name(
+        State(state): State<AppState>,
+        name: &str,
+    ) -> Result<Option<QuestionTemplate>> {
+        sqlx::query_as!(
+            QuestionTemplate,
+            r#"SELECT id, created, creator, updated, name, description,
+                questions as "questions: Json<Vec<TemplateQuestion>>"
+            FROM question_templates WHERE name = $1"#,
+            name.trim()
+        )
+        .fetch_optional(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: QuestionTemplateDetails,
+    ) -> Result<QuestionTemplate> {
+        let now = Utc::now();
+        let template = sqlx::query_as!(
+            QuestionTemplate,
+            r#"INSERT INTO question_templates (id, created, creator, updated, name, description, questions)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, created, creator, updated, name, description,
+                questions as "questions: Json<Vec<TemplateQuestion>>""#,
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            now,
+            user.id,
+            now,
+            details.name.trim(),
+            details.description,
+            Json(details.questions) as _,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            template.id,
+            "question_template",
+            None,
+            Some(&template),
+            "Question template created",
+        )
+        .await?;
+
+        Ok(template)
+    }
+
+    pub async fn update(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: QuestionTemplateDetails,
+    ) -> Result<QuestionTemplate> {
+        let template = sqlx::query_as!(
+            QuestionTemplate,
+            r#"UPDATE question_templates SET updated = $1, name = $2, description = $3, questions = $4
+            WHERE id = $5
+            RETURNING id, created, creator, updated, name, description,
+                questions as "questions: Json<Vec<TemplateQuestion>>""#,
+            Utc::now(),
+            details.name.trim(),
+            details.description,
+            Json(details.questions) as _,
+            self.id,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            template.id,
+            "question_template",
+            Some(self),
+            Some(&template),
+            "Question template updated",
+        )
+        .await?;
+
+        Ok(template)
+    }
+
+    // investigations the template was applied to keep their copies
+    pub async fn delete(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<()> {
+        sqlx::query!("DELETE FROM question_templates WHERE id = $1", self.id)
+            .execute(&state.db)
+            .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "question_template",
+            Some(self),
+            None,
+            "Question template deleted",
+        )
+        .await
+    }
+
+    // turns the template into the same details a lead would have typed in, numbering the
+    // questions from `first_number`
+    pub fn expand(&self, investigation: Uuid, first_number: i64) -> Vec<CreateQuestionDetails> {
+        let today = Utc::now().date_naive();
+        self.questions
+            .iter()
+            .zip(first_number..)
+            .map(|(question, number)| CreateQuestionDetails {
+                pretty_id: number.to_string(),
+                summary: question.summary.clone(),
+                details: question.details.clone(),
+                investigation,
+                outcome: None,
+                status: "not_started".to_string(),
+                action_items: question
+                    .action_items
+                    .iter()
+                    .map(|action_item| CreateActionItemDetails {
+                        pretty_id: None,
+                        summary: action_item.summary.clone(),
+                        details: action_item.details.clone(),
+                        status: None,
+                        outcome: None,
+                        assignee: None,
+                        resolved: None,
+                        due_date: action_item
+                            .due_in_days
+                            .map(|days| today + Duration::days(days.into())),
+                        priority: action_item.priority.clone(),
+                        estimated_hours: action_item.estimated_hours,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    // copies the template's questions and action items into the investigation
+    pub async fn apply(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        investigation: Uuid,
+    ) -> Result<Vec<Question>> {
+        let mut tx = state.db.begin().await?;
+        let questions = self
+            .insert_questions(&mut tx, user.id, investigation)
+            .await?;
+        tx.commit().await?;
+        self.record_applied(State(state), user.id, investigation)
+            .await?;
+        Ok(questions)
+    }
+
+    // the database half of `apply`, for callers that are already in a transaction
+    pub async fn insert_questions(
+        &self,
+        conn: &mut PgConnection,
+        creator: Uuid,
+        investigation: Uuid,
+    ) -> Result<Vec<Question>> {
+        // the investigation stays locked until the transaction ends, so two templates applied at
+        // once can't number their questions the same
+        sqlx::query!(
+            "SELECT id FROM investigations WHERE id = $1 FOR UPDATE",
+            investigation
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let first_number = next_question_number(conn, investigation).await?;
+
+        let mut questions = vec![];
+        for mut details in self.expand(investigation, first_number) {
+            let action_items = std::mem::take(&mut details.action_items);
+            let mut question = Question::insert(conn, creator, details).await?;
+            for details in action_items {
+                let action_item = ActionItem::create(conn, creator, question.id, details).await?;
+                question.action_items.insert(action_item.id, action_item);
+            }
+            questions.push(question);
+        }
+        Ok(questions)
+    }
+
+    pub async fn record_applied(
+        &self,
+        State(state): State<AppState>,
+        actor: Uuid,
+        investigation: Uuid,
+    ) -> Result<()> {
+        log::record(
+            State(state),
+            actor,
+            investigation,
+            "investigation",
+            None::<&Investigation>,
+            None,
+            &format!("Question template \"{}\" applied", self.name),
+        )
+        .await
+    }
+}
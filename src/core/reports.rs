@@ -0,0 +1,688 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, Utc};
+use handlebars::Handlebars;
+use printpdf::{BuiltinFont, Line, Mm, PdfDocument, PdfLayerReference, Point};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::attachments::Attachment;
+use crate::core::investigations::{full_name, Investigation};
+use crate::core::timeline::{self, ChronologyEntry};
+use crate::core::{log, users::User};
+use crate::AppState;
+
+const TEMPLATE_FILE: &str = "templates/reports/case_report.hbs";
+
+// US letter, in millimetres
+const PAGE_WIDTH: f32 = 215.9;
+const PAGE_HEIGHT: f32 = 279.4;
+const MARGIN: f32 = 20.0;
+const BODY_TOP: f32 = PAGE_HEIGHT - 32.0;
+const BODY_BOTTOM: f32 = 24.0;
+const PT_TO_MM: f32 = 0.3528;
+
+// control characters the template can't contain, used to keep values from being read as markup.
+// `pdf_safe` drops both before anything is drawn
+const LITERAL: char = '\u{1}';
+const LINE_BREAK: char = '\u{2}';
+
+// who a report is being handed to decides what's left out of it
+#[derive(Debug, Clone, Copy, Default, Deserialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum RedactionProfile {
+    #[default]
+    LawEnforcement,
+    Family,
+}
+
+struct Redactions {
+    internal_ids: bool,
+    action_items: bool,
+    // question and action item details, which are working notes rather than findings
+    working_notes: bool,
+    assignees: bool,
+    unverified_timeline: bool,
+    timeline_sources: bool,
+    attachment_index: bool,
+}
+
+impl RedactionProfile {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RedactionProfile::LawEnforcement => "Law enforcement copy",
+            RedactionProfile::Family => "Family copy",
+        }
+    }
+
+    // what each profile is allowed to include
+    fn includes(&self) -> Redactions {
+        match self {
+            RedactionProfile::LawEnforcement => Redactions {
+                internal_ids: true,
+                action_items: true,
+                working_notes: true,
+                assignees: true,
+                unverified_timeline: true,
+                timeline_sources: true,
+                attachment_index: true,
+            },
+            RedactionProfile::Family => Redactions {
+                internal_ids: false,
+                action_items: false,
+                working_notes: false,
+                assignees: false,
+                unverified_timeline: false,
+                timeline_sources: false,
+                attachment_index: false,
+            },
+        }
+    }
+}
+
+// everything the template sees, already redacted and formatted, so nothing left out by the
+// profile can end up in the report through a template mistake
+#[derive(Debug, Serialize)]
+struct CaseReport {
+    generated: String,
+    profile_label: &'static str,
+    name: String,
+    aliases: Vec<String>,
+    date_of_birth: String,
+    missing_since: String,
+    internal_id: Option<String>,
+    namus_id: Option<String>,
+    synopsis: String,
+    questions: Vec<ReportQuestion>,
+    timeline: Vec<ReportTimelineEntry>,
+    attachments: Vec<ReportAttachment>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportQuestion {
+    pretty_id: String,
+    summary: String,
+    status: String,
+    details: Option<String>,
+    outcome: Option<String>,
+    action_items: Vec<ReportActionItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportActionItem {
+    pretty_id: String,
+    summary: String,
+    status: String,
+    assignee: Option<String>,
+    due_date: Option<String>,
+    details: Option<String>,
+    outcome: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportTimelineEntry {
+    when: String,
+    description: String,
+    location: Option<String>,
+    source: Option<String>,
+    confidence: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportAttachment {
+    filename: String,
+    mime_type: String,
+    size: String,
+    created: String,
+    sha256: String,
+}
+
+fn organization() -> String {
+    std::env::var("REPORT_ORGANIZATION").unwrap_or("IntriCase".to_string())
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn format_window(entry: &ChronologyEntry) -> String {
+    match entry.earliest == entry.latest {
+        true => format_time(entry.earliest),
+        false => format!(
+            "between {} and {}",
+            format_time(entry.earliest),
+            format_time(entry.latest)
+        ),
+    }
+}
+
+fn format_size(size: i64) -> String {
+    match size {
+        s if s >= 1024 * 1024 => format!("{:.1} MB", s as f64 / (1024.0 * 1024.0)),
+        s if s >= 1024 => format!("{:.1} KB", s as f64 / 1024.0),
+        s => format!("{} bytes", s),
+    }
+}
+
+// renders the investigation as a PDF, and records who exported it and for whom
+pub async fn case_report(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    investigation: Uuid,
+    profile: RedactionProfile,
+) -> Result<Vec<u8>> {
+    let report = gather(State(state.clone()), investigation, profile).await?;
+
+    let mut hb = Handlebars::new();
+    hb.register_escape_fn(escape);
+    hb.register_template_file("case_report", TEMPLATE_FILE)?;
+    let text = hb.render("case_report", &report)?;
+
+    let title = format!("Case report: {}", report.name);
+    let header = format!("{} - {}", organization(), profile.label());
+    let pdf = tokio::task::spawn_blocking(move || render_pdf(&title, &header, &text)).await??;
+
+    log::record(
+        State(state),
+        user.id,
+        investigation,
+        "investigation",
+        None::<&Investigation>,
+        None,
+        &format!("Case report exported ({})", profile.label().to_lowercase()),
+    )
+    .await?;
+
+    Ok(pdf)
+}
+
+async fn gather(
+    State(state): State<AppState>,
+    investigation: Uuid,
+    profile: RedactionProfile,
+) -> Result<CaseReport> {
+    let includes = profile.includes();
+    let investigation =
+        Investigation::get(State(state.clone()), &investigation.to_string(), true).await?;
+
+    let assignees: HashMap<Uuid, String> = match includes.assignees {
+        true => sqlx::query!(r#"SELECT id, coalesce(display_name, email) as "name!" FROM users"#)
+            .fetch_all(&state.db)
+            .await?
+            .into_iter()
+            .map(|u| (u.id, u.name))
+            .collect(),
+        false => HashMap::new(),
+    };
+
+    let mut questions = investigation
+        .questions
+        .as_ref()
+        .map(|q| q.values().collect::<Vec<_>>())
+        .unwrap_or_default();
+    questions.sort_by(|a, b| a.pretty_id.cmp(&b.pretty_id));
+    let questions = questions
+        .into_iter()
+        .map(|question| {
+            let mut action_items = match includes.action_items {
+                true => question.action_items.values().collect::<Vec<_>>(),
+                false => vec![],
+            };
+            action_items.sort_by(|a, b| a.pretty_id.cmp(&b.pretty_id));
+            ReportQuestion {
+                pretty_id: question.pretty_id.clone(),
+                summary: question.summary.clone(),
+                status: question.status.replace('_', " "),
+                details: question.details.clone().filter(|_| includes.working_notes),
+                outcome: question.outcome.clone(),
+                action_items: action_items
+                    .into_iter()
+                    .map(|item| ReportActionItem {
+                        pretty_id: item.pretty_id.clone(),
+                        summary: item.summary.clone(),
+                        status: item.status.replace('_', " "),
+                        assignee: item.assignee.and_then(|a| assignees.get(&a).cloned()),
+                        due_date: item.due_date.map(|d| d.to_string()),
+                        details: item.details.clone().filter(|_| includes.working_notes),
+                        outcome: item.outcome.clone(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let timeline = timeline::get_chronology(State(state.clone()), investigation.id)
+        .await?
+        .into_iter()
+        .filter(|entry| {
+            includes.unverified_timeline || entry.confidence.as_deref() != Some("unverified")
+        })
+        .map(|entry| ReportTimelineEntry {
+            when: format_window(&entry),
+            description: entry.description.clone(),
+            location: entry.location.clone(),
+            source: entry.source.clone().filter(|_| includes.timeline_sources),
+            confidence: entry.confidence.clone(),
+        })
+        .collect();
+
+    let attachments = match includes.attachment_index {
+        true => sqlx::query_as!(
+            Attachment,
+            "SELECT * FROM attachments WHERE investigation = $1 ORDER BY created ASC",
+            investigation.id
+        )
+        .fetch_all(&state.db)
+        .await?
+        .into_iter()
+        .map(|attachment| ReportAttachment {
+            filename: attachment.filename,
+            mime_type: attachment.mime_type,
+            size: format_size(attachment.size),
+            created: format_time(attachment.created),
+            sha256: attachment.sha256,
+        })
+        .collect(),
+        false => vec![],
+    };
+
+    Ok(CaseReport {
+        generated: format_time(Utc::now()),
+        profile_label: profile.label(),
+        name: full_name(
+            &investigation.first_name,
+            investigation.middle_name.as_deref(),
+            &investigation.last_name,
+        ),
+        aliases: investigation
+            .aliases
+            .iter()
+            .flatten()
+            .map(|alias| {
+                [&alias.first_name, &alias.middle_name, &alias.last_name]
+                    .into_iter()
+                    .flatten()
+                    .map(|n| n.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|name| !name.is_empty())
+            .collect(),
+        date_of_birth: investigation.date_of_birth.to_string(),
+        missing_since: investigation.missing_since.to_string(),
+        internal_id: investigation
+            .internal_id
+            .clone()
+            .filter(|_| includes.internal_ids),
+        namus_id: investigation.namus_id.clone(),
+        synopsis: investigation.synopsis.clone(),
+        questions,
+        timeline,
+        attachments,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Title,
+    Heading,
+    Subheading,
+    Body,
+    Note,
+}
+
+impl Style {
+    fn size(&self) -> f32 {
+        match self {
+            Style::Title => 18.0,
+            Style::Heading => 14.0,
+            Style::Subheading => 11.5,
+            Style::Body => 10.0,
+            Style::Note => 9.0,
+        }
+    }
+
+    // space left above the first line
+    fn space_before(&self) -> f32 {
+        match self {
+            Style::Title => 0.0,
+            Style::Heading => 5.0,
+            Style::Subheading => 3.0,
+            Style::Body | Style::Note => 0.0,
+        }
+    }
+}
+
+struct PlacedLine {
+    style: Style,
+    x: f32,
+    y: f32,
+    text: String,
+}
+
+// the built-in PDF fonts only cover Latin-1, so anything else is swapped for something close or
+// dropped rather than coming out as garbage
+fn pdf_safe(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\u{2018}' | '\u{2019}' => Some('\''),
+            '\u{201C}' | '\u{201D}' => Some('"'),
+            '\u{2013}' | '\u{2014}' => Some('-'),
+            '\t' => Some(' '),
+            c if c.is_control() => None,
+            c if (c as u32) < 256 => Some(c),
+            _ => Some('?'),
+        })
+        .collect()
+}
+
+// values are laid out as plain text wherever they land: a value at the start of a line can't be
+// taken for markup, and line breaks in it stay within the same paragraph or bullet point
+fn escape(value: &str) -> String {
+    let mut escaped = String::from(LITERAL);
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push(LINE_BREAK),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Helvetica averages about half an em per character, which is close enough for wrapping
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let max_chars = (width / (size * PT_TO_MM * 0.5)).floor().max(1.0) as usize;
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// turns the rendered template into lines placed on pages, following the markup described at the
+// top of the template
+fn layout(text: &str) -> Vec<Vec<PlacedLine>> {
+    let mut pages: Vec<Vec<PlacedLine>> = vec![vec![]];
+    let mut y = BODY_TOP;
+    let mut after_gap = true;
+
+    for raw in text.lines() {
+        let raw = raw.trim_end();
+        if raw.trim().is_empty() {
+            if !after_gap {
+                y -= 3.0;
+                after_gap = true;
+            }
+            continue;
+        }
+        if raw.trim() == "---" {
+            if !pages.last().is_some_and(|p| p.is_empty()) {
+                pages.push(vec![]);
+                y = BODY_TOP;
+            }
+            after_gap = true;
+            continue;
+        }
+        after_gap = false;
+
+        // escaped values start with `LITERAL`, so they never match any of these
+        let (style, indent, bullet, content) = if let Some(t) = raw.strip_prefix("# ") {
+            (Style::Title, 0.0, false, t)
+        } else if let Some(t) = raw.strip_prefix("## ") {
+            (Style::Heading, 0.0, false, t)
+        } else if let Some(t) = raw.strip_prefix("### ") {
+            (Style::Subheading, 0.0, false, t)
+        } else if let Some(t) = raw.strip_prefix("- ") {
+            (Style::Body, 5.0, true, t)
+        } else if let Some(t) = raw.strip_prefix("> ") {
+            (Style::Note, 10.0, false, t)
+        } else {
+            (Style::Body, 0.0, false, raw)
+        };
+
+        let line_height = style.size() * PT_TO_MM * 1.4;
+        let width = PAGE_WIDTH - MARGIN * 2.0 - indent;
+        y -= style.space_before();
+        let lines = content
+            .split(LINE_BREAK)
+            .flat_map(|part| wrap(&pdf_safe(part), style.size(), width));
+        for (i, line) in lines.enumerate() {
+            if y - line_height < BODY_BOTTOM {
+                pages.push(vec![]);
+                y = BODY_TOP;
+            }
+            y -= line_height;
+            let text = match (bullet, i) {
+                (true, 0) => format!("\u{b7} {}", line),
+                (true, _) => format!("  {}", line),
+                (false, _) => line,
+            };
+            pages.last_mut().unwrap().push(PlacedLine {
+                style,
+                x: MARGIN + indent,
+                y,
+                text,
+            });
+        }
+    }
+
+    pages
+}
+
+fn rule(layer: &PdfLayerReference, y: f32) {
+    layer.add_line(Line {
+        points: vec![
+            (Point::new(Mm(MARGIN), Mm(y)), false),
+            (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(y)), false),
+        ],
+        is_closed: false,
+    });
+}
+
+fn render_pdf(title: &str, header: &str, text: &str) -> Result<Vec<u8>> {
+    let pages = layout(text);
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(pdf_safe(title), Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+    let italic = doc.add_builtin_font(BuiltinFont::HelveticaOblique)?;
+
+    let total = pages.len();
+    for (i, lines) in pages.into_iter().enumerate() {
+        let layer = match i {
+            0 => doc.get_page(first_page).get_layer(first_layer),
+            _ => {
+                let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+                doc.get_page(page).get_layer(layer)
+            }
+        };
+
+        // the same header and footer on every page, so pages that get separated can be put back
+        layer.use_text(
+            pdf_safe(header),
+            9.0,
+            Mm(MARGIN),
+            Mm(PAGE_HEIGHT - 15.0),
+            &bold,
+        );
+        rule(&layer, PAGE_HEIGHT - 18.0);
+        rule(&layer, BODY_BOTTOM - 6.0);
+        layer.use_text(
+            format!("{} - page {} of {}", pdf_safe(title), i + 1, total),
+            8.0,
+            Mm(MARGIN),
+            Mm(BODY_BOTTOM - 11.0),
+            &regular,
+        );
+
+        for line in lines {
+            let font = match line.style {
+                Style::Title | Style::Heading | Style::Subheading => &bold,
+                Style::Body => &regular,
+                Style::Note => &italic,
+            };
+            layer.use_text(line.text, line.style.size(), Mm(line.x), Mm(line.y), font);
+        }
+    }
+
+    doc.save_to_bytes().map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every line on every page, with its style's font size
+    fn texts(pages: &[Vec<PlacedLine>]) -> Vec<Vec<(f32, &str)>> {
+        pages
+            .iter()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .map(|l| (l.style.size(), l.text.as_str()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    // the width that fits exactly `chars` characters of body text
+    fn width_for(chars: usize) -> f32 {
+        (chars as f32 + 0.5) * Style::Body.size() * PT_TO_MM * 0.5
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        assert_eq!(
+            wrap(
+                "the quick brown fox jumps",
+                Style::Body.size(),
+                width_for(10)
+            ),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        // an exact fit stays on one line
+        assert_eq!(
+            wrap("the quick", Style::Body.size(), width_for(9)),
+            vec!["the quick"]
+        );
+        assert_eq!(
+            wrap("  spaced \t  out  ", Style::Body.size(), width_for(20)),
+            vec!["spaced out"]
+        );
+    }
+
+    #[test]
+    fn wraps_long_words_onto_their_own_line() {
+        assert_eq!(
+            wrap(
+                "a sha256:0123456789abcdef b",
+                Style::Body.size(),
+                width_for(10)
+            ),
+            vec!["a", "sha256:0123456789abcdef", "b"]
+        );
+    }
+
+    #[test]
+    fn wraps_nothing_into_no_lines() {
+        assert!(wrap("", Style::Body.size(), width_for(10)).is_empty());
+        assert!(wrap("   ", Style::Body.size(), width_for(10)).is_empty());
+    }
+
+    #[test]
+    fn lays_out_markup() {
+        let pages =
+            layout("# Title\n## Heading\n### Subheading\n- bullet\n> note\nbody\n---\nnext");
+        assert_eq!(
+            texts(&pages),
+            vec![
+                vec![
+                    (Style::Title.size(), "Title"),
+                    (Style::Heading.size(), "Heading"),
+                    (Style::Subheading.size(), "Subheading"),
+                    (Style::Body.size(), "\u{b7} bullet"),
+                    (Style::Note.size(), "note"),
+                    (Style::Body.size(), "body"),
+                ],
+                vec![(Style::Body.size(), "next")],
+            ]
+        );
+        // bullets and notes are indented, and lines run down the page
+        let first = &pages[0];
+        assert_eq!(first[3].x, MARGIN + 5.0);
+        assert_eq!(first[4].x, MARGIN + 10.0);
+        assert!(first.windows(2).all(|w| w[1].y < w[0].y));
+    }
+
+    #[test]
+    fn starts_new_pages_when_full() {
+        let text = (0..200)
+            .map(|i| format!("line {}\n", i))
+            .collect::<String>();
+        let pages = layout(&text);
+        assert!(pages.len() > 1);
+        assert!(pages
+            .iter()
+            .flatten()
+            .all(|l| l.y >= BODY_BOTTOM && l.y <= BODY_TOP));
+        assert_eq!(pages.iter().map(|p| p.len()).sum::<usize>(), 200);
+    }
+
+    #[test]
+    fn ignores_empty_pages() {
+        assert_eq!(
+            texts(&layout("---\n---\nonly")),
+            vec![vec![(Style::Body.size(), "only")]]
+        );
+    }
+
+    #[test]
+    fn lays_out_values_as_plain_text() {
+        let synopsis = "# Not a title\n---\n- not a bullet\n> not a note";
+        let pages = layout(&format!("## Synopsis\n{}\n", escape(synopsis)));
+        assert_eq!(
+            texts(&pages),
+            vec![vec![
+                (Style::Heading.size(), "Synopsis"),
+                (Style::Body.size(), "# Not a title"),
+                (Style::Body.size(), "---"),
+                (Style::Body.size(), "- not a bullet"),
+                (Style::Body.size(), "> not a note"),
+            ]]
+        );
+    }
+
+    #[test]
+    fn keeps_line_breaks_in_values_within_their_bullet() {
+        let mut hb = Handlebars::new();
+        hb.register_escape_fn(escape);
+        let text = hb
+            .render_template(
+                "- {{pretty_id}}: {{summary}}\n",
+                &serde_json::json!({ "pretty_id": "1.1", "summary": "first\r\n## second" }),
+            )
+            .unwrap();
+        assert_eq!(
+            texts(&layout(&text)),
+            vec![vec![
+                (Style::Body.size(), "\u{b7} 1.1: first"),
+                (Style::Body.size(), "  ## second"),
+            ]]
+        );
+    }
+}
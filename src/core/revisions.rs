@@ -0,0 +1,217 @@
+use anyhow::{Error, Result};
+use axum::extract::State;
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::PgConnection;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum RevisionTarget {
+    Investigation,
+    Question,
+    ActionItem,
+}
+
+impl RevisionTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionTarget::Investigation => "investigation",
+            RevisionTarget::Question => "question",
+            RevisionTarget::ActionItem => "action_item",
+        }
+    }
+}
+
+// records whose edits are kept as numbered versions
+pub trait Versioned: Serialize {
+    const TARGET: RevisionTarget;
+    // serialized fields that aren't columns, like related records loaded alongside
+    const DERIVED: &'static [&'static str] = &[];
+
+    fn id(&self) -> Uuid;
+    fn created(&self) -> DateTime<Utc>;
+    fn creator(&self) -> Uuid;
+
+    fn snapshot(&self) -> Result<Map<String, Value>> {
+        let Value::Object(mut fields) = serde_json::to_value(self)? else {
+            return Err(Error::msg("Versioned records must serialize to an object"));
+        };
+        for field in Self::DERIVED {
+            fields.remove(*field);
+        }
+        Ok(fields)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct FieldChange {
+    pub field: String,
+    pub previous: Value,
+    pub current: Value,
+}
+
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Revision {
+    pub id: Uuid,
+    pub target: Uuid,
+    pub target_type: String,
+    pub version: i32,
+    // when this version was saved and who saved it
+    pub created: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub data: Value,
+    // not a database column, the fields that differ from the version before
+    pub changes: Vec<FieldChange>,
+}
+
+impl Revision {
+    // the editable fields of this version, for restoring it
+    pub fn details<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_value(self.data.clone()).map_err(Error::from)
+    }
+}
+
+// fields missing from one side are compared as null
+fn diff(previous: &Value, current: &Value) -> Vec<FieldChange> {
+    let empty = Map::new();
+    let previous = previous.as_object().unwrap_or(&empty);
+    let current = current.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = current.keys().chain(previous.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let before = previous.get(field).unwrap_or(&Value::Null);
+            let after = current.get(field).unwrap_or(&Value::Null);
+            (before != after).then(|| FieldChange {
+                field: field.clone(),
+                previous: before.clone(),
+                current: after.clone(),
+            })
+        })
+        .collect()
+}
+
+async fn insert(
+    conn: &mut PgConnection,
+    target_type: RevisionTarget,
+    target: Uuid,
+    version: i32,
+    (created, created_by): (DateTime<Utc>, Uuid),
+    data: Map<String, Value>,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO revisions (id, target, target_type, version, created, created_by, data) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        Uuid::new_v7(Timestamp::now(NoContext)),
+        target,
+        target_type.as_str(),
+        version,
+        created,
+        created_by,
+        Value::Object(data),
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+// saves `current` as the newest version of the record, given a snapshot of it from before the edit.
+// The first edit also saves that earlier snapshot as version 1, credited to whoever created the
+// record. Run it in the same transaction as the edit; the unique version keeps two concurrent
+// edits from both succeeding
+pub async fn record<T: Versioned>(
+    conn: &mut PgConnection,
+    user: Uuid,
+    previous: Map<String, Value>,
+    current: &T,
+) -> Result<()> {
+    let data = current.snapshot()?;
+    if data == previous {
+        return Ok(());
+    }
+
+    let latest = sqlx::query_scalar!(
+        "SELECT max(version) FROM revisions WHERE target_type = $1 AND target = $2",
+        T::TARGET.as_str(),
+        current.id()
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let version = match latest {
+        Some(version) => version,
+        None => {
+            let original = (current.created(), current.creator());
+            insert(conn, T::TARGET, current.id(), 1, original, previous).await?;
+            1
+        }
+    };
+    insert(
+        conn,
+        T::TARGET,
+        current.id(),
+        version + 1,
+        (Utc::now(), user),
+        data,
+    )
+    .await
+}
+
+// oldest first; records that have never been edited have no versions
+pub async fn list(
+    State(state): State<AppState>,
+    target_type: RevisionTarget,
+    target: Uuid,
+) -> Result<Vec<Revision>> {
+    let rows = sqlx::query!(
+        "SELECT * FROM revisions WHERE target_type = $1 AND target = $2 ORDER BY version ASC",
+        target_type.as_str(),
+        target
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut revisions: Vec<Revision> = vec![];
+    for row in rows {
+        let changes = match revisions.last() {
+            Some(previous) => diff(&previous.data, &row.data),
+            None => vec![],
+        };
+        revisions.push(Revision {
+            id: row.id,
+            target: row.target,
+            target_type: row.target_type,
+            version: row.version,
+            created: row.created,
+            created_by: row.created_by,
+            data: row.data,
+            changes,
+        });
+    }
+    Ok(revisions)
+}
+
+pub async fn get(
+    State(state): State<AppState>,
+    target_type: RevisionTarget,
+    target: Uuid,
+    version: i32,
+) -> Result<Revision> {
+    list(State(state), target_type, target)
+        .await?
+        .into_iter()
+        .find(|revision| revision.version == version)
+        .ok_or(Error::msg("No such version"))
+}
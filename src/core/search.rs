@@ -0,0 +1,63 @@
+use anyhow::{Error, Result};
+use axum::extract::State;
+use serde::Serialize;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::AppState;
+
+const MAX_RESULTS: i64 = 50;
+
+// `title` and `snippet` are HTML - the underlying text is escaped and matches are wrapped in <mark>
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct SearchResult {
+    pub target: Uuid,
+    pub target_type: String,
+    pub investigation: Uuid,
+    pub investigation_name: String,
+    pub title: String,
+    pub snippet: String,
+    pub rank: f32,
+}
+
+// `q` uses the same syntax as web search engines: quoted phrases, `or`, and `-` to exclude. Only
+// investigations `user` can see are searched
+pub async fn search(
+    State(state): State<AppState>,
+    user: Uuid,
+    q: &str,
+    target_type: Option<&str>,
+    investigation: Option<Uuid>,
+) -> Result<Vec<SearchResult>> {
+    sqlx::query_as!(
+        SearchResult,
+        r#"WITH query AS (SELECT websearch_to_tsquery('english', $1) AS q)
+        SELECT
+            search_documents.target,
+            search_documents.target_type,
+            search_documents.investigation,
+            concat_ws(' ', investigations.first_name, investigations.middle_name, investigations.last_name) as "investigation_name!",
+            ts_headline('english', search_escape(search_documents.title), q, 'StartSel=<mark>, StopSel=</mark>, HighlightAll=true') as "title!",
+            ts_headline('english', search_escape(search_documents.body), q, 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2') as "snippet!",
+            ts_rank(search_documents.document, q) as "rank!"
+        FROM search_documents
+        CROSS JOIN query
+        JOIN investigations ON investigations.id = search_documents.investigation
+        WHERE search_documents.document @@ q
+            AND investigation_visible_to(search_documents.investigation, $5)
+            AND ($2::text IS NULL OR search_documents.target_type = $2)
+            AND ($3::uuid IS NULL OR search_documents.investigation = $3)
+        ORDER BY "rank!" DESC
+        LIMIT $4"#,
+        q,
+        target_type,
+        investigation,
+        MAX_RESULTS,
+        user,
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(Error::from)
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
-use axum::response::{IntoResponse, Redirect};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::{
     extract::{Request, State},
     middleware::Next,
@@ -8,7 +9,10 @@ use axum_extra::extract::{cookie::Cookie, CookieJar, PrivateCookieJar};
 use chrono::{DateTime, Duration, Utc};
 use uuid::{NoContext, Timestamp, Uuid};
 
-use crate::{core::users::User, AppState};
+use crate::{
+    core::{tokens::ApiToken, users::User},
+    AppState,
+};
 
 pub struct Session {
     pub id: Uuid,
@@ -56,13 +60,88 @@ impl Session {
     }
 }
 
+// what a request was authenticated with, so responses that stay open can check it still holds
+#[derive(Clone)]
+pub enum Credential {
+    Session(Uuid),
+    Token(Uuid),
+}
+
+impl Credential {
+    // false once the session has expired or been logged out, the token has expired or been
+    // revoked, or the user has been deactivated
+    pub async fn is_valid(&self, State(state): State<AppState>) -> bool {
+        let user = match self {
+            Credential::Session(id) => {
+                match Session::get_by_id(State(state.clone()), &id.to_string()).await {
+                    Ok(session) if session.is_valid() => session.user,
+                    _ => return false,
+                }
+            }
+            Credential::Token(id) => match ApiToken::get_valid(State(state.clone()), *id).await {
+                Ok(token) => token.user,
+                Err(_) => return false,
+            },
+        };
+        User::get_by_id(State(state), &user.to_string())
+            .await
+            .is_ok_and(|user| user.is_active())
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(|token| token.trim())
+}
+
+// scripts get a status code rather than the login redirect, and no cookies are touched
+async fn token_layer(
+    State(state): State<AppState>,
+    secret: &str,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let unauthorized = (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+    );
+    let Ok(token) = ApiToken::authenticate(State(state.clone()), secret).await else {
+        return unauthorized.into_response();
+    };
+    let Ok(user) = User::get_by_id(State(state), &token.user.to_string()).await else {
+        return unauthorized.into_response();
+    };
+    if !user.is_active() {
+        return unauthorized.into_response();
+    }
+    // the routers are nested under /api, so this path is relative to it
+    if !token.allows(request.method(), request.uri().path()) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(Credential::Token(token.id));
+    next.run(request).await
+}
+
+// requests authenticate with either the session cookie from logging in, or an API token in an
+// `Authorization: Bearer` header
 pub async fn session_layer(
     State(state): State<AppState>,
     jar: CookieJar,
     private_jar: PrivateCookieJar,
     mut request: Request,
     next: Next,
-) -> impl IntoResponse {
+) -> Response {
+    if let Some(secret) = bearer_token(request.headers()) {
+        let secret = secret.to_string();
+        return token_layer(State(state), &secret, request, next).await;
+    }
+
     // TODO: see about simplifying these nested if statements
     if let Some(session_id) = private_jar.get("session") {
         // we have an encrypted session cookie
@@ -77,12 +156,16 @@ pub async fn session_layer(
                     if user.is_active() {
                         // the user is active, so we add the user to the request extensions
                         request.extensions_mut().insert(user);
+                        request
+                            .extensions_mut()
+                            .insert(Credential::Session(session.id));
                         return (
                             // TODO: update user data if anything has changed
                             jar,
                             private_jar,
                             next.run(request).await,
-                        );
+                        )
+                            .into_response();
                     }
                 }
             } else {
@@ -98,4 +181,5 @@ pub async fn session_layer(
         private_jar.remove(Cookie::build("session").path("/")),
         Redirect::temporary("/").into_response(),
     )
+        .into_response()
 }
@@ -0,0 +1,348 @@
+use anyhow::{Error, Result};
+use axum::extract::State;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_xlsxwriter::{Format, Workbook};
+use serde::Deserialize;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::investigations::{full_name, ActionItem};
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SpreadsheetFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl SpreadsheetFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SpreadsheetFormat::Csv => "text/csv",
+            SpreadsheetFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SpreadsheetFormat::Csv => "csv",
+            SpreadsheetFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ActionItemFilter {
+    pub investigation: Option<Uuid>,
+    pub assignee: Option<Uuid>,
+    pub status: Option<String>,
+    pub overdue: Option<bool>,
+}
+
+// one row per action item, plus one for each question with no action items (unless the filter
+// only makes sense for action items)
+struct ExportRow {
+    first_name: String,
+    middle_name: Option<String>,
+    last_name: String,
+    question_pretty_id: String,
+    question_summary: String,
+    question_status: String,
+    pretty_id: Option<String>,
+    summary: Option<String>,
+    status: Option<String>,
+    priority: Option<String>,
+    assignee: Option<String>,
+    due_date: Option<NaiveDate>,
+    resolved: Option<DateTime<Utc>>,
+    estimated_hours: Option<f32>,
+    outcome: Option<String>,
+}
+
+const HEADERS: [&str; 14] = [
+    "Investigation",
+    "Question",
+    "Question summary",
+    "Question status",
+    "Action item",
+    "Summary",
+    "Status",
+    "Priority",
+    "Assignee",
+    "Due date",
+    "Overdue",
+    "Resolved",
+    "Estimated hours",
+    "Outcome",
+];
+
+impl ExportRow {
+    fn is_overdue(&self) -> bool {
+        ActionItem::overdue(self.due_date, self.resolved)
+    }
+}
+
+async fn get_rows(
+    State(state): State<AppState>,
+    filter: &ActionItemFilter,
+) -> Result<Vec<ExportRow>> {
+    // questions without action items only belong in an export that isn't filtering on them
+    let include_bare_questions =
+        filter.assignee.is_none() && filter.status.is_none() && filter.overdue.is_none();
+
+    sqlx::query_as!(
+        ExportRow,
+        r#"SELECT
+            investigations.first_name,
+            investigations.middle_name,
+            investigations.last_name,
+            questions.pretty_id                          as question_pretty_id,
+            questions.summary                            as question_summary,
+            questions.status                             as question_status,
+            action_items.pretty_id                       as "pretty_id?",
+            action_items.summary                         as "summary?",
+            action_items.status                          as "status?",
+            action_items.priority                        as "priority?",
+            coalesce(users.display_name, users.email)    as assignee,
+            action_items.due_date                        as "due_date?",
+            action_items.resolved                        as "resolved?",
+            action_items.estimated_hours                 as "estimated_hours?",
+            action_items.outcome                         as "outcome?"
+        FROM questions
+        JOIN investigations ON investigations.id = questions.investigation
+        LEFT JOIN action_items ON action_items.question = questions.id
+        LEFT JOIN users ON users.id = action_items.assignee
+        WHERE investigations.merged_into IS NULL
+            AND ($1::uuid IS NULL OR investigations.id = $1)
+            AND ($2::uuid IS NULL OR action_items.assignee = $2)
+            AND ($3::text IS NULL OR action_items.status = $3)
+            AND ($4::bool IS NULL
+                OR action_item_is_overdue(action_items.resolved, action_items.due_date, $5) = $4)
+            AND (action_items.id IS NOT NULL OR $6)
+        ORDER BY investigations.last_name, investigations.first_name, investigations.id,
+            questions.pretty_id, action_items.pretty_id"#,
+        filter.investigation,
+        filter.assignee,
+        filter.status,
+        filter.overdue,
+        Utc::now().date_naive(),
+        include_bare_questions,
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(Error::from)
+}
+
+pub async fn export_action_items(
+    State(state): State<AppState>,
+    filter: ActionItemFilter,
+    format: SpreadsheetFormat,
+) -> Result<Vec<u8>> {
+    let rows = get_rows(State(state), &filter).await?;
+    match format {
+        SpreadsheetFormat::Csv => to_csv(&rows),
+        SpreadsheetFormat::Xlsx => to_xlsx(&rows),
+    }
+}
+
+// spreadsheet apps run cells starting with these as formulas, which can be abused by anyone who
+// can write a summary or an outcome
+fn csv_safe(value: String) -> String {
+    match value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        true => format!("'{}", value),
+        false => value,
+    }
+}
+
+fn to_csv(rows: &[ExportRow]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(HEADERS)?;
+    for row in rows {
+        let overdue = row.is_overdue();
+        writer.write_record(
+            [
+                full_name(&row.first_name, row.middle_name.as_deref(), &row.last_name),
+                row.question_pretty_id.clone(),
+                row.question_summary.clone(),
+                row.question_status.clone(),
+                row.pretty_id.clone().unwrap_or_default(),
+                row.summary.clone().unwrap_or_default(),
+                row.status.clone().unwrap_or_default(),
+                row.priority.clone().unwrap_or_default(),
+                row.assignee.clone().unwrap_or_default(),
+                row.due_date.map(|d| d.to_string()).unwrap_or_default(),
+                match row.pretty_id.is_some() {
+                    true => overdue.to_string(),
+                    false => String::new(),
+                },
+                row.resolved.map(|r| r.to_rfc3339()).unwrap_or_default(),
+                row.estimated_hours
+                    .map(|h| h.to_string())
+                    .unwrap_or_default(),
+                row.outcome.clone().unwrap_or_default(),
+            ]
+            .map(csv_safe),
+        )?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+fn to_xlsx(rows: &[ExportRow]) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Action items")?;
+
+    let header = Format::new().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    let timestamp = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+
+    for (col, title) in HEADERS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *title, &header)?;
+    }
+
+    for (i, row) in rows.iter().enumerate() {
+        let r = i as u32 + 1;
+        worksheet.write_string(
+            r,
+            0,
+            full_name(&row.first_name, row.middle_name.as_deref(), &row.last_name),
+        )?;
+        worksheet.write_string(r, 1, &row.question_pretty_id)?;
+        worksheet.write_string(r, 2, &row.question_summary)?;
+        worksheet.write_string(r, 3, &row.question_status)?;
+        // blank cells are left empty rather than written, so filters show them as blanks
+        for (col, value) in [
+            (4, &row.pretty_id),
+            (5, &row.summary),
+            (6, &row.status),
+            (7, &row.priority),
+            (8, &row.assignee),
+            (13, &row.outcome),
+        ] {
+            if let Some(value) = value {
+                worksheet.write_string(r, col, value)?;
+            }
+        }
+        if let Some(due_date) = row.due_date {
+            worksheet.write_date_with_format(r, 9, due_date, &date)?;
+        }
+        if row.pretty_id.is_some() {
+            worksheet.write_boolean(r, 10, row.is_overdue())?;
+        }
+        if let Some(resolved) = row.resolved {
+            worksheet.write_datetime_with_format(r, 11, resolved.naive_utc(), &timestamp)?;
+        }
+        if let Some(hours) = row.estimated_hours {
+            worksheet.write_number(r, 12, hours as f64)?;
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofilter(0, 0, rows.len() as u32, HEADERS.len() as u16 - 1)?;
+    worksheet.autofit();
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Days;
+
+    fn question_row() -> ExportRow {
+        ExportRow {
+            first_name: "Jane".to_string(),
+            middle_name: Some("Q".to_string()),
+            last_name: "Doe".to_string(),
+            question_pretty_id: "Q1".to_string(),
+            question_summary: "Where was she last seen?".to_string(),
+            question_status: "open".to_string(),
+            pretty_id: None,
+            summary: None,
+            status: None,
+            priority: None,
+            assignee: None,
+            due_date: None,
+            resolved: None,
+            estimated_hours: None,
+            outcome: None,
+        }
+    }
+
+    fn fixture() -> Vec<ExportRow> {
+        let yesterday = Utc::now().date_naive() - Days::new(1);
+        vec![
+            ExportRow {
+                pretty_id: Some("Q1-A1".to_string()),
+                summary: Some("=HYPERLINK(\"http://example.com\")".to_string()),
+                status: Some("assigned".to_string()),
+                priority: Some("high".to_string()),
+                assignee: Some("Sam".to_string()),
+                due_date: Some(yesterday),
+                estimated_hours: Some(1.5),
+                ..question_row()
+            },
+            ExportRow {
+                pretty_id: Some("Q1-A2".to_string()),
+                summary: Some("Call the family".to_string()),
+                status: Some("complete".to_string()),
+                priority: Some("normal".to_string()),
+                due_date: Some(yesterday),
+                resolved: Some(Utc::now()),
+                outcome: Some("Spoke to her brother".to_string()),
+                ..question_row()
+            },
+            ExportRow {
+                question_pretty_id: "Q2".to_string(),
+                question_summary: "Did she have a car?".to_string(),
+                ..question_row()
+            },
+        ]
+    }
+
+    #[test]
+    fn exports_csv() {
+        let csv = String::from_utf8(to_csv(&fixture()).unwrap()).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(reader.headers().unwrap(), HEADERS.as_slice());
+        let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(&records[0][0], "Jane Q Doe");
+        assert_eq!(&records[0][4], "Q1-A1");
+        // formulas are written as text
+        assert_eq!(&records[0][5], "'=HYPERLINK(\"http://example.com\")");
+        assert_eq!(&records[0][10], "true");
+        assert_eq!(&records[0][11], "");
+        assert_eq!(&records[0][12], "1.5");
+
+        // resolved items aren't overdue, however late they were
+        assert_eq!(&records[1][10], "false");
+        assert!(!records[1][11].is_empty());
+        assert_eq!(&records[1][13], "Spoke to her brother");
+
+        // questions without action items leave the action item columns blank
+        assert_eq!(&records[2][1], "Q2");
+        assert!((4..14).all(|col| records[2][col].is_empty()));
+    }
+
+    #[test]
+    fn marks_overdue_rows() {
+        assert!(fixture()[0].is_overdue());
+        assert!(!fixture()[1].is_overdue());
+        assert!(!fixture()[2].is_overdue());
+    }
+
+    #[test]
+    fn exports_xlsx() {
+        let xlsx = to_xlsx(&fixture()).unwrap();
+        // an xlsx file is a zip archive
+        assert!(xlsx.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::{Error, Result};
+use object_store::{aws::AmazonS3Builder, local::LocalFileSystem, path::Path, ObjectStore};
+use tracing::*;
+
+use std::sync::Arc;
+
+// the backend is chosen with `STORAGE_BACKEND` - anything S3-compatible (including a local MinIO
+// instance) works with the `s3` backend
+pub fn from_env() -> Result<Arc<dyn ObjectStore>> {
+    match std::env::var("STORAGE_BACKEND")
+        .unwrap_or("local".to_string())
+        .as_str()
+    {
+        "local" => {
+            let path = std::env::var("STORAGE_PATH").unwrap_or("storage".to_string());
+            std::fs::create_dir_all(&path)?;
+            Ok(Arc::new(LocalFileSystem::new_with_prefix(path)?))
+        }
+        "s3" => {
+            let mut builder = AmazonS3Builder::new()
+                .with_bucket_name(std::env::var("S3_BUCKET")?)
+                .with_region(std::env::var("S3_REGION").unwrap_or("us-east-1".to_string()))
+                .with_access_key_id(std::env::var("S3_ACCESS_KEY_ID")?)
+                .with_secret_access_key(std::env::var("S3_SECRET_ACCESS_KEY")?);
+            if let Ok(endpoint) = std::env::var("S3_ENDPOINT") {
+                // MinIO and most other self-hosted stores are served over plain HTTP in development
+                builder = builder
+                    .with_allow_http(endpoint.starts_with("http://"))
+                    .with_endpoint(endpoint);
+            }
+            Ok(Arc::new(builder.build()?))
+        }
+        other => Err(Error::msg(format!("Unknown storage backend: {}", other))),
+    }
+}
+
+// for blobs written ahead of a database write that then failed; a blob that can't be removed is
+// only logged, so the original error is the one that gets reported
+pub async fn delete_all(storage: &dyn ObjectStore, keys: &[String]) {
+    for key in keys {
+        if let Err(e) = storage.delete(&Path::from(key.as_str())).await {
+            warn!("Unable to delete orphaned blob {}: {}", key, e);
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use anyhow::{Error, Result};
+use axum::{extract::State, Extension};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::{investigations::Investigation, log, profiles::Profile, users::User};
+use crate::AppState;
+
+// ordered from least to most certain
+pub const CONFIDENCE_LEVELS: [&str; 5] = ["unverified", "low", "medium", "high", "confirmed"];
+
+// places further apart than this are treated as different locations
+const SAME_PLACE_KM: f64 = 1.0;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct TimelineEvent {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub creator: Uuid,
+    pub investigation: Uuid,
+    // the window the event happened in; both are the same when the exact time is known
+    pub earliest: DateTime<Utc>,
+    pub latest: DateTime<Utc>,
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub description: String,
+    // where the information came from, e.g. a witness or a receipt
+    pub source: Option<String>,
+    pub confidence: String,
+    pub question: Option<Uuid>,
+    pub action_item: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TimelineEventDetails {
+    pub earliest: DateTime<Utc>,
+    // defaults to `earliest` for events with an exact time
+    pub latest: Option<DateTime<Utc>>,
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub description: String,
+    pub source: Option<String>,
+    // defaults to "unverified"
+    pub confidence: Option<String>,
+    pub question: Option<Uuid>,
+    pub action_item: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ChronologyKind {
+    Event,
+    LastSeen,
+    Photo,
+}
+
+// a single entry in an investigation's chronology, which combines its timeline events with
+// everything else we know the time of
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ChronologyEntry {
+    pub kind: ChronologyKind,
+    // the timeline event, investigation or attachment the entry comes from
+    pub target: Uuid,
+    pub earliest: DateTime<Utc>,
+    pub latest: DateTime<Utc>,
+    pub description: String,
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub source: Option<String>,
+    pub confidence: Option<String>,
+    // entries whose windows overlap this one's
+    pub overlaps_with: Vec<Uuid>,
+    // overlapping entries somewhere else, i.e. the person would have had to be in two places
+    pub conflicts_with: Vec<Uuid>,
+}
+
+impl TimelineEventDetails {
+    pub fn is_valid(&self) -> bool {
+        let valid_location = match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => {
+                (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        !self.description.trim().is_empty()
+            && self.latest.is_none_or(|latest| latest >= self.earliest)
+            && self
+                .confidence
+                .as_deref()
+                .is_none_or(|c| CONFIDENCE_LEVELS.contains(&c))
+            && valid_location
+    }
+}
+
+impl TimelineEvent {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<TimelineEvent> {
+        sqlx::query_as!(
+            TimelineEvent,
+            "SELECT * FROM timeline_events WHERE id = $1",
+            id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn get_for_investigation(
+        State(state): State<AppState>,
+        investigation: Uuid,
+    ) -> Result<Vec<TimelineEvent>> {
+        sqlx::query_as!(
+            TimelineEvent,
+            "SELECT * FROM timeline_events WHERE investigation = $1 ORDER BY earliest ASC, latest ASC, created ASC",
+            investigation
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        investigation: Uuid,
+        details: TimelineEventDetails,
+    ) -> Result<TimelineEvent> {
+        validate(State(state.clone()), investigation, &details).await?;
+
+        let event = sqlx::query_as!(
+            TimelineEvent,
+            "INSERT INTO timeline_events (id, created, creator, investigation, earliest, latest, location, latitude, longitude, description, source, confidence, question, action_item) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            Utc::now(),
+            user.id,
+            investigation,
+            details.earliest,
+            details.latest.unwrap_or(details.earliest),
+            details.location,
+            details.latitude,
+            details.longitude,
+            details.description,
+            details.source,
+            details.confidence.as_deref().unwrap_or("unverified"),
+            details.question,
+            details.action_item,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            event.id,
+            "timeline_event",
+            None,
+            Some(&event),
+            "Timeline event created",
+        )
+        .await?;
+
+        Ok(event)
+    }
+
+    pub async fn update(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        details: TimelineEventDetails,
+    ) -> Result<()> {
+        validate(State(state.clone()), self.investigation, &details).await?;
+
+        let previous = self.clone();
+        *self = sqlx::query_as!(
+            TimelineEvent,
+            "UPDATE timeline_events SET earliest = $1, latest = $2, location = $3, latitude = $4, longitude = $5, description = $6, source = $7, confidence = $8, question = $9, action_item = $10 WHERE id = $11 RETURNING *",
+            details.earliest,
+            details.latest.unwrap_or(details.earliest),
+            details.location,
+            details.latitude,
+            details.longitude,
+            details.description,
+            details.source,
+            details.confidence.as_deref().unwrap_or("unverified"),
+            details.question,
+            details.action_item,
+            self.id,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "timeline_event",
+            Some(&previous),
+            Some(self),
+            "Timeline event updated",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(
+        self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<()> {
+        sqlx::query!("DELETE FROM timeline_events WHERE id = $1", self.id)
+            .execute(&state.db)
+            .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "timeline_event",
+            Some(&self),
+            None,
+            "Timeline event deleted",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    fn to_chronology_entry(&self) -> ChronologyEntry {
+        ChronologyEntry {
+            kind: ChronologyKind::Event,
+            target: self.id,
+            earliest: self.earliest,
+            latest: self.latest,
+            description: self.description.clone(),
+            location: self.location.clone(),
+            latitude: self.latitude,
+            longitude: self.longitude,
+            source: self.source.clone(),
+            confidence: Some(self.confidence.clone()),
+            overlaps_with: vec![],
+            conflicts_with: vec![],
+        }
+    }
+}
+
+// linked questions and action items have to belong to the same investigation as the event
+async fn validate(
+    State(state): State<AppState>,
+    investigation: Uuid,
+    details: &TimelineEventDetails,
+) -> Result<()> {
+    if !details.is_valid() {
+        return Err(Error::msg("Invalid timeline event"));
+    }
+    if let Some(question) = details.question {
+        let question_investigation = sqlx::query_scalar!(
+            "SELECT investigation FROM questions WHERE id = $1",
+            question
+        )
+        .fetch_one(&state.db)
+        .await?;
+        if question_investigation != investigation {
+            return Err(Error::msg("Question belongs to a different investigation"));
+        }
+    }
+    if let Some(action_item) = details.action_item {
+        let action_item_investigation = sqlx::query_scalar!(
+            "SELECT questions.investigation FROM action_items JOIN questions ON questions.id = action_items.question WHERE action_items.id = $1",
+            action_item
+        )
+        .fetch_one(&state.db)
+        .await?;
+        if action_item_investigation != investigation {
+            return Err(Error::msg(
+                "Action item belongs to a different investigation",
+            ));
+        }
+    }
+    Ok(())
+}
+
+// the investigation's timeline events, when the subject was last seen and when any photos attached
+// to it were taken, in order, with overlapping and conflicting entries flagged
+pub async fn get_chronology(
+    State(state): State<AppState>,
+    investigation: Uuid,
+) -> Result<Vec<ChronologyEntry>> {
+    let investigation =
+        Investigation::get(State(state.clone()), &investigation.to_string(), false).await?;
+
+    let mut entries = TimelineEvent::get_for_investigation(State(state.clone()), investigation.id)
+        .await?
+        .iter()
+        .map(|e| e.to_chronology_entry())
+        .collect::<Vec<ChronologyEntry>>();
+
+    // we only know the day they went missing, not the time
+    let last_seen = investigation
+        .missing_since
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let profile = Profile::get(State(state.clone()), investigation.id).await?;
+    entries.push(ChronologyEntry {
+        kind: ChronologyKind::LastSeen,
+        target: investigation.id,
+        earliest: last_seen,
+        latest: last_seen + Duration::days(1) - Duration::seconds(1),
+        description: "Last seen".to_string(),
+        location: profile.as_ref().and_then(|p| {
+            let parts = [
+                &p.last_known_address,
+                &p.last_known_city,
+                &p.last_known_state,
+            ]
+            .into_iter()
+            .flatten()
+            .map(|s| s.as_str())
+            .collect::<Vec<&str>>();
+            (!parts.is_empty()).then(|| parts.join(", "))
+        }),
+        latitude: profile.as_ref().and_then(|p| p.last_known_latitude),
+        longitude: profile.as_ref().and_then(|p| p.last_known_longitude),
+        source: None,
+        confidence: None,
+        overlaps_with: vec![],
+        conflicts_with: vec![],
+    });
+
+    let photos = sqlx::query!(
+        r#"SELECT attachments.id, attachments.filename, attachment_metadata.captured as "captured!", attachment_metadata.latitude, attachment_metadata.longitude
+        FROM attachments
+        JOIN attachment_metadata ON attachment_metadata.attachment = attachments.id
+        WHERE attachments.investigation = $1 AND attachment_metadata.captured IS NOT NULL"#,
+        investigation.id
+    )
+    .fetch_all(&state.db)
+    .await?;
+    for photo in photos {
+        entries.push(ChronologyEntry {
+            kind: ChronologyKind::Photo,
+            target: photo.id,
+            earliest: photo.captured,
+            latest: photo.captured,
+            description: format!("Photo taken: {}", photo.filename),
+            location: None,
+            latitude: photo.latitude,
+            longitude: photo.longitude,
+            source: Some("Photo metadata".to_string()),
+            confidence: None,
+            overlaps_with: vec![],
+            conflicts_with: vec![],
+        });
+    }
+
+    entries.sort_by_key(|e| (e.earliest, e.latest));
+    flag_overlaps(&mut entries);
+
+    Ok(entries)
+}
+
+// entries are sorted by `earliest`, so each one only has to be checked against the entries after it
+// that start before it ends
+fn flag_overlaps(entries: &mut [ChronologyEntry]) {
+    for i in 0..entries.len() {
+        for j in (i + 1)..entries.len() {
+            if entries[j].earliest > entries[i].latest {
+                break;
+            }
+            let (a, b) = (entries[i].target, entries[j].target);
+            let conflict = different_places(&entries[i], &entries[j]);
+            entries[i].overlaps_with.push(b);
+            entries[j].overlaps_with.push(a);
+            if conflict {
+                entries[i].conflicts_with.push(b);
+                entries[j].conflicts_with.push(a);
+            }
+        }
+    }
+}
+
+// coordinates are compared when both entries have them, otherwise the location descriptions are.
+// Entries without any location can't conflict
+fn different_places(a: &ChronologyEntry, b: &ChronologyEntry) -> bool {
+    if let (Some(a_lat), Some(a_lon), Some(b_lat), Some(b_lon)) =
+        (a.latitude, a.longitude, b.latitude, b.longitude)
+    {
+        return distance_km(a_lat, a_lon, b_lat, b_lon) > SAME_PLACE_KM;
+    }
+    match (&a.location, &b.location) {
+        (Some(a), Some(b)) => a.trim().to_lowercase() != b.trim().to_lowercase(),
+        _ => false,
+    }
+}
+
+// haversine distance
+fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (lon2 - lon1).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
@@ -0,0 +1,721 @@
+use anyhow::{Error, Result};
+use axum::{body::Bytes, extract::State, Extension};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgConnection};
+use tracing::*;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::api::admin::investigations::{CreateActionItemDetails, CreateQuestionDetails};
+use crate::core::attachments::{sha256_hex, Attachment, AttachmentTarget};
+use crate::core::investigations::{ActionItem, Question};
+use crate::core::{log, storage, users::User};
+use crate::AppState;
+
+pub const TIP_STATUSES: [&str; 4] = ["new", "reviewing", "converted", "dismissed"];
+
+pub const MAX_TIP_ATTACHMENTS: usize = 5;
+
+const CHALLENGE_LIFETIME_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct Tip {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub investigation: Uuid,
+    pub status: String,
+    pub body: String,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    // a keyed hash of the submitter's IP address, only good for spotting repeat submitters
+    pub submitter: String,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed: Option<DateTime<Utc>>,
+    // the question or action item the tip was turned into
+    pub converted_target: Option<Uuid>,
+    pub converted_target_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct TipAttachment {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub tip: Uuid,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub sha256: String,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+}
+
+#[derive(Debug, Default)]
+pub struct TipSubmission {
+    pub body: String,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    // filename, claimed content type and contents
+    pub files: Vec<(String, Option<String>, Bytes)>,
+}
+
+// the submitter has to find a nonce where sha256(challenge + nonce) starts with `difficulty` zero
+// bits before their tip is accepted
+#[derive(Debug, Clone, Serialize, FromRow, TS, ToSchema)]
+#[ts(export)]
+pub struct TipChallenge {
+    pub id: Uuid,
+    pub challenge: String,
+    pub difficulty: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, TS, ToSchema)]
+#[serde(tag = "to", rename_all = "snake_case")]
+#[ts(export)]
+pub enum TipConversion {
+    Question { pretty_id: String, summary: String },
+    ActionItem { question: Uuid, summary: String },
+}
+
+// the only public information about an investigation, shown to people submitting a tip
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TipInvestigation {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub missing_since: NaiveDate,
+}
+
+fn pow_difficulty() -> i32 {
+    std::env::var("TIP_POW_DIFFICULTY")
+        .ok()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(18)
+}
+
+fn tips_per_hour() -> i64 {
+    std::env::var("TIP_RATE_LIMIT_PER_HOUR")
+        .ok()
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(5)
+}
+
+// keyed so the hashes can't be reversed by hashing every possible address
+pub fn submitter_hash(address: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(std::env::var("SIGNING_KEY")?.as_bytes());
+    hasher.update(address.as_bytes());
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+// challenges are limited along with tips, so they can't be stockpiled ahead of a flood
+pub async fn is_rate_limited(State(state): State<AppState>, submitter: &str) -> Result<bool> {
+    let since = Utc::now() - Duration::hours(1);
+    let tips = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM tips WHERE submitter = $1 AND created > $2"#,
+        submitter,
+        since
+    )
+    .fetch_one(&state.db)
+    .await?;
+    let challenges = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM tip_challenges WHERE submitter = $1 AND created > $2"#,
+        submitter,
+        since
+    )
+    .fetch_one(&state.db)
+    .await?;
+    Ok(tips >= tips_per_hour() || challenges >= tips_per_hour() * 2)
+}
+
+pub async fn issue_challenge(
+    State(state): State<AppState>,
+    submitter: &str,
+) -> Result<TipChallenge> {
+    // expired challenges are cleared out as new ones are handed out
+    sqlx::query!(
+        "DELETE FROM tip_challenges WHERE created < $1",
+        Utc::now() - Duration::minutes(CHALLENGE_LIFETIME_MINUTES)
+    )
+    .execute(&state.db)
+    .await?;
+
+    sqlx::query_as!(
+        TipChallenge,
+        "INSERT INTO tip_challenges (id, created, challenge, difficulty, submitter) VALUES ($1, $2, $3, $4, $5) RETURNING id, challenge, difficulty",
+        Uuid::new_v7(Timestamp::now(NoContext)),
+        Utc::now(),
+        Uuid::new_v4().simple().to_string(),
+        pow_difficulty(),
+        submitter,
+    )
+    .fetch_one(&state.db)
+    .await
+    .map_err(Error::from)
+}
+
+// each challenge can only be used once, whether or not the nonce is right
+pub async fn verify_challenge(
+    State(state): State<AppState>,
+    challenge_id: Uuid,
+    nonce: &str,
+) -> Result<bool> {
+    let challenge = sqlx::query_as!(
+        IssuedChallenge,
+        "DELETE FROM tip_challenges WHERE id = $1 RETURNING challenge, difficulty, created",
+        challenge_id
+    )
+    .fetch_optional(&state.db)
+    .await?;
+    Ok(is_solved(challenge.as_ref(), nonce, Utc::now()))
+}
+
+struct IssuedChallenge {
+    challenge: String,
+    difficulty: i32,
+    created: DateTime<Utc>,
+}
+
+// challenges are deleted as they're checked, so one that's already been used is `None`
+fn is_solved(challenge: Option<&IssuedChallenge>, nonce: &str, now: DateTime<Utc>) -> bool {
+    let Some(challenge) = challenge else {
+        return false;
+    };
+    if challenge.created < now - Duration::minutes(CHALLENGE_LIFETIME_MINUTES) {
+        return false;
+    }
+    let hash = Sha256::digest(format!("{}{}", challenge.challenge, nonce).as_bytes());
+    leading_zero_bits(&hash) >= challenge.difficulty as u32
+}
+
+pub async fn get_investigation(
+    State(state): State<AppState>,
+    investigation: Uuid,
+) -> Result<TipInvestigation> {
+    sqlx::query_as!(
+        TipInvestigation,
+        "SELECT id, first_name, last_name, missing_since FROM investigations WHERE id = $1 AND merged_into IS NULL",
+        investigation
+    )
+    .fetch_one(&state.db)
+    .await
+    .map_err(Error::from)
+}
+
+impl Tip {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<Tip> {
+        sqlx::query_as!(Tip, "SELECT * FROM tips WHERE id = $1", id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(Error::from)
+    }
+
+    // the triage queue, oldest first so nothing waits forever
+    pub async fn list(
+        State(state): State<AppState>,
+        status: Option<&str>,
+        investigation: Option<Uuid>,
+    ) -> Result<Vec<Tip>> {
+        sqlx::query_as!(
+            Tip,
+            r#"SELECT * FROM tips
+            WHERE ($1::text IS NULL OR status = $1)
+                AND ($2::uuid IS NULL OR investigation = $2)
+            ORDER BY created ASC"#,
+            status,
+            investigation,
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn submit(
+        State(state): State<AppState>,
+        investigation: Uuid,
+        submitter: &str,
+        submission: TipSubmission,
+    ) -> Result<Tip> {
+        // only tips about investigations we'd show publicly are accepted
+        get_investigation(State(state.clone()), investigation).await?;
+
+        // files are written as they're read, so any that were written are removed if the tip can't
+        // be saved
+        let mut written = vec![];
+        let result: Result<Tip> = async {
+            let mut tx = state.db.begin().await?;
+            let tip = Tip::insert(
+                &mut tx,
+                &state,
+                investigation,
+                submitter,
+                submission,
+                &mut written,
+            )
+            .await?;
+            tx.commit().await?;
+            Ok(tip)
+        }
+        .await;
+        if result.is_err() {
+            storage::delete_all(state.storage.as_ref(), &written).await;
+        }
+        result
+    }
+
+    async fn insert(
+        conn: &mut PgConnection,
+        state: &AppState,
+        investigation: Uuid,
+        submitter: &str,
+        submission: TipSubmission,
+        written: &mut Vec<String>,
+    ) -> Result<Tip> {
+        let tip = sqlx::query_as!(
+            Tip,
+            "INSERT INTO tips (id, created, investigation, body, contact_name, contact_email, contact_phone, submitter) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            Utc::now(),
+            investigation,
+            submission.body,
+            submission.contact_name,
+            submission.contact_email,
+            submission.contact_phone,
+            submitter,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        for (filename, content_type, data) in submission.files {
+            let mime_type = infer::get(&data)
+                .map(|t| t.mime_type())
+                .or(content_type.as_deref())
+                .unwrap_or("application/octet-stream")
+                .to_string();
+            let id = Uuid::new_v7(Timestamp::now(NoContext));
+            let storage_key = format!("tips/{}/{}", tip.id, id);
+            let sha256 = sha256_hex(&data);
+            let size = data.len() as i64;
+
+            state
+                .storage
+                .put(&Path::from(storage_key.as_str()), data.into())
+                .await?;
+            written.push(storage_key.clone());
+
+            sqlx::query!(
+                "INSERT INTO tip_attachments (id, created, tip, filename, mime_type, size, sha256, storage_key) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                id,
+                Utc::now(),
+                tip.id,
+                filename,
+                mime_type,
+                size,
+                sha256,
+                storage_key,
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(tip)
+    }
+
+    pub async fn get_attachments(
+        &self,
+        State(state): State<AppState>,
+    ) -> Result<Vec<TipAttachment>> {
+        sqlx::query_as!(
+            TipAttachment,
+            "SELECT * FROM tip_attachments WHERE tip = $1 ORDER BY created ASC",
+            self.id
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn set_status(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        status: &str,
+    ) -> Result<()> {
+        // converted tips are finished with, and only get there through `convert`
+        if !TIP_STATUSES.contains(&status) || status == "converted" {
+            return Err(Error::msg("Invalid tip status"));
+        }
+        if self.status == "converted" {
+            return Err(Error::msg("Tip has already been converted"));
+        }
+
+        let previous = self.clone();
+        *self = sqlx::query_as!(
+            Tip,
+            "UPDATE tips SET status = $1, reviewed_by = $2, reviewed = $3 WHERE id = $4 RETURNING *",
+            status,
+            user.id,
+            Utc::now(),
+            self.id,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "tip",
+            Some(&previous),
+            Some(self),
+            "Tip status changed",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // creates a question or action item from the tip, carrying over its attachments, and returns
+    // the new question or action item's id
+    pub async fn convert(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+        conversion: TipConversion,
+    ) -> Result<Uuid> {
+        if self.status == "converted" {
+            return Err(Error::msg("Tip has already been converted"));
+        }
+        if let TipConversion::ActionItem { question, .. } = &conversion {
+            if Question::get(State(state.clone()), *question)
+                .await?
+                .investigation
+                != self.investigation
+            {
+                return Err(Error::msg("Question belongs to a different investigation"));
+            }
+        }
+        let tip_attachments = self.get_attachments(State(state.clone())).await?;
+
+        // copies of the tip's attachments, written before the transaction commits
+        let mut attachments: Vec<(Attachment, Bytes)> = vec![];
+        let result: Result<(Uuid, &str, Tip)> = async {
+            let mut tx = state.db.begin().await?;
+
+            let details = Some(self.details());
+            let (target, target_type, attachment_target, attachment_target_type) =
+                match conversion {
+                    TipConversion::Question { pretty_id, summary } => {
+                        let question = Question::insert(
+                            &mut tx,
+                            user.id,
+                            CreateQuestionDetails {
+                                pretty_id,
+                                summary,
+                                details,
+                                investigation: self.investigation,
+                                outcome: None,
+                                status: "not_started".to_string(),
+                                action_items: vec![],
+                            },
+                        )
+                        .await?;
+                        // questions can't have attachments, so they go on the investigation instead
+                        (
+                            question.id,
+                            "question",
+                            self.investigation,
+                            AttachmentTarget::Investigation,
+                        )
+                    }
+                    TipConversion::ActionItem { question, summary } => {
+                        let action_item = ActionItem::create(
+                            &mut tx,
+                            user.id,
+                            question,
+                            CreateActionItemDetails {
+                                pretty_id: None,
+                                summary,
+                                details,
+                                status: None,
+                                outcome: None,
+                                assignee: None,
+                                resolved: None,
+                                due_date: None,
+                                priority: None,
+                                estimated_hours: None,
+                            },
+                        )
+                        .await?;
+                        (
+                            action_item.id,
+                            "action_item",
+                            action_item.id,
+                            AttachmentTarget::ActionItem,
+                        )
+                    }
+                };
+
+            for tip_attachment in &tip_attachments {
+                let data = state
+                    .storage
+                    .get(&Path::from(tip_attachment.storage_key.as_str()))
+                    .await?
+                    .bytes()
+                    .await?;
+                if sha256_hex(&data) != tip_attachment.sha256 {
+                    return Err(Error::msg("Tip attachment failed its integrity check"));
+                }
+
+                let id = Uuid::new_v7(Timestamp::now(NoContext));
+                let storage_key = format!("attachments/{}/{}", self.investigation, id);
+                state
+                    .storage
+                    .put(&Path::from(storage_key.as_str()), data.clone().into())
+                    .await?;
+                let attachment = sqlx::query_as!(
+                    Attachment,
+                    "INSERT INTO attachments (id, created, uploader, investigation, target, target_type, filename, mime_type, size, sha256, storage_key) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *",
+                    id,
+                    Utc::now(),
+                    user.id,
+                    self.investigation,
+                    attachment_target,
+                    attachment_target_type.as_str(),
+                    tip_attachment.filename,
+                    tip_attachment.mime_type,
+                    tip_attachment.size,
+                    tip_attachment.sha256,
+                    storage_key,
+                )
+                .fetch_one(&mut *tx)
+                .await;
+                match attachment {
+                    Ok(attachment) => attachments.push((attachment, data)),
+                    Err(e) => {
+                        storage::delete_all(state.storage.as_ref(), &[storage_key]).await;
+                        return Err(e.into());
+                    }
+                }
+            }
+
+            // checking the status again keeps two people converting the same tip at once from
+            // both succeeding
+            let tip = sqlx::query_as!(
+                Tip,
+                "UPDATE tips SET status = 'converted', reviewed_by = $1, reviewed = $2, converted_target = $3, converted_target_type = $4 WHERE id = $5 AND status <> 'converted' RETURNING *",
+                user.id,
+                Utc::now(),
+                target,
+                target_type,
+                self.id,
+            )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(Error::msg("Tip has already been converted"))?;
+
+            tx.commit().await?;
+            Ok((target, target_type, tip))
+        }
+        .await;
+
+        let (target, target_type, tip) = match result {
+            Ok(converted) => converted,
+            Err(e) => {
+                // nothing refers to the copies once the transaction is rolled back
+                let keys = attachments
+                    .iter()
+                    .map(|(a, _)| a.storage_key.clone())
+                    .collect::<Vec<_>>();
+                storage::delete_all(state.storage.as_ref(), &keys).await;
+                return Err(e);
+            }
+        };
+        let previous = std::mem::replace(self, tip);
+
+        for (attachment, data) in attachments {
+            log::record(
+                State(state.clone()),
+                user.id,
+                attachment.id,
+                "attachment",
+                None,
+                Some(&attachment),
+                "Attachment uploaded",
+            )
+            .await?;
+            // as with uploads, processing failures shouldn't undo the conversion
+            if attachment.target_type == "investigation"
+                && attachment.mime_type.starts_with("image/")
+            {
+                if let Err(e) = attachment.process_image(State(state.clone()), data).await {
+                    warn!(
+                        "Unable to process image attachment {}: {}",
+                        attachment.id, e
+                    );
+                }
+            }
+        }
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "tip",
+            Some(&previous),
+            Some(self),
+            &format!("Tip converted to {}", target_type.replace('_', " ")),
+        )
+        .await?;
+
+        Ok(target)
+    }
+
+    // the tip's text along with how to get back in touch with whoever sent it
+    fn details(&self) -> String {
+        let contact = [&self.contact_name, &self.contact_email, &self.contact_phone]
+            .into_iter()
+            .flatten()
+            .map(|c| c.as_str())
+            .collect::<Vec<&str>>();
+        let contact = match contact.is_empty() {
+            true => "none given".to_string(),
+            false => contact.join(", "),
+        };
+        format!(
+            "{}\n\nSubmitted as a tip on {}. Contact: {}",
+            self.body,
+            self.created.format("%Y-%m-%d %H:%M UTC"),
+            contact
+        )
+    }
+}
+
+impl TipAttachment {
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<TipAttachment> {
+        sqlx::query_as!(
+            TipAttachment,
+            "SELECT * FROM tip_attachments WHERE id = $1",
+            id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // tips come from the public, so every read is checked against the hash taken at submission
+    pub async fn read(
+        &self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<Bytes> {
+        let data = state
+            .storage
+            .get(&Path::from(self.storage_key.as_str()))
+            .await?
+            .bytes()
+            .await?;
+        if sha256_hex(&data) != self.sha256 {
+            return Err(Error::msg("Tip attachment failed its integrity check"));
+        }
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "tip_attachment",
+            None::<&TipAttachment>,
+            None,
+            "Tip attachment downloaded",
+        )
+        .await?;
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issued(difficulty: i32, created: DateTime<Utc>) -> IssuedChallenge {
+        IssuedChallenge {
+            challenge: "0123456789abcdef0123456789abcdef".to_string(),
+            difficulty,
+            created,
+        }
+    }
+
+    fn zero_bits(challenge: &IssuedChallenge, nonce: &str) -> u32 {
+        leading_zero_bits(&Sha256::digest(
+            format!("{}{}", challenge.challenge, nonce).as_bytes(),
+        ))
+    }
+
+    #[test]
+    fn counts_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0xff, 0x00]), 0);
+        assert_eq!(leading_zero_bits(&[0x01, 0xff]), 7);
+        assert_eq!(leading_zero_bits(&[0x00, 0x0f, 0x00]), 12);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00, 0x80]), 16);
+        // later bytes don't count once a set bit has been found
+        assert_eq!(leading_zero_bits(&[0x40, 0x00, 0x00]), 1);
+        assert_eq!(leading_zero_bits(&[0x00; 32]), 256);
+    }
+
+    #[test]
+    fn accepts_nonces_at_the_difficulty() {
+        let now = Utc::now();
+        let challenge = issued(0, now);
+        // any nonce solves a difficulty of zero
+        assert!(is_solved(Some(&challenge), "anything", now));
+
+        // find a nonce with a few leading zero bits, then check it right at the boundary
+        let (nonce, bits) = (0..)
+            .map(|n| n.to_string())
+            .map(|nonce| {
+                let bits = zero_bits(&challenge, &nonce);
+                (nonce, bits)
+            })
+            .find(|(_, bits)| *bits >= 6)
+            .unwrap();
+        assert!(is_solved(Some(&issued(bits as i32, now)), &nonce, now));
+        assert!(!is_solved(Some(&issued(bits as i32 + 1, now)), &nonce, now));
+    }
+
+    #[test]
+    fn rejects_expired_challenges() {
+        let now = Utc::now();
+        let lifetime = Duration::minutes(CHALLENGE_LIFETIME_MINUTES);
+        assert!(is_solved(Some(&issued(0, now - lifetime)), "0", now));
+        assert!(!is_solved(
+            Some(&issued(0, now - lifetime - Duration::seconds(1))),
+            "0",
+            now
+        ));
+    }
+
+    #[test]
+    fn rejects_used_challenges() {
+        assert!(!is_solved(None, "0", Utc::now()));
+    }
+}
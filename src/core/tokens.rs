@@ -0,0 +1,298 @@
+use anyhow::{Error, Result};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{extract::State, http::Method, Extension};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::core::attachments::sha256_hex;
+use crate::core::{log, users::User};
+use crate::AppState;
+
+// GET requests outside of /admin
+pub const READ: &str = "read";
+// any request outside of /admin
+pub const WRITE: &str = "write";
+// any request to /admin
+pub const ADMIN: &str = "admin";
+pub const SCOPES: [&str; 3] = [READ, WRITE, ADMIN];
+
+// nobody has to remember to revoke a token that's been forgotten about
+pub const MAX_LIFETIME_DAYS: i64 = 365;
+
+// makes leaked tokens easy to recognize, for people and for secret scanners
+const PREFIX: &str = "intricase_";
+
+// a token can't be used to make or revoke tokens, so a leaked one can't keep itself alive
+const TOKEN_MANAGEMENT_PATHS: [&str; 2] = ["/users/me/tokens", "/admin/service-accounts"];
+
+// the hash is left out, since nothing needs it once a token is saved
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ApiToken {
+    pub id: Uuid,
+    // the account that requests made with the token act as
+    pub user: Uuid,
+    pub name: String,
+    // the start of the token, to tell tokens apart
+    pub hint: String,
+    pub scopes: Vec<String>,
+    pub created: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub expires: DateTime<Utc>,
+    pub last_used: Option<DateTime<Utc>>,
+    pub revoked: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ApiTokenDetails {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires: DateTime<Utc>,
+}
+
+impl ApiTokenDetails {
+    pub fn is_valid(&self) -> bool {
+        let now = Utc::now();
+        !self.name.trim().is_empty()
+            && !self.scopes.is_empty()
+            && self.scopes.iter().all(|s| SCOPES.contains(&s.as_str()))
+            && self.expires > now
+            && self.expires <= now + Duration::days(MAX_LIFETIME_DAYS)
+    }
+}
+
+// the only time the token itself is available; just its hash is saved
+#[derive(Debug, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct CreatedApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+// tokens are long and random, so a fast hash is enough and lets them be looked up by it
+fn hash(secret: &str) -> String {
+    sha256_hex(secret.as_bytes())
+}
+
+fn generate() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let random: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", PREFIX, random)
+}
+
+impl ApiToken {
+    // `user` is who the token acts as: the creator for a personal token, or a service account
+    pub async fn create(
+        State(state): State<AppState>,
+        Extension(creator): Extension<User>,
+        user: Uuid,
+        details: ApiTokenDetails,
+    ) -> Result<CreatedApiToken> {
+        let secret = generate();
+        let mut scopes = details.scopes;
+        scopes.sort();
+        scopes.dedup();
+
+        let token = sqlx::query_as!(
+            ApiToken,
+            r#"INSERT INTO api_tokens (id, "user", name, hash, hint, scopes, created, created_by, expires)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, "user", name, hint, scopes, created, created_by, expires, last_used, revoked"#,
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            user,
+            details.name.trim(),
+            hash(&secret),
+            &secret[..PREFIX.len() + 6],
+            &scopes,
+            Utc::now(),
+            creator.id,
+            details.expires,
+        )
+        .fetch_one(&state.db)
+        .await?;
+
+        log::record(
+            State(state),
+            creator.id,
+            token.id,
+            "api_token",
+            None,
+            Some(&token),
+            "API token created",
+        )
+        .await?;
+
+        Ok(CreatedApiToken { token, secret })
+    }
+
+    pub async fn get(State(state): State<AppState>, id: Uuid) -> Result<ApiToken> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"SELECT id, "user", name, hint, scopes, created, created_by, expires, last_used, revoked FROM api_tokens WHERE id = $1"#,
+            id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // newest first, including expired and revoked tokens
+    pub async fn list_for_user(State(state): State<AppState>, user: Uuid) -> Result<Vec<ApiToken>> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"SELECT id, "user", name, hint, scopes, created, created_by, expires, last_used, revoked FROM api_tokens WHERE "user" = $1 ORDER BY created DESC"#,
+            user
+        )
+        .fetch_all(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // the token matching a bearer secret, as long as it hasn't expired or been revoked, and
+    // whoever made it (and the service account it's for) is still active
+    pub async fn authenticate(State(state): State<AppState>, secret: &str) -> Result<ApiToken> {
+        // keep this in sync with `get_valid`
+        let token = sqlx::query_as!(
+            ApiToken,
+            r#"UPDATE api_tokens SET last_used = $2
+            WHERE hash = $1 AND revoked IS NULL AND expires > $2
+                AND EXISTS (SELECT 1 FROM users WHERE id = api_tokens.created_by AND enabled)
+                AND NOT EXISTS (SELECT 1 FROM users account JOIN users creator ON creator.id = account.created_by
+                    WHERE account.id = api_tokens."user" AND NOT creator.enabled)
+            RETURNING id, "user", name, hint, scopes, created, created_by, expires, last_used, revoked"#,
+            hash(secret),
+            Utc::now(),
+        )
+        .fetch_one(&state.db)
+        .await?;
+        Ok(token)
+    }
+
+    // the token if `authenticate` would still accept it
+    pub async fn get_valid(State(state): State<AppState>, id: Uuid) -> Result<ApiToken> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"SELECT id, "user", name, hint, scopes, created, created_by, expires, last_used, revoked FROM api_tokens
+            WHERE id = $1 AND revoked IS NULL AND expires > $2
+                AND EXISTS (SELECT 1 FROM users WHERE id = api_tokens.created_by AND enabled)
+                AND NOT EXISTS (SELECT 1 FROM users account JOIN users creator ON creator.id = account.created_by
+                    WHERE account.id = api_tokens."user" AND NOT creator.enabled)"#,
+            id,
+            Utc::now(),
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(Error::from)
+    }
+
+    // `path` is relative to /api
+    pub fn allows(&self, method: &Method, path: &str) -> bool {
+        if TOKEN_MANAGEMENT_PATHS.iter().any(|p| path.starts_with(p)) {
+            return false;
+        }
+        let has = |scope: &str| self.scopes.iter().any(|s| s == scope);
+        if path.starts_with("/admin/") {
+            return has(ADMIN);
+        }
+        if method == Method::GET || method == Method::HEAD {
+            return has(READ) || has(WRITE);
+        }
+        has(WRITE)
+    }
+
+    pub async fn revoke(
+        &mut self,
+        State(state): State<AppState>,
+        Extension(user): Extension<User>,
+    ) -> Result<()> {
+        if self.revoked.is_some() {
+            return Ok(());
+        }
+
+        let previous = self.clone();
+        let revoked = Utc::now();
+        sqlx::query!(
+            "UPDATE api_tokens SET revoked = $2 WHERE id = $1",
+            self.id,
+            revoked,
+        )
+        .execute(&state.db)
+        .await?;
+        self.revoked = Some(revoked);
+
+        log::record(
+            State(state),
+            user.id,
+            self.id,
+            "api_token",
+            Some(&previous),
+            Some(self),
+            "API token revoked",
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(scopes: &[&str]) -> ApiToken {
+        ApiToken {
+            id: Uuid::nil(),
+            user: Uuid::nil(),
+            name: "test".to_string(),
+            hint: "intricase_000000".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            created: Utc::now(),
+            created_by: Uuid::nil(),
+            expires: Utc::now() + Duration::days(1),
+            last_used: None,
+            revoked: None,
+        }
+    }
+
+    #[test]
+    fn never_allows_token_management() {
+        let token = token(&SCOPES);
+        for method in [Method::GET, Method::POST, Method::DELETE] {
+            assert!(!token.allows(&method, "/users/me/tokens"));
+            assert!(!token.allows(&method, "/users/me/tokens/0190b1a2"));
+            assert!(!token.allows(&method, "/admin/service-accounts"));
+            assert!(!token.allows(&method, "/admin/service-accounts/0190b1a2/tokens"));
+        }
+    }
+
+    #[test]
+    fn admin_paths_need_the_admin_scope() {
+        assert!(token(&[ADMIN]).allows(&Method::GET, "/admin/users"));
+        assert!(token(&[ADMIN]).allows(&Method::POST, "/admin/investigations/create"));
+        assert!(!token(&[READ, WRITE]).allows(&Method::GET, "/admin/users"));
+        assert!(!token(&[WRITE]).allows(&Method::POST, "/admin/investigations/create"));
+        // the admin scope doesn't extend to anything outside /admin
+        assert!(!token(&[ADMIN]).allows(&Method::GET, "/investigations"));
+    }
+
+    #[test]
+    fn read_only_tokens_can_only_read() {
+        let token = token(&[READ]);
+        assert!(token.allows(&Method::GET, "/investigations"));
+        assert!(token.allows(&Method::HEAD, "/investigations"));
+        for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            assert!(!token.allows(&method, "/investigations"));
+        }
+    }
+
+    #[test]
+    fn write_tokens_can_read_and_write() {
+        let token = token(&[WRITE]);
+        for method in [Method::GET, Method::HEAD, Method::POST, Method::DELETE] {
+            assert!(token.allows(&method, "/investigations"));
+        }
+    }
+}
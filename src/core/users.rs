@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 use ts_rs::TS;
 use url::Url;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::core::{crypto, helpers};
@@ -23,6 +24,8 @@ impl std::fmt::Debug for User {
             .field("email", &self.email)
             .field("display_name", &self.display_name)
             .field("enabled", &self.enabled)
+            .field("service_account", &self.service_account)
+            .field("created_by", &self.created_by)
             .field("created", &self.created)
             .field("secret", &"[redacted]")
             .field("otp", &"[redacted]")
@@ -34,7 +37,7 @@ impl std::fmt::Debug for User {
 
 // we also skip serializing potentially-sensitive fields here to prevent accidental
 // exposure - including fields we wouldn't worry about showing up in debug logs
-#[derive(Clone, Serialize, Deserialize, FromRow, TS)]
+#[derive(Clone, Serialize, Deserialize, FromRow, TS, ToSchema)]
 #[ts(export)]
 pub struct User {
     pub id: Uuid,
@@ -42,6 +45,10 @@ pub struct User {
     display_name: Option<String>,
     enabled: bool,
     created: DateTime<Utc>,
+    // scripts and integrations, which only authenticate with API tokens
+    service_account: bool,
+    // who made a service account
+    created_by: Option<Uuid>,
     otp: Option<String>,
     #[serde(skip_serializing)]
     secret: Option<String>,
@@ -70,10 +77,18 @@ impl User {
         Ok(user)
     }
 
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
     pub fn is_active(&self) -> bool {
         self.enabled
     }
 
+    pub fn is_service_account(&self) -> bool {
+        self.service_account
+    }
+
     pub fn validate_otp(&self, submitted_otp: &str) -> bool {
         if let Some(otp) = &self.otp {
             if let Some(otp_date) = self.otp_date {
@@ -130,6 +145,32 @@ impl User {
         Ok(user)
     }
 
+    // service accounts never get a password or an invitation, and `.invalid` addresses can't
+    // receive mail, so nothing is ever sent to one
+    pub async fn create_service_account(db: &PgPool, creator: Uuid, name: &str) -> Result<User> {
+        let id = Uuid::now_v7();
+        let user = sqlx::query_as!(
+            User,
+            r#"
+        INSERT INTO users
+            ("id", "email", "display_name", "enabled", "created", "service_account", "created_by")
+        VALUES
+            ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+            id,
+            format!("{}@service-accounts.invalid", id),
+            name,
+            true,
+            Utc::now(),
+            true,
+            creator,
+        )
+        .fetch_one(db)
+        .await?;
+        Ok(user)
+    }
+
     pub async fn new_otp(&mut self, db: &PgPool) -> Result<String> {
         let otp = crypto::gen_otp();
         sqlx::query!(
@@ -223,3 +264,12 @@ pub async fn get_all(db: &PgPool) -> Result<Vec<User>, sqlx::Error> {
         .fetch_all(db)
         .await
 }
+
+pub async fn get_service_accounts(db: &PgPool) -> Result<Vec<User>, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        "SELECT * FROM users WHERE service_account ORDER BY display_name ASC"
+    )
+    .fetch_all(db)
+    .await
+}
@@ -1,9 +1,15 @@
 use axum::{extract::FromRef, Router};
 use axum_extra::extract::cookie::Key;
 use log::warn;
+use object_store::ObjectStore;
 use sqlx::PgPool;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
 use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
 use tracing::*;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 /// The `api` module takes HTTP requests and forwards them to the underlying functions in `core`,
 /// translating the results to HTTP responses and response codes. It also handles reading and
@@ -19,6 +25,10 @@ mod core;
 pub struct AppState {
     db: PgPool,
     key: Key,
+    storage: Arc<dyn ObjectStore>,
+    fonts: Arc<core::flyers::Fonts>,
+    // changes from the database, for the clients connected to `/api/events`
+    events: broadcast::Sender<core::events::Event>,
 }
 
 #[tokio::main]
@@ -37,7 +47,23 @@ async fn main() {
     // TODO: complain if the key is too short - or if the shannon entropy is too low?
     let key = Key::from(std::env::var("SIGNING_KEY").unwrap().as_bytes());
 
-    let state = AppState { db, key };
+    let storage = core::storage::from_env().unwrap();
+
+    let fonts = core::flyers::Fonts::load().unwrap();
+
+    // slow clients miss the oldest events once this many are waiting for them
+    let (events, _) = broadcast::channel(1024);
+
+    let state = AppState {
+        db,
+        key,
+        storage,
+        fonts,
+        events,
+    };
+
+    tokio::spawn(core::notifications::run_overdue_digest(state.clone()));
+    tokio::spawn(core::events::run_relay(state.clone()));
 
     let env = std::env::var("ENV").unwrap();
 
@@ -55,6 +81,7 @@ async fn main() {
         // but for now this keeps things contained/encapsulated and keeps the module structure
         // and the api structure similar to one-another
         .nest("/api", api::router(state.clone()))
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", api::ApiDoc::openapi()))
         .with_state(state)
         // TODO: now that we've nested the API routes under /api we probably don't need to do this
         // as a fallback if there's a more appropriate or performant method
@@ -66,5 +93,11 @@ async fn main() {
     let listener = tokio::net::TcpListener::bind(std::env::var("LISTEN_ADDRESS").unwrap())
         .await
         .unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // tip submissions are rate limited by address, so handlers need to know who's connecting
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ActionItem = { id: string, created: string, creator: string, pretty_id: string, summary: string, details: string | null, outcome: string | null, assignee: string | null, question: string, status: string, assigned: string | null, resolved: string | null, };
\ No newline at end of file
+export type ActionItem = { id: string, created: string, creator: string, pretty_id: string, summary: string, details: string | null, outcome: string | null, assignee: string | null, question: string, status: string, assigned: string | null, resolved: string | null, due_date: string | null, priority: string, estimated_hours: number | null, };
\ No newline at end of file
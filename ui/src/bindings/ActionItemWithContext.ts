@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionItem } from "./ActionItem";
+
+export type ActionItemWithContext = { action_item: ActionItem, question_pretty_id: string, question_summary: string, investigation: string, investigation_name: string, };
\ No newline at end of file
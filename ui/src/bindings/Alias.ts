@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Alias = { id: string, created: string, creator: string, investigation: string, kind: string, first_name: string | null, middle_name: string | null, last_name: string | null, date_of_birth: string | null, notes: string | null, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ApiToken = { id: string, user: string, name: string, hint: string, scopes: Array<string>, created: string, created_by: string, expires: string, last_used: string | null, revoked: string | null, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Attachment = { id: string, created: string, uploader: string, investigation: string, target: string, target_type: string, filename: string, mime_type: string, size: bigint, sha256: string, storage_key: string, };
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type AttachmentMetadata = { attachment: string, captured: string | null, latitude: number | null, longitude: number | null, altitude: number | null, camera_make: string | null, camera_model: string | null, exif: JsonValue, xmp: string | null, stripped_key: string | null, stripped_sha256: string | null, stripped_mime_type: string | null, thumbnail_key: string | null, thumbnail_sha256: string | null, };
\ No newline at end of file
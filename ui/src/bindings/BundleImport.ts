@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicateCandidate } from "./DuplicateCandidate";
+
+export type BundleImport = { id: string, unmatched_users: Array<string>, possible_duplicates: Array<DuplicateCandidate>, };
\ No newline at end of file
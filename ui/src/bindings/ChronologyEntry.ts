@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChronologyKind } from "./ChronologyKind";
+
+export type ChronologyEntry = { kind: ChronologyKind, target: string, earliest: string, latest: string, description: string, location: string | null, latitude: number | null, longitude: number | null, source: string | null, confidence: string | null, overlaps_with: Array<string>, conflicts_with: Array<string>, };
\ No newline at end of file
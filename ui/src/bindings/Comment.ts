@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Comment = { id: string, created: string, author: string, investigation: string, target: string, target_type: string, parent: string | null, body: string, edited: string | null, };
\ No newline at end of file
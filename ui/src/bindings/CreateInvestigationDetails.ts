@@ -1,4 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AliasDetails } from "./AliasDetails";
 import type { CreateQuestionDetails } from "./CreateQuestionDetails";
+import type { ProfileDetails } from "./ProfileDetails";
 
-export type CreateInvestigationDetails = { internal_id: string | null, first_name: string, middle_name: string | null, last_name: string, date_of_birth: string, namus_id: string | null, missing_since: string, synopsis: string, questions: Array<CreateQuestionDetails>, };
\ No newline at end of file
+export type CreateInvestigationDetails = { internal_id: string | null, first_name: string, middle_name: string | null, last_name: string, date_of_birth: string, namus_id: string | null, missing_since: string, synopsis: string, questions: Array<CreateQuestionDetails>, aliases: Array<AliasDetails>, profile: ProfileDetails | null, templates: Array<string>, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DuplicateCandidate = { id: string, first_name: string, middle_name: string | null, last_name: string, date_of_birth: string, missing_since: string, matched_alias: string | null, name_similarity: number, phonetic_match: boolean, date_of_birth_days_apart: number, score: number, };
\ No newline at end of file
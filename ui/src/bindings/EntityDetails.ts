@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EntityDetails = { kind: string, name: string, email: string | null, phone: string | null, address: string | null, website: string | null, notes: string | null, };
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GraphEdge } from "./GraphEdge";
+import type { GraphNode } from "./GraphNode";
+
+export type EntityGraph = { nodes: Array<GraphNode>, edges: Array<GraphEdge>, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EntityInvestigation = { investigation: string, investigation_name: string, role: string, notes: string | null, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Event = { target: string, target_type: string, investigation: string | null, action: string, };
\ No newline at end of file
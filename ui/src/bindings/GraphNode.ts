@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GraphNodeKind } from "./GraphNodeKind";
+
+export type GraphNode = { id: string, kind: GraphNodeKind, label: string, role: string | null, };
\ No newline at end of file
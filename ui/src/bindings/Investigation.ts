@@ -1,4 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Alias } from "./Alias";
+import type { Profile } from "./Profile";
 import type { Question } from "./Question";
 
-export type Investigation = { id: string, created: string, creator: string, internal_id: string | null, first_name: string, middle_name: string | null, last_name: string, date_of_birth: string, namus_id: string | null, missing_since: string, synopsis: string, questions: { [key: string]: Question } | null, };
\ No newline at end of file
+export type Investigation = { id: string, created: string, creator: string, internal_id: string | null, first_name: string, middle_name: string | null, last_name: string, date_of_birth: string, namus_id: string | null, missing_since: string, synopsis: string, merged_into: string | null, status: string, status_changed: string, closure_outcome: string | null, closure_notes: string | null, questions: { [key: string]: Question } | null, aliases: Array<Alias> | null, profile: Profile | null, };
\ No newline at end of file
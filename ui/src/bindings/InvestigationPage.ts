@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Investigation } from "./Investigation";
+
+export type InvestigationPage = { investigations: Array<Investigation>, next_cursor: string | null, };
\ No newline at end of file
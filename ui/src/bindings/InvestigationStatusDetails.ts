@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type InvestigationStatusDetails = { status: string, outcome: string | null, notes: string | null, override_open_action_items: boolean, };
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Entity } from "./Entity";
+
+export type LinkedEntity = { entity: Entity, role: string, notes: string | null, };
\ No newline at end of file
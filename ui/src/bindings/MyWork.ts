@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionItemWithContext } from "./ActionItemWithContext";
+import type { WorkCounts } from "./WorkCounts";
+
+export type MyWork = { counts: WorkCounts, by_status: { [key: string]: Array<ActionItemWithContext> }, unassigned_in_my_cases: Array<ActionItemWithContext>, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NotificationPreferences = { user: string, action_item_assigned: boolean, question_status_changed: boolean, followed_case_comment: boolean, overdue_digest: boolean, mentioned: boolean, };
\ No newline at end of file
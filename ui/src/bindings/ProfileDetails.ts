@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DistinguishingMark } from "./DistinguishingMark";
+import type { Identifier } from "./Identifier";
+import type { Vehicle } from "./Vehicle";
+
+export type ProfileDetails = { sex: string | null, race: string | null, height_min_cm: number | null, height_max_cm: number | null, weight_min_kg: number | null, weight_max_kg: number | null, hair_color: string | null, eye_color: string | null, distinguishing_marks: Array<DistinguishingMark>, clothing: string | null, vehicles: Array<Vehicle>, medical_conditions: string | null, identifiers: Array<Identifier>, last_known_address: string | null, last_known_city: string | null, last_known_county: string | null, last_known_state: string | null, last_known_country: string | null, last_known_latitude: number | null, last_known_longitude: number | null, };
\ No newline at end of file
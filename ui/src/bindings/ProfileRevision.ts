@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Profile } from "./Profile";
+
+export type ProfileRevision = { id: string, investigation: string, version: number, created: string, data: Profile, };
\ No newline at end of file
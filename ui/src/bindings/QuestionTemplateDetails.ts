@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateQuestion } from "./TemplateQuestion";
+
+export type QuestionTemplateDetails = { name: string, description: string | null, questions: Array<TemplateQuestion>, };
\ No newline at end of file
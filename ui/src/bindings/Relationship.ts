@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Relationship = { id: string, created: string, creator: string, investigation: string, from_entity: string, to_entity: string | null, kind: string, description: string | null, };
\ No newline at end of file
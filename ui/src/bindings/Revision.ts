@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FieldChange } from "./FieldChange";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type Revision = { id: string, target: string, target_type: string, version: number, created: string, created_by: string, data: JsonValue, changes: Array<FieldChange>, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SearchResult = { target: string, target_type: string, investigation: string, investigation_name: string, title: string, snippet: string, rank: number, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type StatusChange = { id: string, investigation: string, changed: string, changed_by: string, previous_status: string, status: string, outcome: string | null, notes: string | null, open_action_items: number, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TemplateActionItem = { summary: string, details: string | null, priority: string | null, estimated_hours: number | null, due_in_days: number | null, };
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateActionItem } from "./TemplateActionItem";
+
+export type TemplateQuestion = { summary: string, details: string | null, action_items: Array<TemplateActionItem>, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TimelineEvent = { id: string, created: string, creator: string, investigation: string, earliest: string, latest: string, location: string | null, latitude: number | null, longitude: number | null, description: string, source: string | null, confidence: string, question: string | null, action_item: string | null, };
\ No newline at end of file
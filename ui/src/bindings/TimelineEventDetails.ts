@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TimelineEventDetails = { earliest: string, latest: string | null, location: string | null, latitude: number | null, longitude: number | null, description: string, source: string | null, confidence: string | null, question: string | null, action_item: string | null, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Tip = { id: string, created: string, investigation: string, status: string, body: string, contact_name: string | null, contact_email: string | null, contact_phone: string | null, submitter: string, reviewed_by: string | null, reviewed: string | null, converted_target: string | null, converted_target_type: string | null, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TipConversion = { "to": "question", pretty_id: string, summary: string, } | { "to": "action_item", question: string, summary: string, };
\ No newline at end of file
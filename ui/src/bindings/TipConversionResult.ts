@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Tip } from "./Tip";
+
+export type TipConversionResult = { tip: Tip, target: string, };
\ No newline at end of file
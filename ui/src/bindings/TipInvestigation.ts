@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TipInvestigation = { id: string, first_name: string, last_name: string, missing_since: string, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Vehicle = { year: number | null, make: string | null, model: string | null, color: string | null, license_plate: string | null, license_state: string | null, notes: string | null, };
\ No newline at end of file
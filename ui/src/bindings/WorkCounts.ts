@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WorkCounts = { assigned: number, overdue: number, unassigned_in_my_cases: number, by_status: { [key: string]: number }, };
\ No newline at end of file
@@ -63,6 +63,36 @@ const highlightZodErrors = (f: HTMLFormElement, e: ZodError) => {
 	});
 };
 
+// investigation statuses in lifecycle order; the resolved and closed ones need an outcome and notes
+const investigationStatuses = [
+	{ value: 'open', name: 'Open' },
+	{ value: 'active', name: 'Active' },
+	{ value: 'cold', name: 'Cold' },
+	{ value: 'suspended', name: 'Suspended' },
+	{ value: 'resolved_located_safe', name: 'Resolved (located safe)' },
+	{ value: 'resolved_deceased', name: 'Resolved (deceased)' },
+	{ value: 'closed', name: 'Closed' },
+];
+const closedStatuses = ['resolved_located_safe', 'resolved_deceased', 'closed'];
+
+const statusName = (status: string) =>
+	investigationStatuses.find((s) => s.value === status)?.name ?? status;
+
+// keep in sync with CLOSURE_OUTCOMES in src/core/investigations.rs
+const closureOutcomes = [
+	{ value: 'located_by_law_enforcement', name: 'Located by law enforcement' },
+	{ value: 'located_by_family', name: 'Located by family' },
+	{ value: 'located_by_volunteers', name: 'Located by volunteers' },
+	{ value: 'returned_voluntarily', name: 'Returned voluntarily' },
+	{ value: 'remains_identified', name: 'Remains identified' },
+	{ value: 'referred_elsewhere', name: 'Referred elsewhere' },
+	{ value: 'insufficient_information', name: 'Insufficient information' },
+	{ value: 'other', name: 'Other' },
+];
+
+const outcomeName = (outcome: string) =>
+	closureOutcomes.find((o) => o.value === outcome)?.name ?? outcome;
+
 // parses out the value of a single entry in a string of cookies
 // returns undefined if the named value does not exist
 const cookieValue = (s: string): string | undefined => {
@@ -75,4 +105,16 @@ const cookieValue = (s: string): string | undefined => {
 	return decodeURIComponent(val);
 };
 
-export { cookieValue, handleSubmitJson, highlightZodErrors, nc, objectFromForm, resetZodErrors };
+export {
+	closedStatuses,
+	closureOutcomes,
+	cookieValue,
+	handleSubmitJson,
+	highlightZodErrors,
+	investigationStatuses,
+	nc,
+	objectFromForm,
+	outcomeName,
+	resetZodErrors,
+	statusName,
+};
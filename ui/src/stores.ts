@@ -1,7 +1,16 @@
-import { type Writable, writable } from 'svelte/store';
+import { type Readable, type Writable, readable, writable } from 'svelte/store';
 
+import type { Event as ChangeEvent } from './bindings/Event';
 import { type User } from './typedefs';
 
 let userDetails: Writable<undefined | User> = writable(undefined);
 
-export { userDetails };
+// the latest change pushed by the server; the stream is opened by the first subscriber and closed
+// when the last one goes away
+const changes: Readable<undefined | ChangeEvent> = readable(undefined, (set) => {
+	const source = new EventSource('/api/events');
+	source.addEventListener('change', (e) => set(JSON.parse(e.data)));
+	return () => source.close();
+});
+
+export { changes, userDetails };
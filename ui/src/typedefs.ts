@@ -11,6 +11,10 @@ type Investigation = {
 	missing_since: string;
 	synopsis: string;
 	created: string;
+	status: string;
+	status_changed: string;
+	closure_outcome?: string;
+	closure_notes?: string;
 };
 
 type User = {